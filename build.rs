@@ -0,0 +1,5 @@
+fn main() {
+    // Node loads the library itself, napi-build sets up the linker to allow for that
+    #[cfg(feature = "node")]
+    napi_build::setup();
+}
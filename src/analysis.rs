@@ -0,0 +1,79 @@
+mod autoscroll;
+mod clear_time;
+mod collision;
+mod difficulty;
+mod farms;
+mod grid;
+mod items;
+mod reachability;
+mod softlock;
+mod timeline;
+mod tracks;
+mod traps;
+mod vines;
+
+pub use autoscroll::{autoscroll_safety, AutoscrollIssue, AutoscrollIssueKind};
+pub use clear_time::{estimated_clear_time, ClearTimeEstimate};
+pub use collision::{Collision, CollisionMap};
+pub use difficulty::difficulty_score;
+pub use farms::{life_farms, LifeFarm, LifeFarmKind};
+pub use grid::{Cell, OccupancyGrid};
+pub use items::{coins, course_coins, items, CoinCount, CourseCoins, Item, ItemCensus};
+pub use reachability::{reachability, Gap, Reachability};
+pub use softlock::{softlocks, Confidence, Softlock, SoftlockKind};
+pub use timeline::{sound_timeline, SoundTimeline, TimelineEntry};
+pub use tracks::{rails, Attachment, Direction, Rail};
+pub use traps::{hidden_block_traps, HiddenBlockTrap, TrapKind};
+pub use vines::{vine_growth, VineGrowth};
+
+// Blocks visible on screen at once
+const SCREEN_WIDTH: u32 = 24;
+// Blocks of start ground before the player can start moving right
+const START_AREA: u32 = 7;
+// Blocks of goal ground after the goal pole
+const GOAL_AREA: u32 = 10;
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use crate::{
+        date::CreationDate,
+        level::{AutoScroll, CourseTheme, GameMode, Level},
+        objects::{Object, ObjectType},
+    };
+
+    pub fn level(width: u32, objects: Vec<Object>) -> Level {
+        Level::new(
+            0xB,
+            CreationDate::default().to_creation_time().unwrap(),
+            String::new(),
+            GameMode::SuperMarioBros,
+            CourseTheme::Overworld,
+            300,
+            AutoScroll::None,
+            0,
+            width * 16,
+            [0; 0x60],
+            objects,
+            Vec::new(),
+        )
+    }
+
+    pub fn object(object_type: ObjectType, x: u32, y: i16, width: i8, height: i8) -> Object {
+        Object::new(
+            x * 10,
+            0,
+            y * 10,
+            width,
+            height,
+            0,
+            0,
+            0,
+            object_type.into(),
+            -1,
+            -1,
+            -1,
+            -1,
+            -1,
+        )
+    }
+}
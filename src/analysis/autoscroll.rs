@@ -0,0 +1,132 @@
+use std::collections::HashMap;
+
+use crate::{level::Level, objects::ObjectType};
+
+use super::{OccupancyGrid, SCREEN_WIDTH};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AutoscrollIssueKind {
+    // A warp exit that has already scrolled off screen by the time its entrance can be used
+    BehindScrollLine,
+    // A wall spanning the whole course height, the camera will crush the player against it
+    DeadEnd,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AutoscrollIssue {
+    pub kind: AutoscrollIssueKind,
+    pub x: i32,
+    pub y: i32,
+    // Seconds into the level at which the left edge of the camera reaches the issue
+    pub seconds: f32,
+}
+
+// Walks the scroll timeline of an autoscrolling level, returns nothing for levels that don't scroll
+pub fn autoscroll_safety(level: &Level) -> Vec<AutoscrollIssue> {
+    let speed = match level.auto_scroll.blocks_per_second() {
+        Some(speed) => speed,
+        None => return Vec::new(),
+    };
+
+    let mut issues = Vec::new();
+    let grid = OccupancyGrid::from_level(level);
+
+    let mut in_wall = false;
+    for x in 0..grid.width() as i32 {
+        let is_wall = grid.is_wall(x);
+        if is_wall && !in_wall {
+            issues.push(AutoscrollIssue {
+                kind: AutoscrollIssueKind::DeadEnd,
+                x,
+                y: 0,
+                seconds: x as f32 / speed,
+            });
+        }
+        in_wall = is_wall;
+    }
+
+    // Doors and pipes sharing a link ID warp between each other
+    let mut links: HashMap<i16, Vec<(i32, i32)>> = HashMap::new();
+    for object in &level.objects {
+        if object.link_id >= 0
+            && matches!(
+                object.get_object_type(),
+                Some(ObjectType::Door) | Some(ObjectType::Pipe)
+            )
+        {
+            let (x, y, _, _) = object.get_block_bounds();
+            links.entry(object.link_id).or_default().push((x, y));
+        }
+    }
+
+    for warps in links.values() {
+        for &(entrance_x, _) in warps {
+            // The camera can't be further left than this while the entrance is still on screen
+            let scroll_line = entrance_x - SCREEN_WIDTH as i32 + 1;
+            for &(exit_x, exit_y) in warps {
+                if exit_x < scroll_line {
+                    issues.push(AutoscrollIssue {
+                        kind: AutoscrollIssueKind::BehindScrollLine,
+                        x: exit_x,
+                        y: exit_y,
+                        seconds: scroll_line.max(0) as f32 / speed,
+                    });
+                }
+            }
+        }
+    }
+
+    issues.sort_by(|a, b| a.seconds.total_cmp(&b.seconds));
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        analysis::tests::{level, object},
+        level::AutoScroll,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_autoscroll_safety() {
+        let mut door = object(ObjectType::Door, 40, 1, 1, 2);
+        door.link_id = 0;
+        let mut exit = object(ObjectType::Door, 5, 1, 1, 2);
+        exit.link_id = 0;
+        let mut level = level(
+            60,
+            vec![
+                object(ObjectType::Ground, 0, 0, 60, 1),
+                object(ObjectType::HardBlock, 30, 0, 2, 27),
+                door,
+                exit,
+            ],
+        );
+        assert!(autoscroll_safety(&level).is_empty());
+
+        level.auto_scroll = AutoScroll::Medium;
+        assert_eq!(
+            autoscroll_safety(&level),
+            vec![
+                AutoscrollIssue {
+                    kind: AutoscrollIssueKind::BehindScrollLine,
+                    x: 5,
+                    y: 1,
+                    seconds: 17.0 / 4.0,
+                },
+                AutoscrollIssue {
+                    kind: AutoscrollIssueKind::DeadEnd,
+                    x: 30,
+                    y: 0,
+                    seconds: 30.0 / 4.0,
+                },
+            ]
+        );
+
+        level.objects.truncate(1);
+        level.width = u32::MAX;
+        assert!(autoscroll_safety(&level).is_empty());
+    }
+}
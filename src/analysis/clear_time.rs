@@ -0,0 +1,56 @@
+use crate::level::Level;
+
+use super::{GOAL_AREA, SCREEN_WIDTH, START_AREA};
+
+// Full run speed in blocks per second, close enough across all four game modes
+pub(super) const RUN_SPEED: f32 = 9.0;
+// A time limit below the estimate times this margin is flagged as too tight
+const TIGHT_MARGIN: f32 = 1.25;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ClearTimeEstimate {
+    // Blocks between the start area and the goal pole
+    pub distance: u32,
+    // Estimated fastest possible clear, in seconds
+    pub seconds: f32,
+    pub time_limit: u16,
+    pub tight: bool,
+}
+
+pub fn estimated_clear_time(level: &Level) -> ClearTimeEstimate {
+    let distance = level.block_width().saturating_sub(START_AREA + GOAL_AREA);
+
+    let seconds = match level.auto_scroll.blocks_per_second() {
+        // The goal is reachable once it scrolls into view, so the scroll speed bounds the clear time
+        Some(speed) => distance.saturating_sub(SCREEN_WIDTH) as f32 / speed,
+        None => distance as f32 / RUN_SPEED,
+    };
+
+    ClearTimeEstimate {
+        distance,
+        seconds,
+        time_limit: level.time_limit,
+        tight: (level.time_limit as f32) < seconds * TIGHT_MARGIN,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{analysis::tests::level, level::AutoScroll};
+
+    use super::*;
+
+    #[test]
+    fn test_estimated_clear_time() {
+        let mut level = level(240, Vec::new());
+        let estimate = estimated_clear_time(&level);
+        assert_eq!(estimate.distance, 223);
+        assert!(!estimate.tight);
+
+        level.time_limit = 30;
+        level.auto_scroll = AutoScroll::Slow;
+        let estimate = estimated_clear_time(&level);
+        assert!(estimate.seconds > 90.0);
+        assert!(estimate.tight);
+    }
+}
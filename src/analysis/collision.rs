@@ -0,0 +1,126 @@
+use crate::level::Level;
+
+use super::{Cell, OccupancyGrid};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Collision {
+    #[default]
+    Passable,
+    // Can be stood on but passed through from below
+    Semisolid,
+    Solid,
+    // Hurts on touch, whether or not it can be stood on. Enemies move and aren't included
+    Harmful,
+}
+
+// Static collision for pathfinding and simulators, one cell per block. Row 0 is the bottom of the
+// course.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CollisionMap {
+    width: u32,
+    height: u32,
+    cells: Vec<Collision>,
+}
+
+impl CollisionMap {
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    // Anything outside of the course is passable
+    pub fn get(&self, x: i32, y: i32) -> Collision {
+        self.index(x, y)
+            .map_or(Collision::Passable, |index| self.cells[index])
+    }
+
+    // Rows from the bottom up
+    pub fn rows(&self) -> impl Iterator<Item = &[Collision]> {
+        self.cells.chunks(self.width.max(1) as usize)
+    }
+
+    fn index(&self, x: i32, y: i32) -> Option<usize> {
+        if x < 0 || y < 0 || x >= self.width as i32 || y >= self.height as i32 {
+            return None;
+        }
+
+        Some((y as u32 * self.width + x as u32) as usize)
+    }
+}
+
+impl Level {
+    pub fn collision_map(&self) -> CollisionMap {
+        let grid = OccupancyGrid::from_level(self);
+        let (width, height) = (grid.width(), grid.height());
+        let mut map = CollisionMap {
+            width,
+            height,
+            cells: (0..height as i32)
+                .flat_map(|y| (0..width as i32).map(move |x| (x, y)))
+                .map(|(x, y)| match grid.get(x, y) {
+                    Cell::Empty => Collision::Passable,
+                    Cell::Semisolid => Collision::Semisolid,
+                    Cell::Solid => Collision::Solid,
+                })
+                .collect(),
+        };
+
+        for object in &self.objects {
+            if !object
+                .get_object_type()
+                .is_some_and(|object_type| object_type.is_hazard())
+            {
+                continue;
+            }
+
+            let (left, bottom, object_width, object_height) = object.get_block_bounds();
+            for x in left..left + object_width as i32 {
+                for y in bottom..bottom + object_height as i32 {
+                    if let Some(index) = map.index(x, y) {
+                        map.cells[index] = Collision::Harmful;
+                    }
+                }
+            }
+        }
+
+        map
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        analysis::tests::{level, object},
+        objects::ObjectType,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_collision_map() {
+        let mut objects: Vec<_> = (0..24)
+            .map(|x| object(ObjectType::Ground, x, 0, 1, 1))
+            .collect();
+        objects.push(object(ObjectType::SemisolidPlatform, 4, 3, 3, 1));
+        objects.push(object(ObjectType::Spikes, 10, 1, 2, 1));
+        objects.push(object(ObjectType::Goomba, 15, 1, 1, 1));
+
+        let map = level(24, objects).collision_map();
+        assert_eq!((map.width(), map.height()), (24, 27));
+        assert_eq!(map.get(0, 0), Collision::Solid);
+        assert_eq!(map.get(5, 3), Collision::Semisolid);
+        assert_eq!(map.get(11, 1), Collision::Harmful);
+        assert_eq!(map.get(15, 1), Collision::Passable);
+        assert_eq!(map.get(-1, 0), Collision::Passable);
+        assert_eq!(map.rows().count(), 27);
+        assert!(map
+            .rows()
+            .next()
+            .unwrap()
+            .iter()
+            .all(|&cell| cell == Collision::Solid));
+    }
+}
@@ -0,0 +1,62 @@
+use crate::{level::Level, objects::ObjectType};
+
+use super::{estimated_clear_time, reachability, GOAL_AREA, SCREEN_WIDTH, START_AREA};
+
+// Hazards (see ObjectType::is_hazard) count for half an enemy, they don't move towards the player
+const HAZARD_WEIGHT: f32 = 0.5;
+// Added when the time limit leaves little slack
+const TIGHT_TIME_PENALTY: f32 = 2.0;
+// Added when running and jumping alone can't reach the goal
+const UNREACHABLE_PENALTY: f32 = 1.0;
+
+// A rough score for sorting courses: enemies and hazards per screen, plus penalties for a tight
+// time limit and jumps that need more than running. Higher is harder, there's no upper bound.
+pub fn difficulty_score(level: &Level) -> f32 {
+    let threats: f32 = level
+        .objects
+        .iter()
+        .filter_map(|object| ObjectType::try_from(object.object_type).ok())
+        .map(|object_type| {
+            if object_type.is_enemy() {
+                1.0
+            } else if object_type.is_hazard() {
+                HAZARD_WEIGHT
+            } else {
+                0.0
+            }
+        })
+        .sum();
+    let screens = level
+        .block_width()
+        .saturating_sub(START_AREA + GOAL_AREA)
+        .max(SCREEN_WIDTH) as f32
+        / SCREEN_WIDTH as f32;
+
+    let mut score = threats / screens;
+    if estimated_clear_time(level).tight {
+        score += TIGHT_TIME_PENALTY;
+    }
+    if !reachability(level).goal_reachable {
+        score += UNREACHABLE_PENALTY;
+    }
+    score
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analysis::tests::{level, object};
+
+    use super::*;
+
+    #[test]
+    fn test_difficulty_score() {
+        let ground = |x| object(ObjectType::Ground, x, 0, 1, 1);
+        let mut objects: Vec<_> = (0..48).map(ground).collect();
+        let easy = difficulty_score(&level(48, objects.clone()));
+
+        objects.extend((10..20).map(|x| object(ObjectType::Goomba, x, 1, 1, 1)));
+        objects.push(object(ObjectType::FireBar, 25, 3, 1, 1));
+        let hard = difficulty_score(&level(48, objects));
+        assert!(hard > easy + 5.0);
+    }
+}
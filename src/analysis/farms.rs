@@ -0,0 +1,167 @@
+use crate::{level::Level, objects::ObjectType};
+
+use super::{Confidence, OccupancyGrid, SCREEN_WIDTH};
+
+// Steps down from a shell needed before it counts as a staircase
+const STAIRCASE_STEPS: i32 = 3;
+// Enemies within a screen that a single shell could chain into a 1-up
+const CHAIN_LENGTH: usize = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LifeFarmKind {
+    // A pipe or launcher spawning 1-ups forever
+    OneUpSpawner,
+    // A shelled enemy above steps of single blocks, for the shell jump trick
+    ShellStaircase,
+    // Enough enemies close together for one kicked shell to earn lives
+    EnemyChain,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LifeFarm {
+    pub kind: LifeFarmKind,
+    pub x: i32,
+    pub y: i32,
+    // Columns covered starting at x
+    pub width: u32,
+    pub confidence: Confidence,
+}
+
+// Candidate infinite life setups, going by object arrangement alone
+pub fn life_farms(level: &Level) -> Vec<LifeFarm> {
+    let grid = OccupancyGrid::from_level(level);
+    let mut farms = Vec::new();
+
+    for object in &level.objects {
+        let (x, y, width, _) = object.get_block_bounds();
+        let object_type = object.get_object_type();
+        let child_type = ObjectType::try_from(object.child_object_type).ok();
+
+        let spawner = matches!(
+            object_type,
+            Some(ObjectType::Pipe | ObjectType::BillBlaster | ObjectType::Cannon)
+        );
+        if spawner && child_type == Some(ObjectType::OneUp) {
+            farms.push(LifeFarm {
+                kind: LifeFarmKind::OneUpSpawner,
+                x,
+                y,
+                width,
+                confidence: Confidence::High,
+            });
+        }
+
+        if matches!(
+            object_type,
+            Some(ObjectType::Koopa | ObjectType::BuzzyBeetle)
+        ) {
+            // The surface the enemy starts on, then each column stepping down by one
+            let floor = |x: i32| (0..y).rev().find(|&floor| grid.is_surface(x, floor));
+            for direction in [-1, 1] {
+                let Some(top) = floor(x) else {
+                    continue;
+                };
+                let stairs = (1..=STAIRCASE_STEPS)
+                    .all(|step| floor(x + step * direction) == Some(top - step));
+                if stairs {
+                    let left = if direction < 0 {
+                        x - STAIRCASE_STEPS
+                    } else {
+                        x
+                    };
+                    farms.push(LifeFarm {
+                        kind: LifeFarmKind::ShellStaircase,
+                        x: left,
+                        y: top - STAIRCASE_STEPS,
+                        width: STAIRCASE_STEPS as u32 + 1,
+                        confidence: Confidence::Medium,
+                    });
+                }
+            }
+        }
+    }
+
+    farms.extend(enemy_chains(level));
+    farms
+}
+
+// Windows of a screen holding a shell and enough other enemies, merged where they overlap
+fn enemy_chains(level: &Level) -> Vec<LifeFarm> {
+    let mut enemies: Vec<(i32, i32, ObjectType)> = level
+        .objects
+        .iter()
+        .filter_map(|object| {
+            let object_type = object.get_object_type()?;
+            let (x, y, _, _) = object.get_block_bounds();
+            object_type.is_enemy().then_some((x, y, object_type))
+        })
+        .collect();
+    enemies.sort_by_key(|&(x, _, _)| x);
+
+    let mut chains: Vec<LifeFarm> = Vec::new();
+    for start in 0..enemies.len() {
+        let (x, y, _) = enemies[start];
+        let window: Vec<_> = enemies[start..]
+            .iter()
+            .take_while(|(other_x, _, _)| other_x - x < SCREEN_WIDTH as i32)
+            .collect();
+        let has_shell = window.iter().any(|(_, _, object_type)| {
+            matches!(object_type, ObjectType::Koopa | ObjectType::BuzzyBeetle)
+        });
+        if !has_shell || window.len() <= CHAIN_LENGTH {
+            continue;
+        }
+
+        let right = window.last().map_or(x, |(other_x, _, _)| *other_x);
+        match chains.last_mut() {
+            Some(chain) if x <= chain.x + chain.width as i32 => {
+                chain.width = (right - chain.x + 1) as u32;
+            }
+            _ => chains.push(LifeFarm {
+                kind: LifeFarmKind::EnemyChain,
+                x,
+                y,
+                width: (right - x + 1) as u32,
+                confidence: Confidence::Low,
+            }),
+        }
+    }
+
+    chains
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        analysis::tests::{level, object},
+        objects::Object,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_life_farms() {
+        let ground = |x| object(ObjectType::Ground, x, 0, 1, 1);
+        let mut objects: Vec<_> = (0..100).map(ground).collect();
+        // Steps at heights 4, 3, 2 and 1 from column 20
+        objects.extend(
+            (0..4).map(|step| object(ObjectType::HardBlock, 20 + step, 1, 1, 4 - step as i8)),
+        );
+        objects.push(object(ObjectType::Koopa, 20, 5, 1, 1));
+        objects.push(Object::pipe(40, 1, 3, Some(ObjectType::OneUp)));
+        objects.extend((60..70).map(|x| object(ObjectType::Goomba, x, 1, 1, 1)));
+        objects.push(object(ObjectType::Koopa, 70, 1, 1, 1));
+
+        let farms = life_farms(&level(100, objects));
+        let kinds: Vec<_> = farms.iter().map(|farm| (farm.kind, farm.x)).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                (LifeFarmKind::ShellStaircase, 20),
+                (LifeFarmKind::OneUpSpawner, 40),
+                (LifeFarmKind::EnemyChain, 60),
+            ]
+        );
+        assert_eq!(farms[2].width, 11);
+    }
+}
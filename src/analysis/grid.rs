@@ -0,0 +1,127 @@
+use crate::level::Level;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Cell {
+    #[default]
+    Empty,
+    // Can be stood on but passed through from below
+    Semisolid,
+    Solid,
+}
+
+// Static collision of a level, one cell per block. Row 0 is the bottom of the course.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OccupancyGrid {
+    width: u32,
+    height: u32,
+    cells: Vec<Cell>,
+}
+
+impl OccupancyGrid {
+    // A width past Level::MAX_BLOCK_WIDTH is cut off there rather than sizing the grid by it
+    pub fn from_level(level: &Level) -> OccupancyGrid {
+        let width = level.block_width().min(Level::MAX_BLOCK_WIDTH);
+        let height = level.block_height();
+        let mut grid = OccupancyGrid {
+            width,
+            height,
+            cells: vec![Cell::Empty; (width * height) as usize],
+        };
+
+        for object in &level.objects {
+            let cell = match object.get_object_type() {
+                Some(object_type) if object_type.is_solid() => Cell::Solid,
+                Some(object_type) if object_type.is_semisolid() => Cell::Semisolid,
+                _ => continue,
+            };
+
+            let (left, bottom, object_width, object_height) = object.get_block_bounds();
+            for x in left..left + object_width as i32 {
+                for y in bottom..bottom + object_height as i32 {
+                    if let Some(index) = grid.index(x, y) {
+                        // Solid always wins over a semisolid sharing the same tile
+                        if grid.cells[index] != Cell::Solid {
+                            grid.cells[index] = cell;
+                        }
+                    }
+                }
+            }
+        }
+
+        grid
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    // Anything outside of the course is empty
+    pub fn get(&self, x: i32, y: i32) -> Cell {
+        self.index(x, y)
+            .map_or(Cell::Empty, |index| self.cells[index])
+    }
+
+    pub fn is_standable(&self, x: i32, y: i32) -> bool {
+        self.get(x, y) != Cell::Empty
+    }
+
+    // A tile the player can stand on top of: standable with a free tile above it
+    pub fn is_surface(&self, x: i32, y: i32) -> bool {
+        self.is_standable(x, y) && self.get(x, y + 1) != Cell::Solid
+    }
+
+    // Solid from the bottom to the top of the course
+    pub fn is_wall(&self, x: i32) -> bool {
+        (0..self.height as i32).all(|y| self.get(x, y) == Cell::Solid)
+    }
+
+    fn index(&self, x: i32, y: i32) -> Option<usize> {
+        if x < 0 || y < 0 || x >= self.width as i32 || y >= self.height as i32 {
+            return None;
+        }
+
+        Some((y as u32 * self.width + x as u32) as usize)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        analysis::tests::{level, object},
+        objects::ObjectType,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_from_level() {
+        let mut level = level(
+            30,
+            vec![
+                object(ObjectType::Ground, 0, 0, 10, 1),
+                object(ObjectType::SemisolidPlatform, 12, 2, 4, 1),
+                object(ObjectType::HardBlock, 14, 2, 1, 1),
+                object(ObjectType::Goomba, 20, 1, 1, 1),
+            ],
+        );
+        let grid = OccupancyGrid::from_level(&level);
+        assert_eq!((grid.width(), grid.height()), (30, 27));
+        assert_eq!(grid.get(9, 0), Cell::Solid);
+        assert_eq!(grid.get(10, 0), Cell::Empty);
+        assert_eq!(grid.get(13, 2), Cell::Semisolid);
+        assert_eq!(grid.get(14, 2), Cell::Solid);
+        assert_eq!(grid.get(20, 1), Cell::Empty);
+        assert_eq!(grid.get(-1, 0), Cell::Empty);
+        assert!(grid.is_surface(0, 0));
+        assert!(!grid.is_surface(0, 1));
+        assert!(!grid.is_wall(0));
+
+        level.width = u32::MAX;
+        let grid = OccupancyGrid::from_level(&level);
+        assert_eq!(grid.width(), Level::MAX_BLOCK_WIDTH);
+    }
+}
@@ -0,0 +1,230 @@
+use std::{collections::HashMap, ops::Add};
+
+use crate::{course::Course, level::Level, objects::ObjectType};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Item {
+    pub object_type: ObjectType,
+    pub x: i32,
+    pub y: i32,
+    // Block, pipe or other object the item comes out of, None for items placed loose
+    pub container: Option<ObjectType>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ItemCensus {
+    pub items: Vec<Item>,
+    pub totals: HashMap<ObjectType, u32>,
+}
+
+impl ItemCensus {
+    pub fn total(&self, object_type: ObjectType) -> u32 {
+        self.totals.get(&object_type).copied().unwrap_or(0)
+    }
+
+    pub fn coins(&self) -> u32 {
+        self.total(ObjectType::Coin)
+    }
+
+    pub fn powerups(&self) -> u32 {
+        self.totals
+            .iter()
+            .filter(|(object_type, _)| object_type.is_powerup())
+            .map(|(_, count)| count)
+            .sum()
+    }
+}
+
+fn is_item(object_type: ObjectType) -> bool {
+    object_type == ObjectType::Coin || object_type.is_powerup()
+}
+
+// Every powerup, 1-up, star and coin in the level, including the ones held by other objects
+pub fn items(level: &Level) -> ItemCensus {
+    let mut census = ItemCensus::default();
+
+    for object in &level.objects {
+        let (x, y, _, _) = object.get_block_bounds();
+        let object_type = object.get_object_type();
+        let child_type = match (object_type, object.child_object_type) {
+            // An empty ? Block gives a coin
+            (Some(ObjectType::QuestionBlock), -1) => Some(ObjectType::Coin),
+            (_, child_type) => ObjectType::try_from(child_type).ok(),
+        };
+
+        let found = [(object_type, None), (child_type, object_type)];
+        for (item_type, container) in found {
+            if let Some(item_type) = item_type.filter(|&item_type| is_item(item_type)) {
+                census.items.push(Item {
+                    object_type: item_type,
+                    x,
+                    y,
+                    container,
+                });
+                *census.totals.entry(item_type).or_default() += 1;
+            }
+        }
+    }
+
+    census
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CoinCount {
+    pub loose: u32,
+    // Blocks and anything else but pipes give one coin each
+    pub in_blocks: u32,
+    // Pipes keep spawning coins, so these are pipes rather than coins
+    pub pipes: u32,
+}
+
+impl CoinCount {
+    // Coins that can be collected at most, leaving out the endless ones from pipes
+    pub fn total(&self) -> u32 {
+        self.loose + self.in_blocks
+    }
+}
+
+impl Add for CoinCount {
+    type Output = CoinCount;
+
+    fn add(self, other: CoinCount) -> CoinCount {
+        CoinCount {
+            loose: self.loose + other.loose,
+            in_blocks: self.in_blocks + other.in_blocks,
+            pipes: self.pipes + other.pipes,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CourseCoins {
+    pub main: CoinCount,
+    pub sub: CoinCount,
+}
+
+impl CourseCoins {
+    pub fn combined(&self) -> CoinCount {
+        self.main + self.sub
+    }
+}
+
+// Only counts coins, there is no maximum score: the points for enemies, combos and the time bonus
+// aren't documented anywhere this crate could check them against
+pub fn coins(level: &Level) -> CoinCount {
+    let mut count = CoinCount::default();
+    let census = items(level);
+    for item in census
+        .items
+        .iter()
+        .filter(|item| item.object_type == ObjectType::Coin)
+    {
+        match item.container {
+            None => count.loose += 1,
+            Some(ObjectType::Pipe) => count.pipes += 1,
+            Some(_) => count.in_blocks += 1,
+        }
+    }
+    count
+}
+
+pub fn course_coins(course: &Course) -> CourseCoins {
+    CourseCoins {
+        main: coins(&course.level),
+        sub: coins(&course.sub_level),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        analysis::tests::{level, object},
+        fixtures::minimal_course,
+        level::{CourseTheme, GameMode},
+        objects::Object,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_items() {
+        let census = items(&level(
+            40,
+            vec![
+                object(ObjectType::Ground, 0, 0, 40, 1),
+                object(ObjectType::SuperMushroom, 3, 1, 1, 1),
+                Object::question_block(6, 4, Some(ObjectType::FireFlower)),
+                Object::question_block(8, 4, Some(ObjectType::FireFlower)),
+                Object::question_block(10, 4, None),
+                object(ObjectType::Coin, 12, 3, 1, 1),
+                object(ObjectType::Goomba, 14, 1, 1, 1),
+            ],
+        ));
+
+        assert_eq!(census.items.len(), 5);
+        assert_eq!(
+            census.items[0],
+            Item {
+                object_type: ObjectType::SuperMushroom,
+                x: 3,
+                y: 1,
+                container: None,
+            }
+        );
+        assert_eq!(
+            census.items[1],
+            Item {
+                object_type: ObjectType::FireFlower,
+                x: 6,
+                y: 4,
+                container: Some(ObjectType::QuestionBlock),
+            }
+        );
+        assert_eq!(census.total(ObjectType::FireFlower), 2);
+        assert_eq!(
+            census.items[3],
+            Item {
+                object_type: ObjectType::Coin,
+                x: 10,
+                y: 4,
+                container: Some(ObjectType::QuestionBlock),
+            }
+        );
+        assert_eq!(census.total(ObjectType::Goomba), 0);
+        assert_eq!(census.coins(), 2);
+        assert_eq!(census.powerups(), 3);
+    }
+
+    #[test]
+    fn test_coins() {
+        let mut objects: Vec<_> = (0..5)
+            .map(|x| object(ObjectType::Coin, x, 3, 1, 1))
+            .collect();
+        objects.push(Object::question_block(6, 4, Some(ObjectType::Coin)));
+        objects.push(Object::question_block(7, 4, None));
+        objects.push(Object::brick(9, 4));
+        objects.push(Object::pipe(8, 0, 3, Some(ObjectType::Coin)));
+        objects.push(Object::question_block(
+            10,
+            4,
+            Some(ObjectType::SuperMushroom),
+        ));
+
+        let count = coins(&level(24, objects.clone()));
+        assert_eq!(
+            count,
+            CoinCount {
+                loose: 5,
+                in_blocks: 2,
+                pipes: 1
+            }
+        );
+        assert_eq!(count.total(), 7);
+
+        let mut course = minimal_course(GameMode::Mario3, CourseTheme::Overworld);
+        course.sub_level.objects.extend(objects);
+        let course_coins = course_coins(&course);
+        assert_eq!(course_coins.main.total(), 0);
+        assert_eq!(course_coins.combined(), count);
+    }
+}
@@ -0,0 +1,130 @@
+use std::collections::VecDeque;
+
+use crate::{level::Level, objects::ObjectType};
+
+use super::{OccupancyGrid, GOAL_AREA, START_AREA};
+
+// The furthest the player got before running out of places to jump to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Gap {
+    // Last reachable surface
+    pub x: i32,
+    pub y: i32,
+    // Columns to the next surface or the goal
+    pub width: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Reachability {
+    pub goal_reachable: bool,
+    pub first_gap: Option<Gap>,
+}
+
+// Coarse check that the goal can be reached from the start by running and jumping between
+// surfaces. Obstacles inside a jump arc are ignored, so this can only prove a level unbeatable
+// without powerups, not that it's beatable.
+pub fn reachability(level: &Level) -> Reachability {
+    let grid = OccupancyGrid::from_level(level);
+    let width = grid.width() as i32;
+    let height = grid.height() as i32;
+    let jump_height = level.game_mode.jump_height() as i32;
+    let jump_length = level.game_mode.jump_length() as i32;
+
+    let goal_x = level
+        .objects
+        .iter()
+        .find(|object| object.get_object_type() == Some(ObjectType::Goal))
+        .map_or(width - GOAL_AREA as i32, |goal| goal.get_block_bounds().0);
+
+    let index = |x: i32, y: i32| (y * width + x) as usize;
+    let mut reached = vec![false; (width * height) as usize];
+    let mut queue = VecDeque::new();
+
+    for x in 0..(START_AREA as i32).min(width) {
+        for y in 0..height {
+            if grid.is_surface(x, y) {
+                reached[index(x, y)] = true;
+                queue.push_back((x, y));
+            }
+        }
+    }
+
+    let mut furthest: Option<(i32, i32)> = None;
+    while let Some((x, y)) = queue.pop_front() {
+        if furthest.is_none_or(|(furthest_x, _)| x > furthest_x) {
+            furthest = Some((x, y));
+        }
+
+        if x >= goal_x {
+            return Reachability {
+                goal_reachable: true,
+                first_gap: None,
+            };
+        }
+
+        // Jumps go up to the jump height, falls can go down any distance
+        for next_x in (x - jump_length).max(0)..=(x + jump_length).min(width - 1) {
+            for next_y in 0..=(y + jump_height).min(height - 1) {
+                if !reached[index(next_x, next_y)] && grid.is_surface(next_x, next_y) {
+                    reached[index(next_x, next_y)] = true;
+                    queue.push_back((next_x, next_y));
+                }
+            }
+        }
+    }
+
+    let first_gap = furthest.map(|(x, y)| {
+        let next_surface = (x + 1..goal_x)
+            .find(|&next_x| (0..height).any(|next_y| grid.is_surface(next_x, next_y)))
+            .unwrap_or(goal_x);
+
+        Gap {
+            x,
+            y,
+            width: (next_surface - x) as u32,
+        }
+    });
+
+    Reachability {
+        goal_reachable: false,
+        first_gap,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analysis::tests::{level, object};
+
+    use super::*;
+
+    #[test]
+    fn test_reachability() {
+        let mut level = level(
+            60,
+            vec![
+                object(ObjectType::Ground, 0, 0, 10, 1),
+                object(ObjectType::Ground, 30, 0, 30, 1),
+                object(ObjectType::Goal, 45, 1, 1, 1),
+            ],
+        );
+        assert_eq!(
+            reachability(&level),
+            Reachability {
+                goal_reachable: false,
+                first_gap: Some(Gap {
+                    x: 9,
+                    y: 0,
+                    width: 21,
+                }),
+            }
+        );
+
+        level
+            .objects
+            .push(object(ObjectType::HardBlock, 18, 2, 4, 1));
+        assert!(reachability(&level).goal_reachable);
+
+        level.width = u32::MAX;
+        assert!(reachability(&level).goal_reachable);
+    }
+}
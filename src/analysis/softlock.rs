@@ -0,0 +1,299 @@
+use std::collections::HashMap;
+
+use crate::{level::Level, objects::ObjectType};
+
+use super::{Cell, OccupancyGrid};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Confidence {
+    Low,
+    Medium,
+    High,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SoftlockKind {
+    // A floored pit with walls too high to jump out of and nothing to climb on
+    InescapablePit,
+    // A one-way wall separating the P-switches from the coins they'd turn into platforms
+    PSwitchGate,
+    // A door or pipe leading to an exit sealed by terrain
+    NoReturnWarp,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Softlock {
+    pub kind: SoftlockKind,
+    pub x: i32,
+    pub y: i32,
+    pub confidence: Confidence,
+}
+
+pub fn softlocks(level: &Level) -> Vec<Softlock> {
+    let grid = OccupancyGrid::from_level(level);
+
+    let mut softlocks = pits(level, &grid);
+    softlocks.extend(p_switch_gates(level, &grid));
+    softlocks.extend(sealed_warps(level, &grid));
+    softlocks
+}
+
+fn pits(level: &Level, grid: &OccupancyGrid) -> Vec<Softlock> {
+    let mut pits = Vec::new();
+
+    for y in 0..grid.height() as i32 {
+        let mut x = 0;
+        while x < grid.width() as i32 {
+            if !is_floor(grid, x, y) {
+                x += 1;
+                continue;
+            }
+
+            let left = x;
+            while is_floor(grid, x + 1, y) {
+                x += 1;
+            }
+            let right = x;
+            x += 1;
+
+            let wall_height = wall_height(grid, left - 1, y).min(wall_height(grid, right + 1, y));
+            if wall_height <= level.game_mode.jump_height() as i32
+                || y + wall_height >= grid.height() as i32
+            {
+                continue;
+            }
+
+            // Anything to stand on inside the pit could be used to climb out
+            let interior_clear = (left..=right)
+                .all(|x| (y + 2..=y + wall_height).all(|y| grid.get(x, y) == Cell::Empty));
+            if !interior_clear {
+                continue;
+            }
+
+            let inside = |object_type: &dyn Fn(ObjectType) -> bool| {
+                level.objects.iter().any(|object| {
+                    let (object_x, object_y, _, _) = object.get_block_bounds();
+                    (left..=right).contains(&object_x)
+                        && (y + 1..=y + wall_height).contains(&object_y)
+                        && object.get_object_type().is_some_and(object_type)
+                })
+            };
+
+            let escapable = inside(&|object_type| {
+                matches!(
+                    object_type,
+                    ObjectType::Trampoline
+                        | ObjectType::Vine
+                        | ObjectType::Door
+                        | ObjectType::Pipe
+                        | ObjectType::NoteBlock
+                        | ObjectType::Lift
+                )
+            });
+            if escapable {
+                continue;
+            }
+
+            pits.push(Softlock {
+                kind: SoftlockKind::InescapablePit,
+                x: left,
+                y: y + 1,
+                // Enemies can sometimes be bounced off to get out
+                confidence: if inside(&|object_type| object_type.is_enemy()) {
+                    Confidence::Medium
+                } else {
+                    Confidence::High
+                },
+            });
+        }
+    }
+
+    pits
+}
+
+fn is_floor(grid: &OccupancyGrid, x: i32, y: i32) -> bool {
+    grid.is_standable(x, y) && grid.get(x, y + 1) == Cell::Empty
+}
+
+// Solid tiles stacked directly on top of the floor at the given height
+fn wall_height(grid: &OccupancyGrid, x: i32, floor: i32) -> i32 {
+    (floor + 1..grid.height() as i32)
+        .take_while(|&y| grid.get(x, y) == Cell::Solid)
+        .count() as i32
+}
+
+fn p_switch_gates(level: &Level, grid: &OccupancyGrid) -> Vec<Softlock> {
+    let positions = |object_type: ObjectType| -> Vec<(i32, i32)> {
+        level
+            .objects
+            .iter()
+            .filter(|object| object.get_object_type() == Some(object_type))
+            .map(|object| {
+                let (x, y, _, _) = object.get_block_bounds();
+                (x, y)
+            })
+            .collect()
+    };
+
+    let p_switches = positions(ObjectType::PSwitch);
+    let coins = positions(ObjectType::Coin);
+    if p_switches.is_empty() || coins.is_empty() {
+        return Vec::new();
+    }
+
+    positions(ObjectType::OneWayWall)
+        .into_iter()
+        .filter(|&(wall_x, _)| p_switches.iter().all(|&(x, _)| x < wall_x))
+        .filter_map(|(wall_x, wall_y)| {
+            let gated: Vec<_> = coins.iter().filter(|&&(x, _)| x > wall_x).collect();
+            if gated.is_empty() {
+                return None;
+            }
+
+            // Coins with nothing underneath them are likely meant to become a bridge
+            let bridging = gated
+                .iter()
+                .any(|&&(x, y)| (0..y).all(|y| !grid.is_standable(x, y)));
+
+            Some(Softlock {
+                kind: SoftlockKind::PSwitchGate,
+                x: wall_x,
+                y: wall_y,
+                confidence: if bridging {
+                    Confidence::Medium
+                } else {
+                    Confidence::Low
+                },
+            })
+        })
+        .collect()
+}
+
+fn sealed_warps(level: &Level, grid: &OccupancyGrid) -> Vec<Softlock> {
+    let mut links: HashMap<i16, Vec<_>> = HashMap::new();
+    for object in &level.objects {
+        let object_type = object.get_object_type();
+        if object.link_id >= 0
+            && matches!(object_type, Some(ObjectType::Door) | Some(ObjectType::Pipe))
+        {
+            links.entry(object.link_id).or_default().push(object);
+        }
+    }
+
+    let mut sealed = Vec::new();
+    for warps in links.values().filter(|warps| warps.len() > 1) {
+        for exit in warps {
+            let (x, y, width, height) = exit.get_block_bounds();
+            // Pipes are exited through the tile above their opening, doors through the door itself
+            let (exit_y, exit_height) = match exit.get_object_type() {
+                Some(ObjectType::Pipe) => (y + height as i32, 1),
+                _ => (y, height as i32),
+            };
+
+            let blocked = (x..x + width as i32)
+                .all(|x| (exit_y..exit_y + exit_height).any(|y| grid.get(x, y) == Cell::Solid));
+            if blocked {
+                sealed.push(Softlock {
+                    kind: SoftlockKind::NoReturnWarp,
+                    x,
+                    y,
+                    confidence: Confidence::High,
+                });
+            }
+        }
+    }
+
+    sealed
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analysis::tests::{level, object};
+
+    use super::*;
+
+    #[test]
+    fn test_inescapable_pit() {
+        let mut level = level(
+            20,
+            vec![
+                object(ObjectType::Ground, 0, 0, 20, 1),
+                object(ObjectType::Ground, 5, 1, 1, 6),
+                object(ObjectType::Ground, 10, 1, 1, 6),
+            ],
+        );
+        assert_eq!(
+            softlocks(&level),
+            vec![Softlock {
+                kind: SoftlockKind::InescapablePit,
+                x: 6,
+                y: 1,
+                confidence: Confidence::High,
+            }]
+        );
+
+        level
+            .objects
+            .push(object(ObjectType::Trampoline, 7, 1, 1, 1));
+        assert!(softlocks(&level).is_empty());
+    }
+
+    #[test]
+    fn test_p_switch_gates() {
+        // Coins over a pit past a one-way wall, with the P Switch before it
+        let mut level = level(
+            30,
+            vec![
+                object(ObjectType::Ground, 0, 0, 14, 1),
+                object(ObjectType::Ground, 20, 0, 10, 1),
+                object(ObjectType::PSwitch, 3, 1, 1, 1),
+                object(ObjectType::OneWayWall, 10, 1, 1, 1),
+                object(ObjectType::Coin, 16, 1, 1, 1),
+            ],
+        );
+        let grid = OccupancyGrid::from_level(&level);
+        assert_eq!(
+            p_switch_gates(&level, &grid),
+            vec![Softlock {
+                kind: SoftlockKind::PSwitchGate,
+                x: 10,
+                y: 1,
+                confidence: Confidence::Medium,
+            }]
+        );
+
+        // Carried past the wall the P Switch can be pressed on the other side
+        level.objects[2] = object(ObjectType::PSwitch, 12, 1, 1, 1);
+        let grid = OccupancyGrid::from_level(&level);
+        assert!(p_switch_gates(&level, &grid).is_empty());
+    }
+
+    #[test]
+    fn test_sealed_warps() {
+        let door = |x| {
+            let mut door = object(ObjectType::Door, x, 1, 1, 2);
+            door.link_id = 0;
+            door
+        };
+        let mut level = level(
+            30,
+            vec![object(ObjectType::Ground, 0, 0, 30, 1), door(3), door(15)],
+        );
+        let grid = OccupancyGrid::from_level(&level);
+        assert!(sealed_warps(&level, &grid).is_empty());
+
+        level
+            .objects
+            .push(object(ObjectType::HardBlock, 15, 2, 1, 1));
+        let grid = OccupancyGrid::from_level(&level);
+        assert_eq!(
+            sealed_warps(&level, &grid),
+            vec![Softlock {
+                kind: SoftlockKind::NoReturnWarp,
+                x: 15,
+                y: 1,
+                confidence: Confidence::High,
+            }]
+        );
+    }
+}
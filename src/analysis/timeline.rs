@@ -0,0 +1,84 @@
+use crate::level::Level;
+
+use super::{clear_time::RUN_SPEED, SCREEN_WIDTH, START_AREA};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TimelineEntry {
+    pub effect_index: usize,
+    pub sound_type: u8,
+    pub variation: u8,
+    pub x: u8,
+    pub y: u8,
+    // Estimated seconds into the level at which the effect plays
+    pub seconds: f32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SoundTimeline {
+    // Blocks per second the player or camera moves at, the scroll speed for autoscroll levels
+    pub speed: f32,
+    pub entries: Vec<TimelineEntry>,
+}
+
+// Placed sound effects in the order a player running right would hear them. Autoscroll levels
+// play effects as they scroll into view, others as the player reaches them at full run speed
+pub fn sound_timeline(level: &Level) -> SoundTimeline {
+    let scroll_speed = level.auto_scroll.blocks_per_second();
+    let speed = scroll_speed.unwrap_or(RUN_SPEED);
+
+    let mut entries: Vec<TimelineEntry> = level
+        .sound_effects
+        .iter()
+        .enumerate()
+        .filter(|(_, effect)| !effect.is_empty())
+        .map(|(effect_index, effect)| {
+            let x = u32::from(effect.x_position);
+            let distance = match scroll_speed {
+                Some(_) => x.saturating_sub(SCREEN_WIDTH),
+                None => x.saturating_sub(START_AREA),
+            };
+            TimelineEntry {
+                effect_index,
+                sound_type: effect.sound_type,
+                variation: effect.variation,
+                x: effect.x_position,
+                y: effect.y_position,
+                seconds: distance as f32 / speed,
+            }
+        })
+        .collect();
+    entries.sort_by_key(|entry| (entry.x, entry.y, entry.effect_index));
+
+    SoundTimeline { speed, entries }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{analysis::tests::level, level::AutoScroll, sound_effects::SoundEffect};
+
+    use super::*;
+
+    #[test]
+    fn test_sound_timeline() {
+        let mut level = level(100, Vec::new());
+        level.sound_effects = vec![SoundEffect::default(); 2];
+        level.sound_effects[0] = SoundEffect::from_raw(0x01006002);
+        level.sound_effects[1] = SoundEffect::from_raw(0x02001002);
+
+        let timeline = sound_timeline(&level);
+        let order: Vec<usize> = timeline
+            .entries
+            .iter()
+            .map(|entry| entry.effect_index)
+            .collect();
+        assert_eq!(order, [1, 0]);
+        assert_eq!(timeline.entries[0].seconds, 9.0 / RUN_SPEED);
+
+        level.auto_scroll = AutoScroll::Medium;
+        let timeline = sound_timeline(&level);
+        assert_eq!(timeline.entries[0].seconds, 0.0);
+        assert_eq!(timeline.entries[1].seconds, (0x60 - 24) as f32 / 4.0);
+    }
+}
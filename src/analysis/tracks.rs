@@ -0,0 +1,216 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{level::Level, objects::ObjectType};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+// An object placed on a rail
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Attachment {
+    pub object: usize,
+    pub object_type: Option<ObjectType>,
+    // Index into Rail::path of the first rail tile the object covers
+    pub tile: usize,
+    // The way it sets off along the path, None on a single tile rail. Objects are taken to follow
+    // the path order, turning back at the far end of an open rail
+    pub direction: Option<Direction>,
+}
+
+// Track pieces joined into one rail
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rail {
+    // Indexes of the Track objects making up the rail
+    pub pieces: Vec<usize>,
+    // Tiles in travel order. Open rails start at their left end, loops at their bottom left tile
+    // going right
+    pub path: Vec<(i32, i32)>,
+    pub is_loop: bool,
+    // Tiles touching more than two others, which a single path can't cover
+    pub branches: Vec<(i32, i32)>,
+    pub attached: Vec<Attachment>,
+}
+
+const NEIGHBOURS: [(i32, i32, Direction); 4] = [
+    (1, 0, Direction::Right),
+    (0, 1, Direction::Up),
+    (-1, 0, Direction::Left),
+    (0, -1, Direction::Down),
+];
+
+fn step(from: (i32, i32), to: (i32, i32)) -> Option<Direction> {
+    NEIGHBOURS
+        .iter()
+        .find(|(dx, dy, _)| (from.0 + dx, from.1 + dy) == to)
+        .map(|(_, _, direction)| *direction)
+}
+
+// Rails of the level. Track piece shapes aren't decoded yet, so pieces covering tiles that share an
+// edge are taken to be connected
+pub fn rails(level: &Level) -> Vec<Rail> {
+    let mut tiles: HashMap<(i32, i32), usize> = HashMap::new();
+    for (index, object) in level.objects.iter().enumerate() {
+        if object.get_object_type() != Some(ObjectType::Track) {
+            continue;
+        }
+        let (left, bottom, width, height) = object.get_block_bounds();
+        for x in left..left + width as i32 {
+            for y in bottom..bottom + height as i32 {
+                tiles.entry((x, y)).or_insert(index);
+            }
+        }
+    }
+    let neighbours = |(x, y): (i32, i32)| {
+        NEIGHBOURS
+            .iter()
+            .map(move |(dx, dy, _)| (x + dx, y + dy))
+            .filter(|tile| tiles.contains_key(tile))
+    };
+
+    let mut starts: Vec<(i32, i32)> = tiles.keys().copied().collect();
+    starts.sort();
+    let mut visited = HashSet::new();
+    let mut rails = Vec::new();
+    for start in starts {
+        if visited.contains(&start) {
+            continue;
+        }
+
+        let mut component = vec![start];
+        visited.insert(start);
+        let mut next = 0;
+        while next < component.len() {
+            for tile in neighbours(component[next]) {
+                if visited.insert(tile) {
+                    component.push(tile);
+                }
+            }
+            next += 1;
+        }
+        component.sort();
+
+        let degree = |tile| neighbours(tile).count();
+        let branches: Vec<_> = component
+            .iter()
+            .copied()
+            .filter(|&tile| degree(tile) > 2)
+            .collect();
+        let is_loop = component.len() > 2 && component.iter().all(|&tile| degree(tile) == 2);
+        let first = component
+            .iter()
+            .copied()
+            .find(|&tile| is_loop || degree(tile) <= 1)
+            .unwrap_or(component[0]);
+
+        // Walks the rail, preferring right, then up, then left, then down where it splits
+        let mut path = vec![first];
+        let mut on_path = HashSet::from([first]);
+        let mut stack = vec![first];
+        while let Some(&tile) = stack.last() {
+            match neighbours(tile).find(|next| !on_path.contains(next)) {
+                Some(next) => {
+                    on_path.insert(next);
+                    path.push(next);
+                    stack.push(next);
+                }
+                None => {
+                    stack.pop();
+                }
+            }
+        }
+
+        let mut pieces: Vec<usize> = component.iter().map(|tile| tiles[tile]).collect();
+        pieces.sort();
+        pieces.dedup();
+
+        let attached = level
+            .objects
+            .iter()
+            .enumerate()
+            .filter(|(_, object)| object.get_object_type() != Some(ObjectType::Track))
+            .filter_map(|(index, object)| {
+                let (left, bottom, width, height) = object.get_block_bounds();
+                let tile = path.iter().position(|&(x, y)| {
+                    (left..left + width as i32).contains(&x)
+                        && (bottom..bottom + height as i32).contains(&y)
+                })?;
+                let direction = match (path.get(tile + 1), tile.checked_sub(1)) {
+                    (Some(&next), _) => step(path[tile], next),
+                    (None, Some(_)) if is_loop => step(path[tile], path[0]),
+                    (None, Some(previous)) => step(path[tile], path[previous]),
+                    (None, None) => None,
+                };
+                Some(Attachment {
+                    object: index,
+                    object_type: object.get_object_type(),
+                    tile,
+                    direction,
+                })
+            })
+            .collect();
+
+        rails.push(Rail {
+            pieces,
+            path,
+            is_loop,
+            branches,
+            attached,
+        });
+    }
+
+    rails
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analysis::tests::{level, object};
+
+    use super::*;
+
+    #[test]
+    fn test_rails() {
+        // An L shaped rail with a lift near its far end
+        let mut objects = vec![
+            object(ObjectType::Track, 2, 5, 4, 1),
+            object(ObjectType::Track, 5, 6, 1, 3),
+            object(ObjectType::Lift, 5, 7, 1, 1),
+        ];
+        // A square loop from (10, 3) to (12, 5)
+        objects.push(object(ObjectType::Track, 10, 3, 3, 1));
+        objects.push(object(ObjectType::Track, 10, 5, 3, 1));
+        objects.push(object(ObjectType::Track, 10, 4, 1, 1));
+        objects.push(object(ObjectType::Track, 12, 4, 1, 1));
+        objects.push(object(ObjectType::Goomba, 12, 5, 1, 1));
+
+        let rails = rails(&level(24, objects));
+        assert_eq!(rails.len(), 2);
+
+        let open = &rails[0];
+        assert_eq!(open.pieces, vec![0, 1]);
+        assert!(!open.is_loop);
+        assert_eq!(open.path.first(), Some(&(2, 5)));
+        assert_eq!(open.path.last(), Some(&(5, 8)));
+        assert_eq!(
+            open.attached,
+            vec![Attachment {
+                object: 2,
+                object_type: Some(ObjectType::Lift),
+                tile: 5,
+                direction: Some(Direction::Up)
+            }]
+        );
+
+        let square = &rails[1];
+        assert!(square.is_loop);
+        assert!(square.branches.is_empty());
+        assert_eq!(square.path.len(), 8);
+        assert_eq!(square.path[..3], [(10, 3), (11, 3), (12, 3)]);
+        assert_eq!(square.attached[0].object, 7);
+        assert_eq!(square.attached[0].direction, Some(Direction::Left));
+    }
+}
@@ -0,0 +1,109 @@
+use crate::{level::Level, objects::ObjectType};
+
+use super::OccupancyGrid;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrapKind {
+    // Above a pit, where jumping across bumps into it and drops the player
+    OverPit,
+    // Between a floor and a ceiling with barely room to jump, so it can't be jumped around
+    UnderLowCeiling,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HiddenBlockTrap {
+    pub kind: TrapKind,
+    pub object: usize,
+    pub x: i32,
+    pub y: i32,
+}
+
+// Hidden blocks where players run into them mid jump, the usual troll setup. Blocks elsewhere,
+// e.g. hiding a 1-up above flat ground, aren't reported
+pub fn hidden_block_traps(level: &Level) -> Vec<HiddenBlockTrap> {
+    let grid = OccupancyGrid::from_level(level);
+    let jump_height = level.game_mode.jump_height() as i32;
+    let jump_length = level.game_mode.jump_length() as i32;
+
+    // Highest surface in the column at or below y
+    let floor = |x: i32, y: i32| (0..y).rev().find(|&floor| grid.is_surface(x, floor));
+
+    let mut traps = Vec::new();
+    for (index, object) in level.objects.iter().enumerate() {
+        if object.get_object_type() != Some(ObjectType::HiddenBlock) {
+            continue;
+        }
+        let (x, y, _, _) = object.get_block_bounds();
+
+        let kind = match floor(x, y) {
+            // Reachable from a jump off a surface on either side of the pit
+            None => {
+                let in_arc = (x - jump_length..=x + jump_length)
+                    .filter(|&from| from != x)
+                    .filter_map(|from| floor(from, y))
+                    .any(|from_y| (1..=jump_height).contains(&(y - from_y)));
+                in_arc.then_some(TrapKind::OverPit)
+            }
+            Some(floor_y) => {
+                let ceiling = (y + 1..=floor_y + jump_height + 1).any(|ceiling| {
+                    grid.is_standable(x, ceiling)
+                        || grid.is_standable(x - 1, ceiling)
+                        || grid.is_standable(x + 1, ceiling)
+                });
+                ((1..=jump_height).contains(&(y - floor_y)) && ceiling)
+                    .then_some(TrapKind::UnderLowCeiling)
+            }
+        };
+
+        if let Some(kind) = kind {
+            traps.push(HiddenBlockTrap {
+                kind,
+                object: index,
+                x,
+                y,
+            });
+        }
+    }
+
+    traps
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analysis::tests::{level, object};
+
+    use super::*;
+
+    #[test]
+    fn test_hidden_block_traps() {
+        let ground = |x| object(ObjectType::Ground, x, 0, 1, 1);
+        let mut objects: Vec<_> = (0..10).chain(14..48).map(ground).collect();
+        // Over the pit between 10 and 13
+        objects.push(object(ObjectType::HiddenBlock, 12, 3, 1, 1));
+        // Hiding something above open ground
+        objects.push(object(ObjectType::HiddenBlock, 20, 4, 1, 1));
+        // Under a ceiling three blocks above the floor
+        objects.extend((28..34).map(|x| object(ObjectType::HardBlock, x, 4, 1, 1)));
+        let ceiling_trap = objects.len();
+        objects.push(object(ObjectType::HiddenBlock, 30, 3, 1, 1));
+
+        let traps = hidden_block_traps(&level(48, objects));
+        assert_eq!(
+            traps,
+            vec![
+                HiddenBlockTrap {
+                    kind: TrapKind::OverPit,
+                    object: 44,
+                    x: 12,
+                    y: 3
+                },
+                HiddenBlockTrap {
+                    kind: TrapKind::UnderLowCeiling,
+                    object: ceiling_trap,
+                    x: 30,
+                    y: 3
+                },
+            ]
+        );
+    }
+}
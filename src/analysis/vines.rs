@@ -0,0 +1,91 @@
+use crate::{level::Level, objects::ObjectType};
+
+use super::{Cell, OccupancyGrid};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VineGrowth {
+    // The block holding the vine
+    pub object: usize,
+    pub x: i32,
+    // The tile right above the block, where the vine starts
+    pub bottom: i32,
+    // Tiles grown through
+    pub length: u32,
+    // The solid tile stopping the vine, None when it grows to the top of the course
+    pub blocked_by: Option<(i32, i32)>,
+}
+
+impl VineGrowth {
+    // The block can be hit, but nothing grows out of it
+    pub fn is_blocked_immediately(&self) -> bool {
+        self.length == 0
+    }
+}
+
+// How far the vine in each block grows once hit. Vines grow through semisolids and stop under
+// anything solid. Vines placed loose have a set length and aren't included
+pub fn vine_growth(level: &Level) -> Vec<VineGrowth> {
+    let grid = OccupancyGrid::from_level(level);
+    let top = grid.height() as i32;
+
+    level
+        .objects
+        .iter()
+        .enumerate()
+        .filter(|(_, object)| {
+            object.child_object_type == i8::from(ObjectType::Vine)
+                && object
+                    .get_object_type()
+                    .is_some_and(|object_type| object_type != ObjectType::Vine)
+        })
+        .map(|(index, object)| {
+            let (x, y, _, height) = object.get_block_bounds();
+            let bottom = y + height as i32;
+            let blocked_by = (bottom..top)
+                .find(|&y| grid.get(x, y) == Cell::Solid)
+                .map(|y| (x, y));
+            VineGrowth {
+                object: index,
+                x,
+                bottom,
+                length: (blocked_by.map_or(top, |(_, y)| y) - bottom).max(0) as u32,
+                blocked_by,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analysis::tests::{level, object};
+
+    use super::*;
+
+    #[test]
+    fn test_vine_growth() {
+        let with_vine = |object_type, x, y| {
+            let mut block = object(object_type, x, y, 1, 1);
+            block.child_object_type = ObjectType::Vine.into();
+            block
+        };
+        let mut objects: Vec<_> = (0..24)
+            .map(|x| object(ObjectType::Ground, x, 0, 1, 1))
+            .collect();
+        objects.push(with_vine(ObjectType::Brick, 3, 4));
+        objects.push(object(ObjectType::SemisolidPlatform, 2, 8, 3, 1));
+        objects.push(with_vine(ObjectType::QuestionBlock, 10, 4));
+        objects.push(object(ObjectType::HardBlock, 10, 5, 1, 1));
+        objects.push(with_vine(ObjectType::NoteBlock, 15, 4));
+        objects.push(object(ObjectType::HardBlock, 15, 12, 1, 1));
+
+        let vines = vine_growth(&level(24, objects));
+        assert_eq!(
+            vines.iter().map(|vine| vine.length).collect::<Vec<_>>(),
+            vec![22, 0, 7]
+        );
+        assert_eq!(vines[0].blocked_by, None);
+        assert!(vines[1].is_blocked_immediately());
+        assert_eq!(vines[2].blocked_by, Some((15, 12)));
+        assert_eq!(vines[2].object, 28);
+    }
+}
@@ -0,0 +1,154 @@
+// Animated GIF sweeps across a level, a lightweight video preview for web front-ends. The camera
+// pans from the start to the goal at the level's auto-scroll speed or a fixed one.
+
+use std::io::Write;
+
+use image::{
+    codecs::gif::{GifEncoder, Repeat},
+    imageops::FilterType,
+    Delay, Frame,
+};
+
+use crate::{
+    level::Level,
+    render::{render_level, BLOCK_SIZE},
+    Error,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PanSpeed {
+    // The level's auto-scroll speed, or the given blocks per second if it doesn't scroll
+    AutoScroll(f32),
+    BlocksPerSecond(f32),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AnimationOptions {
+    pub speed: PanSpeed,
+    pub frames_per_second: u32,
+    // Width of the camera in blocks, 24 is a screen
+    pub view_width: u32,
+    // Pixels per block in the output
+    pub scale: u32,
+    // The sweep is sped up to fit, keeping GIFs of wide levels small
+    pub max_frames: usize,
+}
+
+impl Default for AnimationOptions {
+    fn default() -> Self {
+        AnimationOptions {
+            speed: PanSpeed::AutoScroll(6.0),
+            frames_per_second: 10,
+            view_width: 24,
+            scale: 4,
+            max_frames: 200,
+        }
+    }
+}
+
+impl AnimationOptions {
+    fn blocks_per_second(&self, level: &Level) -> f32 {
+        match self.speed {
+            PanSpeed::AutoScroll(fallback) => {
+                level.auto_scroll.blocks_per_second().unwrap_or(fallback)
+            }
+            PanSpeed::BlocksPerSecond(speed) => speed,
+        }
+    }
+}
+
+// Returns the number of frames written. Levels no wider than the camera give a single frame,
+// levels wider than Level::MAX_BLOCK_WIDTH fail with Error::InvalidData
+pub fn render_gif(
+    level: &Level,
+    options: &AnimationOptions,
+    writer: impl Write,
+) -> Result<usize, Error> {
+    // Checked before the scaled size is worked out, which a corrupt width would overflow
+    if level.block_width() > Level::MAX_BLOCK_WIDTH {
+        return Err(Error::InvalidData);
+    }
+    let scale = options.scale.clamp(1, BLOCK_SIZE);
+    let frames_per_second = options.frames_per_second.max(1);
+    let image = render_level(level)?;
+    let image = image::imageops::resize(
+        &image,
+        level.block_width().max(1) * scale,
+        level.block_height() * scale,
+        FilterType::Triangle,
+    );
+    let view_width = (options.view_width.max(1) * scale).min(image.width());
+
+    let distance = (image.width() - view_width) as f32;
+    let step = options.blocks_per_second(level).max(0.1) * scale as f32 / frames_per_second as f32;
+    let frame_count = ((distance / step).ceil() as usize + 1).clamp(1, options.max_frames.max(1));
+    let step = if frame_count > 1 {
+        distance / (frame_count - 1) as f32
+    } else {
+        0.0
+    };
+
+    let mut encoder = GifEncoder::new(writer);
+    encoder
+        .set_repeat(Repeat::Infinite)
+        .map_err(|_| Error::InvalidData)?;
+    let delay = Delay::from_numer_denom_ms(1000, frames_per_second);
+    for frame in 0..frame_count {
+        let x = (frame as f32 * step).round() as u32;
+        let view = image::imageops::crop_imm(&image, x, 0, view_width, image.height()).to_image();
+        encoder
+            .encode_frame(Frame::from_parts(view, 0, 0, delay))
+            .map_err(|error| match error {
+                image::ImageError::IoError(error) => Error::Io(error),
+                _ => Error::InvalidData,
+            })?;
+    }
+
+    Ok(frame_count)
+}
+
+#[cfg(test)]
+mod tests {
+    use image::{codecs::gif::GifDecoder, AnimationDecoder};
+
+    use crate::{
+        fixtures::minimal_level,
+        level::{AutoScroll, CourseTheme, GameMode},
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_render_gif() {
+        let mut level = minimal_level(GameMode::Mario3, CourseTheme::Overworld);
+        level.width = 48 * 16;
+        level.auto_scroll = AutoScroll::Fast;
+
+        let mut gif = Vec::new();
+        let frames = render_gif(&level, &AnimationOptions::default(), &mut gif).unwrap();
+        // 24 blocks to pan at 8 blocks per second and 10 frames per second
+        assert_eq!(frames, 31);
+
+        let decoded = GifDecoder::new(std::io::Cursor::new(gif))
+            .unwrap()
+            .into_frames()
+            .collect_frames()
+            .unwrap();
+        assert_eq!(decoded.len(), frames);
+        assert_eq!(decoded[0].buffer().dimensions(), (96, 108));
+
+        let options = AnimationOptions {
+            max_frames: 5,
+            ..Default::default()
+        };
+        assert_eq!(render_gif(&level, &options, &mut Vec::new()).unwrap(), 5);
+
+        level.width = u32::MAX;
+        let mut gif = Vec::new();
+        assert!(matches!(
+            render_gif(&level, &AnimationOptions::default(), &mut gif),
+            Err(Error::InvalidData)
+        ));
+        assert!(gif.is_empty());
+    }
+}
@@ -0,0 +1,278 @@
+use std::{
+    fs::File,
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+use clap::{Parser, Subcommand};
+use mm1_level_parser::{
+    analysis,
+    course::{Course, CourseData},
+    level::Level,
+    render,
+    thumbnail::Thumbnail,
+    validation,
+};
+
+#[derive(Parser)]
+#[command(name = "mm1", about = "Inspect and edit Super Mario Maker courses")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Print the header of both areas of a course
+    Info { course: PathBuf },
+    /// Parse a course and run every analysis pass over it
+    Validate { course: PathBuf },
+    /// Unpack a course tar into a directory
+    Extract { archive: PathBuf, output: PathBuf },
+    /// Pack a course directory into a tar
+    Pack { directory: PathBuf, output: PathBuf },
+    /// Read or replace one of the course thumbnails
+    #[command(subcommand)]
+    Thumbnail(ThumbnailCommand),
+    /// Render an area of a course to a PNG
+    Render {
+        course: PathBuf,
+        output: PathBuf,
+        #[arg(long)]
+        sub: bool,
+    },
+    /// Print the sound effects of an area in play order as JSON
+    Timeline {
+        course: PathBuf,
+        #[arg(long)]
+        sub: bool,
+    },
+    /// Convert between course formats
+    #[command(subcommand)]
+    Convert(ConvertCommand),
+}
+
+#[derive(Subcommand)]
+enum ThumbnailCommand {
+    /// Write the JPEG from thumbnail0 (preview) or thumbnail1
+    Get {
+        course: PathBuf,
+        #[arg(value_parser = clap::value_parser!(u8).range(0..=1))]
+        slot: u8,
+        output: PathBuf,
+    },
+    /// Replace thumbnail0 (preview) or thumbnail1 with a JPEG
+    Set {
+        course: PathBuf,
+        #[arg(value_parser = clap::value_parser!(u8).range(0..=1))]
+        slot: u8,
+        input: PathBuf,
+    },
+}
+
+#[derive(Subcommand)]
+enum ConvertCommand {
+    /// Write a course as JSON, or a .json file back to a course directory or tar
+    Json { input: PathBuf, output: PathBuf },
+}
+
+fn main() -> ExitCode {
+    match run(Cli::parse().command) {
+        Ok(code) => code,
+        Err(message) => {
+            eprintln!("error: {}", message);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(command: Command) -> Result<ExitCode, String> {
+    match command {
+        Command::Info { course } => {
+            let course = load(&course)?;
+            print_level(CourseData::CourseData, &course.level);
+            print_level(CourseData::CourseDataSub, &course.sub_level);
+        }
+        Command::Validate { course } => {
+            let course = load(&course)?;
+            let mut problems = 0;
+            problems += validate(CourseData::CourseData, &course.level);
+            problems += validate(CourseData::CourseDataSub, &course.sub_level);
+            for issue in course.check_consistency() {
+                println!("course: {:?}", issue);
+                problems += 1;
+            }
+
+            if problems > 0 {
+                return Ok(ExitCode::FAILURE);
+            }
+            println!("ok");
+        }
+        Command::Extract { archive, output } => {
+            load(&archive)?
+                .to_dir(&output)
+                .map_err(|error| format!("{:?}", error))?;
+        }
+        Command::Pack { directory, output } => {
+            save(&load(&directory)?, &output)?;
+        }
+        Command::Thumbnail(ThumbnailCommand::Get {
+            course,
+            slot,
+            output,
+        }) => {
+            let course = load(&course)?;
+            let thumbnail = match slot {
+                0 => &course.level_preview,
+                _ => &course.level_thumbnail,
+            };
+            std::fs::write(output, &thumbnail.jpeg_data).map_err(|error| error.to_string())?;
+        }
+        Command::Thumbnail(ThumbnailCommand::Set {
+            course: path,
+            slot,
+            input,
+        }) => {
+            let mut course = load(&path)?;
+            let thumbnail = Thumbnail {
+                jpeg_data: std::fs::read(input).map_err(|error| error.to_string())?,
+            };
+            // Re-encode images too large for the file rather than failing on save
+            let (bytes, quality) = thumbnail
+                .to_bytes_fitted()
+                .map_err(|error| format!("{:?}", error))?;
+            if let Some(quality) = quality {
+                eprintln!("thumbnail re-encoded at quality {}", quality);
+            }
+            let thumbnail = Thumbnail::from_bytes(&bytes).map_err(|error| format!("{:?}", error))?;
+            match slot {
+                0 => course.level_preview = thumbnail,
+                _ => course.level_thumbnail = thumbnail,
+            }
+            save(&course, &path)?;
+        }
+        Command::Render {
+            course,
+            output,
+            sub,
+        } => {
+            let course = load(&course)?;
+            let level = if sub {
+                &course.sub_level
+            } else {
+                &course.level
+            };
+            render::render_level(level)
+                .map_err(|error| format!("{:?}", error))?
+                .save(output)
+                .map_err(|error| error.to_string())?;
+        }
+        Command::Timeline { course, sub } => {
+            let course = load(&course)?;
+            let level = if sub {
+                &course.sub_level
+            } else {
+                &course.level
+            };
+            let timeline = analysis::sound_timeline(level);
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&timeline).map_err(|error| error.to_string())?
+            );
+        }
+        Command::Convert(ConvertCommand::Json { input, output }) => {
+            if input.extension().is_some_and(|extension| extension == "json") {
+                let json = std::fs::read_to_string(&input).map_err(|error| error.to_string())?;
+                let course: Course = serde_json::from_str(&json)
+                    .map_err(|error| format!("{}: {}", input.display(), error))?;
+                save(&course, &output)?;
+            } else {
+                let json = serde_json::to_string_pretty(&load(&input)?)
+                    .map_err(|error| error.to_string())?;
+                std::fs::write(output, json).map_err(|error| error.to_string())?;
+            }
+        }
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+fn load(path: &Path) -> Result<Course, String> {
+    Course::from_path(path).map_err(|error| format!("{}: {:?}", path.display(), error))
+}
+
+fn save(course: &Course, path: &Path) -> Result<(), String> {
+    if path.is_dir() {
+        return course.to_dir(path).map_err(|error| format!("{:?}", error));
+    }
+
+    let file = File::create(path).map_err(|error| error.to_string())?;
+    let mut builder = tar::Builder::new(file);
+    course
+        .to_tar(&mut builder)
+        .map_err(|error| format!("{:?}", error))?;
+    builder.finish().map_err(|error| error.to_string())
+}
+
+fn print_level(data: CourseData, level: &Level) {
+    println!("{}:", data.file_name());
+    println!("  name: {}", level.level_name);
+    println!("  created: {}", level.creation_time);
+    println!("  game mode: {:?}", level.game_mode);
+    println!("  theme: {:?}", level.course_theme);
+    println!("  time limit: {}", level.time_limit);
+    println!("  auto scroll: {:?}", level.auto_scroll);
+    println!("  width: {} blocks", level.block_width());
+    println!("  objects: {}", level.objects.len());
+}
+
+fn validate(data: CourseData, level: &Level) -> usize {
+    let mut problems = Vec::new();
+
+    for issue in validation::validate(level) {
+        problems.push(format!("{:?}", issue));
+    }
+
+    // The playability heuristics assume a start and a goal, which the sub area doesn't have
+    if data == CourseData::CourseData {
+        let estimate = analysis::estimated_clear_time(level);
+        if estimate.tight {
+            problems.push(format!(
+                "time limit {} is tight for an estimated {:.0}s clear",
+                estimate.time_limit, estimate.seconds
+            ));
+        }
+
+        for issue in analysis::autoscroll_safety(level) {
+            problems.push(format!(
+                "{:?} at ({}, {}) after {:.0}s of scrolling",
+                issue.kind, issue.x, issue.y, issue.seconds
+            ));
+        }
+
+        for softlock in analysis::softlocks(level) {
+            problems.push(format!(
+                "{:?} at ({}, {}), {:?} confidence",
+                softlock.kind, softlock.x, softlock.y, softlock.confidence
+            ));
+        }
+
+        let reachability = analysis::reachability(level);
+        if !reachability.goal_reachable {
+            // No gap when not even the start has anywhere to stand
+            problems.push(match reachability.first_gap {
+                Some(gap) => format!(
+                    "goal unreachable, {} block gap after ({}, {})",
+                    gap.width, gap.x, gap.y
+                ),
+                None => "goal unreachable".to_string(),
+            });
+        }
+    }
+
+    for problem in &problems {
+        println!("{}: {}", data.file_name(), problem);
+    }
+
+    problems.len()
+}
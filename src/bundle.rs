@@ -0,0 +1,129 @@
+// A course in a single file instead of four, for sharing with players. The layout is
+//
+// 00   [u8; 4]   Magic, "MM1B"
+// 04   u8        Format version, 1
+// 05   u8        Flags, 0x1 when the payload is zstd compressed
+// 06   u8        Entry count
+// 07   u8        Padding
+// 08             Manifest, 5 bytes per entry: the CourseData index (u8) and the file's size (u32)
+// ..             Payload, every file in manifest order, compressed as a whole if flagged
+//
+// with integers big endian like the course files themselves.
+
+use std::io::Read;
+
+use crate::{
+    course::{Course, CourseData},
+    Error,
+};
+
+pub const MAGIC: [u8; 4] = *b"MM1B";
+pub const VERSION: u8 = 1;
+const FLAG_COMPRESSED: u8 = 0x1;
+const HEADER_SIZE: usize = 8;
+const ENTRY_SIZE: usize = 5;
+// The four files come to under 0x50000 bytes, the payload is bounded when decompressing so a
+// malicious bundle can't expand without limit
+const MAX_PAYLOAD_SIZE: usize = 0x80000;
+
+impl Course {
+    pub fn to_bundle(&self, compress: bool) -> Result<Vec<u8>, Error> {
+        let mut bundle = MAGIC.to_vec();
+        bundle.push(VERSION);
+        bundle.push(if compress { FLAG_COMPRESSED } else { 0 });
+        bundle.push(CourseData::ALL.len() as u8);
+        bundle.push(0);
+
+        let mut payload = Vec::new();
+        for (index, data) in CourseData::ALL.into_iter().enumerate() {
+            let bytes = self.to_data_bytes(data)?;
+            bundle.push(index as u8);
+            bundle.extend_from_slice(&(bytes.len() as u32).to_be_bytes());
+            payload.extend_from_slice(&bytes);
+        }
+
+        if compress {
+            payload = zstd::encode_all(payload.as_slice(), 0).map_err(Error::Io)?;
+        }
+        bundle.extend_from_slice(&payload);
+        Ok(bundle)
+    }
+
+    pub fn from_bundle(bundle: &[u8]) -> Result<Course, Error> {
+        if bundle.len() < HEADER_SIZE || bundle[..4] != MAGIC || bundle[4] != VERSION {
+            return Err(Error::InvalidData);
+        }
+        let compressed = bundle[5] & FLAG_COMPRESSED != 0;
+        let manifest_end = HEADER_SIZE + bundle[6] as usize * ENTRY_SIZE;
+        let manifest = bundle
+            .get(HEADER_SIZE..manifest_end)
+            .ok_or(Error::InvalidData)?;
+
+        let payload = if compressed {
+            let mut payload = Vec::new();
+            let decoder = zstd::Decoder::new(&bundle[manifest_end..]).map_err(Error::Io)?;
+            decoder
+                .take(MAX_PAYLOAD_SIZE as u64 + 1)
+                .read_to_end(&mut payload)
+                .map_err(|_| Error::InvalidData)?;
+            if payload.len() > MAX_PAYLOAD_SIZE {
+                return Err(Error::FileTooLarge);
+            }
+            payload
+        } else {
+            bundle[manifest_end..].to_vec()
+        };
+
+        let mut files: [Option<&[u8]>; 4] = [None; 4];
+        let mut offset = 0;
+        for entry in manifest.chunks_exact(ENTRY_SIZE) {
+            let length = u32::from_be_bytes(entry[1..].try_into().unwrap()) as usize;
+            let bytes = payload
+                .get(offset..offset + length)
+                .ok_or(Error::InvalidData)?;
+            offset += length;
+            // Entries of unknown kinds are skipped, for later versions adding files
+            if let Some(file) = files.get_mut(entry[0] as usize) {
+                *file = Some(bytes);
+            }
+        }
+
+        let file = |data: CourseData| files[data as usize].ok_or(Error::MissingCourseData(data));
+        Course::from_bytes(
+            file(CourseData::CourseData)?,
+            file(CourseData::CourseDataSub)?,
+            file(CourseData::Thumbnail0)?,
+            file(CourseData::Thumbnail1)?,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        fixtures::minimal_course,
+        level::{CourseTheme, GameMode},
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_bundle_round_trip() {
+        let course = minimal_course(GameMode::NewSuperMarioBrosU, CourseTheme::Airship);
+        let bundle = course.to_bundle(false).unwrap();
+        assert_eq!(bundle[..4], MAGIC);
+        assert_eq!(Course::from_bundle(&bundle).unwrap(), course);
+
+        let compressed = course.to_bundle(true).unwrap();
+        assert!(compressed.len() < bundle.len());
+        assert_eq!(Course::from_bundle(&compressed).unwrap(), course);
+
+        assert!(Course::from_bundle(&bundle[..bundle.len() - 1]).is_err());
+        let mut missing = bundle.clone();
+        missing[HEADER_SIZE + 3 * ENTRY_SIZE] = 9;
+        assert!(matches!(
+            Course::from_bundle(&missing),
+            Err(Error::MissingCourseData(CourseData::Thumbnail1))
+        ));
+    }
+}
@@ -0,0 +1,339 @@
+// The checksums course files carry, and verification without decoding courses. Files are streamed
+// through crc32fast, and with the rayon feature paths are checked in parallel.
+
+use std::{
+    fs::File,
+    io::{BufReader, Read},
+    path::{Path, PathBuf},
+};
+
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+use crate::{course::CourseData, Error};
+
+const BUFFER_SIZE: usize = 0x10000;
+
+// Standard CRC32, as used by course data, thumbnails and saves
+pub fn crc32(bytes: &[u8]) -> u32 {
+    crc32fast::hash(bytes)
+}
+
+// CRC16-CCITT with a zero initial value, as used by Mii data
+pub fn crc16(bytes: &[u8]) -> u16 {
+    let mut crc: u16 = 0;
+    for byte in bytes {
+        crc ^= (*byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+// Where each kind of data keeps its checksum. All are stored big endian
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChecksumKind {
+    // .cdt files and save.dat, a CRC32 at 0x8 covering 0x10 onwards
+    CourseData,
+    // .tnl files, a CRC32 at 0x0 covering 0x4 onwards
+    Thumbnail,
+    // The 0x60 bytes of Mii data in a level, a CRC16 at 0x5E covering everything before it
+    Mii,
+}
+
+impl ChecksumKind {
+    // Going by the extension, course data for anything but .tnl files
+    pub fn for_file_name(name: &str) -> ChecksumKind {
+        if name.ends_with(".tnl") {
+            ChecksumKind::Thumbnail
+        } else {
+            ChecksumKind::CourseData
+        }
+    }
+
+    // Offset of the checksum, and where the data it covers starts
+    fn layout(&self) -> (usize, usize) {
+        match self {
+            ChecksumKind::CourseData => (0x8, 0x10),
+            ChecksumKind::Thumbnail => (0x0, 0x4),
+            ChecksumKind::Mii => (0x5E, 0x0),
+        }
+    }
+
+    fn size(&self) -> usize {
+        match self {
+            ChecksumKind::Mii => 2,
+            _ => 4,
+        }
+    }
+
+    fn covered<'a>(&self, bytes: &'a [u8]) -> Result<&'a [u8], Error> {
+        let (offset, start) = self.layout();
+        if bytes.len() < offset + self.size() || bytes.len() < start {
+            return Err(Error::InvalidData);
+        }
+        Ok(match self {
+            ChecksumKind::Mii => &bytes[..offset],
+            _ => &bytes[start..],
+        })
+    }
+
+    pub fn compute(&self, bytes: &[u8]) -> Result<u32, Error> {
+        let covered = self.covered(bytes)?;
+        Ok(match self {
+            ChecksumKind::Mii => crc16(covered) as u32,
+            _ => crc32(covered),
+        })
+    }
+
+    pub fn stored(&self, bytes: &[u8]) -> Result<u32, Error> {
+        self.covered(bytes)?;
+        let (offset, _) = self.layout();
+        let mut stored = [0; 4];
+        stored[4 - self.size()..].copy_from_slice(&bytes[offset..offset + self.size()]);
+        Ok(u32::from_be_bytes(stored))
+    }
+
+    pub fn verify(&self, bytes: &[u8]) -> ChecksumStatus {
+        match (self.stored(bytes), self.compute(bytes)) {
+            (Ok(expected), Ok(actual)) if expected == actual => ChecksumStatus::Valid,
+            (Ok(expected), Ok(actual)) => ChecksumStatus::Mismatch { expected, actual },
+            (Err(error), _) | (_, Err(error)) => ChecksumStatus::Unreadable(error),
+        }
+    }
+
+    // Writes the correct checksum into the data
+    pub fn patch(&self, bytes: &mut [u8]) -> Result<(), Error> {
+        let checksum = self.compute(bytes)?.to_be_bytes();
+        let (offset, _) = self.layout();
+        bytes[offset..offset + self.size()].copy_from_slice(&checksum[4 - self.size()..]);
+        Ok(())
+    }
+}
+
+// What parsing does about a bad checksum. Modified files are common in research, so by default they
+// only produce a warning with the tracing feature
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ChecksumPolicy {
+    // Fails with Error::ChecksumMismatch
+    Require,
+    #[default]
+    WarnOnly,
+    // Skips computing the checksum at all
+    Ignore,
+}
+
+impl ChecksumPolicy {
+    pub fn check(&self, kind: ChecksumKind, bytes: &[u8]) -> Result<(), Error> {
+        match self {
+            ChecksumPolicy::Require => match kind.verify(bytes) {
+                ChecksumStatus::Valid => Ok(()),
+                ChecksumStatus::Mismatch { expected, actual } => {
+                    Err(Error::ChecksumMismatch { expected, actual })
+                }
+                ChecksumStatus::Unreadable(error) => Err(error),
+            },
+            ChecksumPolicy::WarnOnly => {
+                #[cfg(feature = "tracing")]
+                if let ChecksumStatus::Mismatch { expected, actual } = kind.verify(bytes) {
+                    trace_span!("checksum");
+                    tracing::warn!(expected, actual, ?kind, "checksum mismatch");
+                }
+                Ok(())
+            }
+            ChecksumPolicy::Ignore => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ChecksumStatus {
+    Valid,
+    Mismatch { expected: u32, actual: u32 },
+    Unreadable(Error),
+}
+
+impl ChecksumStatus {
+    pub fn is_valid(&self) -> bool {
+        matches!(self, ChecksumStatus::Valid)
+    }
+}
+
+#[derive(Debug)]
+pub struct ChecksumReport {
+    pub path: PathBuf,
+    // Each .cdt and .tnl file checked, by file name
+    pub files: Vec<(String, ChecksumStatus)>,
+}
+
+impl ChecksumReport {
+    pub fn is_valid(&self) -> bool {
+        !self.files.is_empty() && self.files.iter().all(|(_, status)| status.is_valid())
+    }
+}
+
+// Each path may be a course directory, a course tar, or a single .cdt or .tnl file
+pub fn verify_checksums<P: AsRef<Path> + Sync>(paths: &[P]) -> Vec<ChecksumReport> {
+    #[cfg(feature = "rayon")]
+    let paths = paths.par_iter();
+    #[cfg(not(feature = "rayon"))]
+    let paths = paths.iter();
+
+    paths.map(|path| verify_path(path.as_ref())).collect()
+}
+
+fn verify_path(path: &Path) -> ChecksumReport {
+    let mut files = Vec::new();
+
+    if path.is_dir() {
+        for data in CourseData::ALL {
+            let status = match File::open(path.join(data.file_name())) {
+                Ok(file) => verify_file(data.file_name(), file),
+                Err(_) => ChecksumStatus::Unreadable(Error::MissingCourseData(data)),
+            };
+            files.push((data.file_name().to_string(), status));
+        }
+    } else if path.extension().is_some_and(|extension| extension == "tar") {
+        if let Err(error) = verify_tar(path, &mut files) {
+            files.push((String::new(), ChecksumStatus::Unreadable(error)));
+        }
+    } else {
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let status = match File::open(path) {
+            Ok(file) => verify_file(&name, file),
+            Err(error) => ChecksumStatus::Unreadable(Error::Io(error)),
+        };
+        files.push((name, status));
+    }
+
+    ChecksumReport {
+        path: path.to_path_buf(),
+        files,
+    }
+}
+
+fn verify_tar(path: &Path, files: &mut Vec<(String, ChecksumStatus)>) -> Result<(), Error> {
+    let mut archive = tar::Archive::new(File::open(path).map_err(Error::Io)?);
+    for entry in archive.entries().map_err(|_| Error::InvalidData)? {
+        let entry = entry.map_err(|_| Error::InvalidData)?;
+        let name = entry
+            .path()
+            .map_err(|_| Error::InvalidData)?
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        if name.ends_with(".cdt") || name.ends_with(".tnl") {
+            let status = verify_file(&name, entry);
+            files.push((name, status));
+        }
+    }
+
+    Ok(())
+}
+
+fn verify_file<R: Read>(name: &str, reader: R) -> ChecksumStatus {
+    let (checksum_offset, data_offset) = ChecksumKind::for_file_name(name).layout();
+
+    let mut reader = BufReader::with_capacity(BUFFER_SIZE, reader);
+    let mut header = [0; 0x10];
+    if let Err(error) = reader.read_exact(&mut header[..data_offset]) {
+        return ChecksumStatus::Unreadable(Error::Io(error));
+    }
+    let expected = u32::from_be_bytes(
+        header[checksum_offset..checksum_offset + 4]
+            .try_into()
+            .unwrap(),
+    );
+
+    let mut hasher = crc32fast::Hasher::new();
+    let mut buffer = vec![0; BUFFER_SIZE];
+    loop {
+        match reader.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(length) => hasher.update(&buffer[..length]),
+            Err(error) if error.kind() == std::io::ErrorKind::Interrupted => {}
+            Err(error) => return ChecksumStatus::Unreadable(Error::Io(error)),
+        }
+    }
+
+    let actual = hasher.finalize();
+    if expected == actual {
+        ChecksumStatus::Valid
+    } else {
+        ChecksumStatus::Mismatch { expected, actual }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        fixtures::minimal_course_tar,
+        level::{CourseTheme, GameMode, Level},
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_checksum_kinds() {
+        // The CRC16-CCITT (XMODEM) check value
+        assert_eq!(crc16(b"123456789"), 0x31C3);
+
+        let mut mii = [0x5A; 0x60];
+        assert!(!ChecksumKind::Mii.verify(&mii).is_valid());
+        ChecksumKind::Mii.patch(&mut mii).unwrap();
+        assert!(ChecksumKind::Mii.verify(&mii).is_valid());
+        assert_eq!(
+            ChecksumKind::Mii.stored(&mii).unwrap(),
+            crc16(&mii[..0x5E]) as u32
+        );
+
+        let mut level = minimal_course_tar(GameMode::Mario3, CourseTheme::Castle).unwrap();
+        let level = &mut level[512..512 + Level::SIZE];
+        assert!(ChecksumKind::CourseData.verify(level).is_valid());
+        level[0x100] ^= 0xFF;
+        assert!(!ChecksumKind::CourseData.verify(level).is_valid());
+        ChecksumKind::CourseData.patch(level).unwrap();
+        assert!(ChecksumKind::CourseData.verify(level).is_valid());
+
+        assert!(matches!(
+            ChecksumKind::Thumbnail.verify(&[0; 3]),
+            ChecksumStatus::Unreadable(Error::InvalidData)
+        ));
+    }
+
+    #[test]
+    fn test_verify_checksums() {
+        let directory = std::env::temp_dir().join(format!("mm1-checksum-{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+
+        let mut tar = minimal_course_tar(GameMode::SuperMarioBros, CourseTheme::Water).unwrap();
+        let valid = directory.join("valid.tar");
+        std::fs::write(&valid, &tar).unwrap();
+        // Flip a byte inside the first member, course_data.cdt, past its header
+        tar[512 + 0x100] ^= 0xFF;
+        let corrupt = directory.join("corrupt.tar");
+        std::fs::write(&corrupt, &tar).unwrap();
+
+        let reports = verify_checksums(&[&valid, &corrupt]);
+        assert!(reports[0].is_valid());
+        assert_eq!(reports[0].files.len(), 4);
+        assert!(!reports[1].is_valid());
+        assert!(matches!(
+            reports[1].files[0],
+            (ref name, ChecksumStatus::Mismatch { .. }) if name == "course_data.cdt"
+        ));
+        assert!(reports[1].files[1..]
+            .iter()
+            .all(|(_, status)| status.is_valid()));
+
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+}
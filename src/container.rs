@@ -0,0 +1,325 @@
+// Where the four course files are kept, behind one interface so loading and saving a course is
+// written once for every kind of container. Members are looked up by file name, ignoring case and
+// any directory they're in.
+
+use std::{
+    io::{Read, Write},
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    checksum::ChecksumPolicy,
+    course::{Course, CourseData},
+    level::Level,
+    Error,
+};
+
+// The largest of the four course files. Archive members are read up to this size so a malicious
+// archive can't make a container allocate without limit
+const MAX_MEMBER_SIZE: usize = Level::SIZE;
+
+pub trait CourseContainer {
+    // None if the container has no such member
+    fn open_member(&self, name: &str) -> Result<Option<Vec<u8>>, Error>;
+
+    // Replaces a member of the same name
+    fn write_member(&mut self, name: &str, bytes: &[u8]) -> Result<(), Error>;
+}
+
+impl Course {
+    // Fails with Error::MissingCourseData for the first file the container doesn't hold
+    pub fn from_container(container: &impl CourseContainer) -> Result<Course, Error> {
+        Course::from_container_with_policy(container, ChecksumPolicy::default())
+    }
+
+    pub fn from_container_with_policy(
+        container: &impl CourseContainer,
+        policy: ChecksumPolicy,
+    ) -> Result<Course, Error> {
+        let read = |data: CourseData| {
+            container
+                .open_member(data.file_name())?
+                .ok_or(Error::MissingCourseData(data))
+        };
+
+        Course::from_bytes_with_policy(
+            &read(CourseData::CourseData)?,
+            &read(CourseData::CourseDataSub)?,
+            &read(CourseData::Thumbnail0)?,
+            &read(CourseData::Thumbnail1)?,
+            policy,
+        )
+    }
+
+    pub fn to_container(&self, container: &mut impl CourseContainer) -> Result<(), Error> {
+        for data in CourseData::ALL {
+            container.write_member(data.file_name(), &self.to_data_bytes(data)?)?;
+        }
+        Ok(())
+    }
+}
+
+// A course directory as found on the Wii U, created on the first write
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirContainer {
+    path: PathBuf,
+}
+
+impl DirContainer {
+    pub fn new<P: AsRef<Path>>(path: P) -> DirContainer {
+        DirContainer {
+            path: path.as_ref().to_path_buf(),
+        }
+    }
+}
+
+impl CourseContainer for DirContainer {
+    // Only the exact name is tried, file systems decide whether case matters
+    fn open_member(&self, name: &str) -> Result<Option<Vec<u8>>, Error> {
+        match std::fs::read(self.path.join(name)) {
+            Ok(bytes) => Ok(Some(bytes)),
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(error) => Err(Error::Io(error)),
+        }
+    }
+
+    fn write_member(&mut self, name: &str, bytes: &[u8]) -> Result<(), Error> {
+        std::fs::create_dir_all(&self.path).map_err(Error::Io)?;
+        std::fs::write(self.path.join(name), bytes).map_err(Error::Io)
+    }
+}
+
+// Members by file name in the order they were added, shared by the archive containers
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct Members(Vec<(String, Vec<u8>)>);
+
+impl Members {
+    fn file_name(path: &str) -> &str {
+        path.rsplit(['/', '\\']).next().unwrap_or(path)
+    }
+
+    fn get(&self, name: &str) -> Option<Vec<u8>> {
+        self.0
+            .iter()
+            .find(|(member, _)| member.eq_ignore_ascii_case(name))
+            .map(|(_, bytes)| bytes.clone())
+    }
+
+    fn insert(&mut self, name: &str, bytes: &[u8]) {
+        let name = Members::file_name(name);
+        match self
+            .0
+            .iter_mut()
+            .find(|(member, _)| member.eq_ignore_ascii_case(name))
+        {
+            Some((_, member)) => *member = bytes.to_vec(),
+            None => self.0.push((name.to_string(), bytes.to_vec())),
+        }
+    }
+
+    // Fails with Error::FileTooLarge for a member over MAX_MEMBER_SIZE
+    fn read(reader: impl Read) -> Result<Vec<u8>, Error> {
+        let mut bytes = Vec::new();
+        reader
+            .take(MAX_MEMBER_SIZE as u64 + 1)
+            .read_to_end(&mut bytes)
+            .map_err(|_| Error::InvalidData)?;
+        if bytes.len() > MAX_MEMBER_SIZE {
+            return Err(Error::FileTooLarge);
+        }
+        Ok(bytes)
+    }
+}
+
+// A tar held in memory. Course::from_tar streams instead and reports what's missing
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TarContainer {
+    members: Members,
+}
+
+impl TarContainer {
+    pub fn new() -> TarContainer {
+        TarContainer::default()
+    }
+
+    // Reads every file in the archive, directories are skipped. Fails with Error::FileTooLarge for
+    // a file larger than any course file
+    pub fn read<R: Read>(reader: R) -> Result<TarContainer, Error> {
+        let mut container = TarContainer::new();
+        let mut archive = tar::Archive::new(reader);
+        for entry in archive.entries().map_err(|_| Error::InvalidData)? {
+            let mut entry = entry.map_err(|_| Error::InvalidData)?;
+            if entry.header().entry_type().is_dir() {
+                continue;
+            }
+            let path = String::from_utf8_lossy(&entry.path_bytes()).into_owned();
+            let bytes = Members::read(&mut entry)?;
+            container.members.insert(&path, &bytes);
+        }
+        Ok(container)
+    }
+
+    // Members are written in the order they were added, with the same metadata as Course::to_tar
+    pub fn write<W: Write>(&self, writer: W) -> Result<W, Error> {
+        let options = crate::course::TarOptions::default();
+        let mut builder = tar::Builder::new(writer);
+        for (name, bytes) in &self.members.0 {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(bytes.len() as u64);
+            header.set_mode(options.mode);
+            header.set_uid(options.uid);
+            header.set_gid(options.gid);
+            header.set_mtime(options.mtime);
+            header.set_cksum();
+            builder
+                .append_data(&mut header, name, bytes.as_slice())
+                .map_err(Error::Io)?;
+        }
+        builder.into_inner().map_err(Error::Io)
+    }
+}
+
+impl CourseContainer for TarContainer {
+    fn open_member(&self, name: &str) -> Result<Option<Vec<u8>>, Error> {
+        Ok(self.members.get(name))
+    }
+
+    fn write_member(&mut self, name: &str, bytes: &[u8]) -> Result<(), Error> {
+        self.members.insert(name, bytes);
+        Ok(())
+    }
+}
+
+// A zip held in memory, members are deflated when written
+#[cfg(feature = "zip")]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ZipContainer {
+    members: Members,
+}
+
+#[cfg(feature = "zip")]
+impl ZipContainer {
+    pub fn new() -> ZipContainer {
+        ZipContainer::default()
+    }
+
+    // Like TarContainer::read
+    pub fn read<R: Read + std::io::Seek>(reader: R) -> Result<ZipContainer, Error> {
+        let mut container = ZipContainer::new();
+        let mut archive = zip::ZipArchive::new(reader).map_err(|_| Error::InvalidData)?;
+        for index in 0..archive.len() {
+            let mut file = archive.by_index(index).map_err(|_| Error::InvalidData)?;
+            if file.is_dir() {
+                continue;
+            }
+            let name = file.name().to_string();
+            let bytes = Members::read(&mut file)?;
+            container.members.insert(&name, &bytes);
+        }
+        Ok(container)
+    }
+
+    pub fn write<W: Write + std::io::Seek>(&self, writer: W) -> Result<W, Error> {
+        let mut archive = zip::ZipWriter::new(writer);
+        let options = zip::write::SimpleFileOptions::default()
+            .compression_method(zip::CompressionMethod::Deflated);
+        for (name, bytes) in &self.members.0 {
+            archive
+                .start_file(name.as_str(), options)
+                .map_err(|_| Error::InvalidData)?;
+            archive.write_all(bytes).map_err(Error::Io)?;
+        }
+        archive.finish().map_err(|_| Error::InvalidData)
+    }
+}
+
+#[cfg(feature = "zip")]
+impl CourseContainer for ZipContainer {
+    fn open_member(&self, name: &str) -> Result<Option<Vec<u8>>, Error> {
+        Ok(self.members.get(name))
+    }
+
+    fn write_member(&mut self, name: &str, bytes: &[u8]) -> Result<(), Error> {
+        self.members.insert(name, bytes);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        fixtures::minimal_course,
+        level::{CourseTheme, GameMode},
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_tar_container() {
+        let course = minimal_course(GameMode::Mario3, CourseTheme::Castle);
+        let mut container = TarContainer::new();
+        assert!(matches!(
+            Course::from_container(&container),
+            Err(Error::MissingCourseData(CourseData::CourseData))
+        ));
+
+        course.to_container(&mut container).unwrap();
+        let bytes = container.write(Vec::new()).unwrap();
+        let mut archive = tar::Archive::new(bytes.as_slice());
+        assert_eq!(Course::from_tar(&mut archive).unwrap(), course);
+
+        let mut written = Vec::new();
+        course.to_tar(&mut tar::Builder::new(&mut written)).unwrap();
+        let container = TarContainer::read(written.as_slice()).unwrap();
+        assert_eq!(Course::from_container(&container).unwrap(), course);
+
+        let mut container = TarContainer::new();
+        container
+            .write_member("course_data.cdt", &vec![0; MAX_MEMBER_SIZE + 1])
+            .unwrap();
+        let bytes = container.write(Vec::new()).unwrap();
+        assert!(matches!(
+            TarContainer::read(bytes.as_slice()),
+            Err(Error::FileTooLarge)
+        ));
+    }
+
+    #[test]
+    fn test_dir_container() {
+        let course = minimal_course(GameMode::MarioWorld, CourseTheme::Water);
+        let path = std::env::temp_dir().join(format!("mm1-container-{}", std::process::id()));
+        let mut container = DirContainer::new(&path);
+        assert!(matches!(
+            Course::from_container(&container),
+            Err(Error::MissingCourseData(CourseData::CourseData))
+        ));
+
+        course.to_container(&mut container).unwrap();
+        assert_eq!(Course::from_dir(&path).unwrap(), course);
+        std::fs::remove_dir_all(&path).unwrap();
+    }
+
+    #[cfg(feature = "zip")]
+    #[test]
+    fn test_zip_container() {
+        use std::io::Cursor;
+
+        let course = minimal_course(GameMode::NewSuperMarioBrosU, CourseTheme::Airship);
+        let mut container = ZipContainer::new();
+        course.to_container(&mut container).unwrap();
+        let bytes = container.write(Cursor::new(Vec::new())).unwrap();
+
+        let container = ZipContainer::read(Cursor::new(bytes.into_inner())).unwrap();
+        assert_eq!(Course::from_container(&container).unwrap(), course);
+
+        let mut container = ZipContainer::new();
+        container
+            .write_member("course_data.cdt", &vec![0; MAX_MEMBER_SIZE + 1])
+            .unwrap();
+        let bytes = container.write(Cursor::new(Vec::new())).unwrap();
+        assert!(matches!(
+            ZipContainer::read(Cursor::new(bytes.into_inner())),
+            Err(Error::FileTooLarge)
+        ));
+    }
+}
@@ -0,0 +1,82 @@
+// Reports from changing the game style of a level or course with Level::set_game_mode and
+// Course::set_game_mode. Parts the new style doesn't offer are swapped for the closest one it
+// does, see ObjectType::replacement_in, or removed when there's nothing close.
+
+use crate::{
+    level::GameMode,
+    objects::{Object, ObjectType},
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConversionReport {
+    pub from: GameMode,
+    pub to: GameMode,
+    // Objects whose type, or whose child's type, was swapped, by index after converting
+    pub replaced: Vec<(usize, ObjectType, ObjectType)>,
+    // Objects with nothing close in the new style, by index before converting
+    pub removed_objects: Vec<(usize, Object)>,
+    // Objects that lost their child, by index after converting
+    pub removed_children: Vec<usize>,
+}
+
+impl ConversionReport {
+    pub fn is_lossless(&self) -> bool {
+        self.removed_objects.is_empty() && self.removed_children.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CourseConversionReport {
+    pub main: ConversionReport,
+    pub sub: ConversionReport,
+}
+
+impl CourseConversionReport {
+    pub fn is_lossless(&self) -> bool {
+        self.main.is_lossless() && self.sub.is_lossless()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        fixtures::minimal_course,
+        level::CourseTheme,
+        objects::Costume,
+        validation::{style_issues, validate},
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_set_game_mode() {
+        let mut course = minimal_course(GameMode::SuperMarioBros, CourseTheme::Overworld);
+        let mut block = Object::question_block(6, 4, Some(ObjectType::MysteryMushroom));
+        block.set_costume(Costume(3)).unwrap();
+        course.sub_level.objects.push(block);
+        let mut mushroom = Object::of(ObjectType::MysteryMushroom).unwrap().at(8, 1);
+        mushroom.set_big(true);
+        course.level.objects.insert(0, mushroom);
+        assert!(!course.level.objects[0].available_in(GameMode::MarioWorld));
+
+        let report = course.set_game_mode(GameMode::MarioWorld);
+        assert!(report.is_lossless());
+        assert_eq!(
+            report.main.replaced,
+            vec![(0, ObjectType::MysteryMushroom, ObjectType::SuperMushroom)]
+        );
+        assert!(!course.level.objects[0].is_big());
+        assert!(course.level.objects[0].available_in(GameMode::MarioWorld));
+        assert_eq!(report.sub.replaced.len(), 1);
+        let block = course.sub_level.objects.last().unwrap();
+        assert_eq!(block.child_object_type, i8::from(ObjectType::SuperMushroom));
+        assert_eq!(block.costume(), None);
+
+        for level in [&course.level, &course.sub_level] {
+            assert_eq!(level.game_mode, GameMode::MarioWorld);
+            assert!(style_issues(level).is_empty());
+        }
+        assert!(validate(&course.level).is_empty());
+        assert!(course.check_consistency().is_empty());
+    }
+}
@@ -1,7 +1,19 @@
-use std::io::Read;
-use crate::{level::Level, thumbnail::Thumbnail, Error};
+use std::{io::{Read, Write}, path::Path};
+use crate::{
+    checksum::ChecksumPolicy,
+    container::DirContainer,
+    convert::CourseConversionReport,
+    date::CreationTime,
+    level::{CourseTheme, GameMode, Level},
+    progress::ProgressSink,
+    render::{render_preview, render_thumbnail},
+    thumbnail::Thumbnail,
+    validation::{consistency_issues, validate_level_name, ConsistencyIssue, LevelNameIssue},
+    Error,
+};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum CourseData {
     CourseData,
     CourseDataSub,
@@ -9,7 +21,102 @@ pub enum CourseData {
     Thumbnail1,
 }
 
+impl CourseData {
+    pub const ALL: [CourseData; 4] = [
+        CourseData::CourseData,
+        CourseData::CourseDataSub,
+        CourseData::Thumbnail0,
+        CourseData::Thumbnail1,
+    ];
+
+    pub fn file_name(&self) -> &'static str {
+        match self {
+            CourseData::CourseData => "course_data.cdt",
+            CourseData::CourseDataSub => "course_data_sub.cdt",
+            CourseData::Thumbnail0 => "thumbnail0.tnl",
+            CourseData::Thumbnail1 => "thumbnail1.tnl",
+        }
+    }
+
+    // Ignores case, as archives made on some systems upper case names
+    pub fn from_file_name(name: &str) -> Option<CourseData> {
+        CourseData::ALL
+            .into_iter()
+            .find(|data| data.file_name().eq_ignore_ascii_case(name))
+    }
+}
+
+// Metadata for written tars, so archives can match what save dumping tools and injectors expect.
+// The defaults give reproducible archives
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TarOptions {
+    // Every file has to be listed once
+    pub order: [CourseData; 4],
+    pub mode: u32,
+    pub uid: u64,
+    pub gid: u64,
+    // Seconds since the Unix epoch
+    pub mtime: u64,
+    // Puts the files in a directory, e.g. course000 as dumped from the console
+    pub directory: Option<String>,
+}
+
+impl Default for TarOptions {
+    fn default() -> Self {
+        TarOptions {
+            order: CourseData::ALL,
+            mode: 0o644,
+            uid: 0,
+            gid: 0,
+            mtime: 0,
+            directory: None,
+        }
+    }
+}
+
+// The entries of a course tar, by path
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TarReport {
+    // Each course file once, the first copy in the archive is the one used
+    pub found: Vec<(CourseData, String)>,
+    pub missing: Vec<CourseData>,
+    // Files that aren't course files and later copies of ones that are, directories aren't listed
+    pub unexpected: Vec<String>,
+}
+
+impl TarReport {
+    pub fn is_complete(&self) -> bool {
+        self.missing.is_empty()
+    }
+
+    // Members may be nested in directories or start with ./, only the file name counts
+    fn add<R: std::io::Read>(&mut self, entry: &tar::Entry<R>) -> Option<CourseData> {
+        if entry.header().entry_type().is_dir() {
+            return None;
+        }
+        let path = String::from_utf8_lossy(&entry.path_bytes()).into_owned();
+        match path.rsplit(['/', '\\']).next().and_then(CourseData::from_file_name) {
+            Some(data) if !self.found.iter().any(|(found, _)| *found == data) => {
+                self.found.push((data, path));
+                Some(data)
+            }
+            _ => {
+                self.unexpected.push(path);
+                None
+            }
+        }
+    }
+
+    fn finish(&mut self) {
+        self.missing = CourseData::ALL
+            .into_iter()
+            .filter(|data| !self.found.iter().any(|(found, _)| found == data))
+            .collect();
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Course {
     pub level: Level,
     pub sub_level: Level,
@@ -27,54 +134,488 @@ impl Course {
         }
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
     pub fn from_bytes(level: &[u8], sub_level: &[u8], level_preview: &[u8], level_thumbnail: &[u8]) -> Result<Course, Error> {
+        Course::from_bytes_with_policy(level, sub_level, level_preview, level_thumbnail, ChecksumPolicy::default())
+    }
+
+    // The policy applies to all four files
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub fn from_bytes_with_policy(
+        level: &[u8],
+        sub_level: &[u8],
+        level_preview: &[u8],
+        level_thumbnail: &[u8],
+        policy: ChecksumPolicy,
+    ) -> Result<Course, Error> {
         Ok(Course {
-            level: Level::from_bytes(level)?,
-            sub_level: Level::from_bytes(sub_level)?,
-            level_preview: Thumbnail::from_bytes(level_preview),
-            level_thumbnail: Thumbnail::from_bytes(level_thumbnail),
+            level: Level::from_bytes_with_policy(level, policy)?,
+            sub_level: Level::from_bytes_with_policy(sub_level, policy)?,
+            level_preview: Thumbnail::from_bytes_with_policy(level_preview, policy)?,
+            level_thumbnail: Thumbnail::from_bytes_with_policy(level_thumbnail, policy)?,
         })
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
     pub fn from_tar<T: std::io::Read>(archive: &mut tar::Archive<T>) -> Result<Self, Error> {
+        Course::from_tar_with_policy(archive, ChecksumPolicy::default())
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub fn from_tar_with_policy<T: std::io::Read>(archive: &mut tar::Archive<T>, policy: ChecksumPolicy) -> Result<Self, Error> {
         let mut level = None;
         let mut sub_level = None;
         let mut level_preview = None;
         let mut level_thumbnail = None;
-    
+        // Shared by every member, each is parsed as soon as it's read
+        let mut buffer = Vec::with_capacity(Level::SIZE);
+        // What the archive held, returned when something is missing
+        let mut report = TarReport::default();
+
         for entry in archive.entries().map_err(|_| Error::InvalidData)? {
             let mut entry = entry.map_err(|_| Error::InvalidData)?;
-            match entry.path().map_err(|_| Error::InvalidData)?.file_name().unwrap().to_str().unwrap() {
-                "course_data.cdt" => {
-                    let mut buffer = Vec::new();
-                    entry.read_to_end(&mut buffer).map_err(|_| Error::InvalidData)?;
-                    level = Some(buffer);
-                }
-                "course_data_sub.cdt" => {
-                    let mut buffer = Vec::new();
-                    entry.read_to_end(&mut buffer).map_err(|_| Error::InvalidData)?;
-                    sub_level = Some(buffer);
-                }
-                "thumbnail0.tnl" => {
-                    let mut buffer = Vec::new();
-                    entry.read_to_end(&mut buffer).map_err(|_| Error::InvalidData)?;
-                    level_preview = Some(buffer);
-                }
-                "thumbnail1.tnl" => {
-                    let mut buffer = Vec::new();
-                    entry.read_to_end(&mut buffer).map_err(|_| Error::InvalidData)?;
-                    level_thumbnail = Some(buffer);
-                }
-                _ => {}
+            let Some(data) = report.add(&entry) else {
+                continue;
+            };
+            trace_span!("entry", data = ?data);
+
+            buffer.clear();
+            entry.read_to_end(&mut buffer).map_err(|_| Error::InvalidData)?;
+            match data {
+                CourseData::CourseData => level = Some(Level::from_bytes_with_policy(&buffer, policy)?),
+                CourseData::CourseDataSub => sub_level = Some(Level::from_bytes_with_policy(&buffer, policy)?),
+                CourseData::Thumbnail0 => level_preview = Some(Thumbnail::from_bytes_with_policy(&buffer, policy)?),
+                CourseData::Thumbnail1 => level_thumbnail = Some(Thumbnail::from_bytes_with_policy(&buffer, policy)?),
+            }
+
+            // The rest of the archive doesn't need to be read once every member is found
+            if level.is_some() && sub_level.is_some() && level_preview.is_some() && level_thumbnail.is_some() {
+                break;
+            }
+        }
+
+        match (level, sub_level, level_preview, level_thumbnail) {
+            (Some(level), Some(sub_level), Some(level_preview), Some(level_thumbnail)) => Ok(Course {
+                level,
+                sub_level,
+                level_preview,
+                level_thumbnail,
+            }),
+            _ => {
+                report.finish();
+                Err(Error::IncompleteTar(report))
+            }
+        }
+    }
+
+    // Lists the course files in an archive without parsing them
+    pub fn inspect_tar<T: std::io::Read>(archive: &mut tar::Archive<T>) -> Result<TarReport, Error> {
+        let mut report = TarReport::default();
+        for entry in archive.entries().map_err(|_| Error::InvalidData)? {
+            report.add(&entry.map_err(|_| Error::InvalidData)?);
+        }
+        report.finish();
+        Ok(report)
+    }
+
+    // Reads a course directory as found on the Wii U, e.g. course000
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(path = %path.as_ref().display())))]
+    pub fn from_dir<P: AsRef<Path>>(path: P) -> Result<Course, Error> {
+        Course::from_dir_with_policy(path, ChecksumPolicy::default())
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(path = %path.as_ref().display())))]
+    pub fn from_dir_with_policy<P: AsRef<Path>>(path: P, policy: ChecksumPolicy) -> Result<Course, Error> {
+        Course::from_container_with_policy(&DirContainer::new(path), policy)
+    }
+
+    // Opens either a course directory or a tar of the four course files
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(path = %path.as_ref().display())))]
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Course, Error> {
+        Course::from_path_with_policy(path, ChecksumPolicy::default())
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(path = %path.as_ref().display())))]
+    pub fn from_path_with_policy<P: AsRef<Path>>(path: P, policy: ChecksumPolicy) -> Result<Course, Error> {
+        if path.as_ref().is_dir() {
+            return Course::from_dir_with_policy(path, policy);
+        }
+
+        let file = std::fs::File::open(path).map_err(Error::Io)?;
+        Course::from_tar_with_policy(&mut tar::Archive::new(file), policy)
+    }
+
+    // Opens each path with from_path, a course failing to parse doesn't stop the others
+    pub fn from_paths<P: AsRef<Path>>(
+        paths: &[P],
+        progress: &mut impl ProgressSink,
+    ) -> Result<Vec<Result<Course, Error>>, Error> {
+        let mut courses = Vec::with_capacity(paths.len());
+        for (done, path) in paths.iter().enumerate() {
+            if !progress.progress(done, paths.len(), Some(path.as_ref())) {
+                return Err(Error::Cancelled);
+            }
+            courses.push(Course::from_path(path));
+        }
+        progress.progress(paths.len(), paths.len(), None);
+
+        Ok(courses)
+    }
+
+    // Both areas carry the name, style and creation time, the main area's are the ones shown
+    pub fn name(&self) -> &str {
+        &self.level.level_name
+    }
+
+    pub fn game_mode(&self) -> GameMode {
+        self.level.game_mode
+    }
+
+    pub fn creation_time(&self) -> CreationTime {
+        self.level.creation_time
+    }
+
+    pub fn set_creation_time(&mut self, creation_time: CreationTime) {
+        self.level.creation_time = creation_time;
+        self.sub_level.creation_time = creation_time;
+    }
+
+    // Themes of the main and sub area
+    pub fn theme_pair(&self) -> (CourseTheme, CourseTheme) {
+        (self.level.course_theme, self.sub_level.course_theme)
+    }
+
+    pub fn set_theme_pair(&mut self, main: CourseTheme, sub: CourseTheme) {
+        self.level.course_theme = main;
+        self.sub_level.course_theme = sub;
+    }
+
+    // Both areas always share a style, so they're converted together
+    pub fn set_game_mode(&mut self, game_mode: GameMode) -> CourseConversionReport {
+        CourseConversionReport {
+            main: self.level.set_game_mode(game_mode),
+            sub: self.sub_level.set_game_mode(game_mode),
+        }
+    }
+
+    // Sets the name of both areas, which the console expects to match. Checksums are computed when
+    // the course is written, so nothing else needs updating. Like Level::to_bytes only names that
+    // can't be written are refused
+    pub fn rename(&mut self, name: &str) -> Result<(), Error> {
+        if let Some(issue) = validate_level_name(name).into_iter().find(LevelNameIssue::is_fatal) {
+            return Err(Error::InvalidLevelName(issue));
+        }
+
+        self.level.level_name = name.to_string();
+        self.sub_level.level_name = name.to_string();
+        Ok(())
+    }
+
+    // Replaces both thumbnails with renders of the main level, e.g. for courses built in code. Fails
+    // with Error::InvalidData, leaving the thumbnails as they were, for a level too wide to render
+    pub fn regenerate_thumbnails(&mut self) -> Result<(), Error> {
+        self.level_preview = Thumbnail::from_image(&render_preview(&self.level)?)?;
+        self.level_thumbnail = Thumbnail::from_image(&render_thumbnail(&self.level)?)?;
+        Ok(())
+    }
+
+    // Checks the two areas against each other, see ConsistencyIssue
+    pub fn check_consistency(&self) -> Vec<ConsistencyIssue> {
+        consistency_issues(&self.level, &self.sub_level)
+    }
+
+    // Identifies the course by the contents of both areas, ignoring the thumbnails
+    pub fn content_hash(&self) -> Result<u64, Error> {
+        let level = crc32fast::hash(&self.level.to_bytes()?);
+        let sub_level = crc32fast::hash(&self.sub_level.to_bytes()?);
+        Ok((level as u64) << 32 | sub_level as u64)
+    }
+
+    pub fn to_data_bytes(&self, data: CourseData) -> Result<Vec<u8>, Error> {
+        match data {
+            CourseData::CourseData => self.level.to_bytes(),
+            CourseData::CourseDataSub => self.sub_level.to_bytes(),
+            CourseData::Thumbnail0 => self.level_preview.to_bytes(),
+            CourseData::Thumbnail1 => self.level_thumbnail.to_bytes(),
+        }
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(path = %path.as_ref().display())))]
+    pub fn to_dir<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        self.to_container(&mut DirContainer::new(path))
+    }
+
+    pub fn to_tar<W: Write>(&self, builder: &mut tar::Builder<W>) -> Result<(), Error> {
+        self.to_tar_with_options(builder, &TarOptions::default())
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub fn to_tar_with_options<W: Write>(&self, builder: &mut tar::Builder<W>, options: &TarOptions) -> Result<(), Error> {
+        for data in options.order {
+            let bytes = self.to_data_bytes(data)?;
+
+            let mut header = tar::Header::new_gnu();
+            header.set_size(bytes.len() as u64);
+            header.set_mode(options.mode);
+            header.set_uid(options.uid);
+            header.set_gid(options.gid);
+            header.set_mtime(options.mtime);
+            header.set_cksum();
+
+            let path = match &options.directory {
+                Some(directory) => format!("{}/{}", directory.trim_end_matches('/'), data.file_name()),
+                None => data.file_name().to_string(),
+            };
+            builder
+                .append_data(&mut header, path, bytes.as_slice())
+                .map_err(Error::Io)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        date::CreationDate,
+        fixtures::{minimal_course, minimal_course_tar},
+        level::{CourseTheme, GameMode},
+        thumbnail::{PREVIEW_SIZE, THUMBNAIL_SIZE},
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_regenerate_thumbnails() {
+        let mut course = minimal_course(GameMode::SuperMarioBros, CourseTheme::Overworld);
+        course.regenerate_thumbnails().unwrap();
+
+        for thumbnail in [&course.level_preview, &course.level_thumbnail] {
+            let bytes = thumbnail.to_bytes().unwrap();
+            assert_eq!(Thumbnail::from_bytes(&bytes).unwrap(), *thumbnail);
+        }
+        let preview = image::load_from_memory(&course.level_preview.jpeg_data).unwrap();
+        assert_eq!((preview.width(), preview.height()), PREVIEW_SIZE);
+        let thumbnail = image::load_from_memory(&course.level_thumbnail.jpeg_data).unwrap();
+        assert_eq!((thumbnail.width(), thumbnail.height()), THUMBNAIL_SIZE);
+
+        let previous = course.clone();
+        course.level.width = u32::MAX;
+        assert!(matches!(course.regenerate_thumbnails(), Err(Error::InvalidData)));
+        assert_eq!(course.level_preview, previous.level_preview);
+        assert_eq!(course.level_thumbnail, previous.level_thumbnail);
+    }
+
+    #[test]
+    fn test_checksum_policy() {
+        let course = minimal_course(GameMode::Mario3, CourseTheme::GhostHouse);
+        let data = CourseData::ALL.map(|data| course.to_data_bytes(data).unwrap());
+        let mut level = data[0].clone();
+        level[0x8] ^= 0xFF;
+        let mut preview = data[2].clone();
+        preview[0x0] ^= 0xFF;
+
+        let parse = |level: &[u8], preview: &[u8], policy| {
+            Course::from_bytes_with_policy(level, &data[1], preview, &data[3], policy)
+        };
+        assert!(parse(&data[0], &data[2], ChecksumPolicy::Require).is_ok());
+        assert!(matches!(
+            parse(&level, &data[2], ChecksumPolicy::Require),
+            Err(Error::ChecksumMismatch { .. })
+        ));
+        assert!(matches!(
+            parse(&data[0], &preview, ChecksumPolicy::Require),
+            Err(Error::ChecksumMismatch { .. })
+        ));
+        for policy in [ChecksumPolicy::WarnOnly, ChecksumPolicy::Ignore] {
+            assert_eq!(parse(&level, &preview, policy).unwrap(), course);
+        }
+    }
+
+    #[test]
+    fn test_from_tar_matching() {
+        let course = minimal_course(GameMode::MarioWorld, CourseTheme::Airship);
+        let mut builder = tar::Builder::new(Vec::new());
+        for (data, path) in CourseData::ALL.into_iter().zip([
+            "./course000/COURSE_DATA.CDT",
+            "course000/course_data_sub.cdt",
+            "./Thumbnail0.tnl",
+            "course000/readme.txt",
+        ]) {
+            let bytes = course.to_data_bytes(data).unwrap();
+            let mut header = tar::Header::new_gnu();
+            header.set_size(bytes.len() as u64);
+            header.set_cksum();
+            builder.append_data(&mut header, path, bytes.as_slice()).unwrap();
+        }
+        let tar = builder.into_inner().unwrap();
+
+        let expected = TarReport {
+            found: vec![
+                (CourseData::CourseData, "course000/COURSE_DATA.CDT".to_string()),
+                (CourseData::CourseDataSub, "course000/course_data_sub.cdt".to_string()),
+                (CourseData::Thumbnail0, "Thumbnail0.tnl".to_string()),
+            ],
+            missing: vec![CourseData::Thumbnail1],
+            unexpected: vec!["course000/readme.txt".to_string()],
+        };
+        match Course::from_tar(&mut tar::Archive::new(tar.as_slice())) {
+            Err(Error::IncompleteTar(report)) => assert_eq!(report, expected),
+            result => panic!("unexpected {:?}", result),
+        }
+        assert_eq!(Course::inspect_tar(&mut tar::Archive::new(tar.as_slice())).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_from_tar_duplicates() {
+        let course = minimal_course(GameMode::Mario3, CourseTheme::Water);
+        let mut other = course.clone();
+        other.rename("Other").unwrap();
+        let mut builder = tar::Builder::new(Vec::new());
+        for (course, data, path) in [
+            (&course, CourseData::CourseData, "course_data.cdt"),
+            (&other, CourseData::CourseData, "copy/course_data.cdt"),
+            (&course, CourseData::CourseDataSub, "course_data_sub.cdt"),
+            (&course, CourseData::Thumbnail0, "thumbnail0.tnl"),
+            (&course, CourseData::Thumbnail1, "thumbnail1.tnl"),
+        ] {
+            let bytes = course.to_data_bytes(data).unwrap();
+            let mut header = tar::Header::new_gnu();
+            header.set_size(bytes.len() as u64);
+            header.set_cksum();
+            builder.append_data(&mut header, path, bytes.as_slice()).unwrap();
+        }
+        let tar = builder.into_inner().unwrap();
+
+        assert_eq!(Course::from_tar(&mut tar::Archive::new(tar.as_slice())).unwrap(), course);
+        let report = Course::inspect_tar(&mut tar::Archive::new(tar.as_slice())).unwrap();
+        assert_eq!(report.found.len(), 4);
+        assert_eq!(report.unexpected, vec!["copy/course_data.cdt".to_string()]);
+    }
+
+    #[test]
+    fn test_from_tar_short_thumbnail() {
+        let course = minimal_course(GameMode::Mario3, CourseTheme::Overworld);
+        let mut builder = tar::Builder::new(Vec::new());
+        for data in CourseData::ALL {
+            let mut bytes = course.to_data_bytes(data).unwrap();
+            if data == CourseData::Thumbnail0 {
+                bytes.truncate(16);
             }
+            let mut header = tar::Header::new_gnu();
+            header.set_size(bytes.len() as u64);
+            header.set_cksum();
+            builder.append_data(&mut header, data.file_name(), bytes.as_slice()).unwrap();
         }
-        
-        Course::from_bytes(
-            &level.ok_or(Error::MissingCourseData(CourseData::CourseData))?,
-            &sub_level.ok_or(Error::MissingCourseData(CourseData::CourseDataSub))?,
-            &level_preview.ok_or(Error::MissingCourseData(CourseData::Thumbnail0))?,
-            &level_thumbnail.ok_or(Error::MissingCourseData(CourseData::Thumbnail1))?,
-        )
-        
+        let tar = builder.into_inner().unwrap();
+
+        assert!(matches!(
+            Course::from_tar(&mut tar::Archive::new(tar.as_slice())),
+            Err(Error::WrongSize { actual: 16, kind: crate::FileKind::Thumbnail, .. })
+        ));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_from_tar_with_policy() {
+        let course = minimal_course(GameMode::MarioWorld, CourseTheme::Castle);
+        let mut builder = tar::Builder::new(Vec::new());
+        for data in CourseData::ALL {
+            let mut bytes = course.to_data_bytes(data).unwrap();
+            if data == CourseData::Thumbnail1 {
+                bytes[0x0] ^= 0xFF;
+            }
+            let mut header = tar::Header::new_gnu();
+            header.set_size(bytes.len() as u64);
+            header.set_cksum();
+            builder.append_data(&mut header, data.file_name(), bytes.as_slice()).unwrap();
+        }
+        let tar = builder.into_inner().unwrap();
+
+        let parse = |policy| Course::from_tar_with_policy(&mut tar::Archive::new(tar.as_slice()), policy);
+        assert!(matches!(parse(ChecksumPolicy::Require), Err(Error::ChecksumMismatch { .. })));
+        assert_eq!(parse(ChecksumPolicy::Ignore).unwrap(), course);
+        assert_eq!(Course::from_tar(&mut tar::Archive::new(tar.as_slice())).unwrap(), course);
+    }
+
+    #[test]
+    fn test_to_tar_with_options() {
+        let course = minimal_course(GameMode::SuperMarioBros, CourseTheme::Castle);
+        let options = TarOptions {
+            order: [
+                CourseData::Thumbnail0,
+                CourseData::Thumbnail1,
+                CourseData::CourseData,
+                CourseData::CourseDataSub,
+            ],
+            mode: 0o666,
+            uid: 1000,
+            gid: 100,
+            mtime: 1_450_000_000,
+            directory: Some("course000/".to_string()),
+        };
+        let mut builder = tar::Builder::new(Vec::new());
+        course.to_tar_with_options(&mut builder, &options).unwrap();
+        let tar = builder.into_inner().unwrap();
+
+        let mut archive = tar::Archive::new(tar.as_slice());
+        for (entry, data) in archive.entries().unwrap().zip(options.order) {
+            let entry = entry.unwrap();
+            let header = entry.header();
+            assert_eq!(entry.path().unwrap().to_str().unwrap(), format!("course000/{}", data.file_name()));
+            assert_eq!(header.mode().unwrap(), 0o666);
+            assert_eq!((header.uid().unwrap(), header.gid().unwrap()), (1000, 100));
+            assert_eq!(header.mtime().unwrap(), 1_450_000_000);
+        }
+        assert_eq!(Course::from_tar(&mut tar::Archive::new(tar.as_slice())).unwrap(), course);
+    }
+
+    #[test]
+    fn test_accessors() {
+        let mut course = minimal_course(GameMode::Mario3, CourseTheme::Overworld);
+        assert_eq!(course.game_mode(), GameMode::Mario3);
+
+        course.set_theme_pair(CourseTheme::Airship, CourseTheme::Castle);
+        assert_eq!(course.theme_pair(), (CourseTheme::Airship, CourseTheme::Castle));
+        assert_eq!(course.sub_level.course_theme, CourseTheme::Castle);
+
+        let creation_time = CreationDate::new(2017, 3, 4, 5, 6)
+            .and_then(CreationDate::to_creation_time)
+            .unwrap();
+        course.set_creation_time(creation_time);
+        assert_eq!(course.creation_time(), creation_time);
+        assert_eq!(course.sub_level.creation_time, creation_time);
+    }
+
+    #[test]
+    fn test_rename() {
+        let mut course = minimal_course(GameMode::MarioWorld, CourseTheme::Underground);
+        course.rename("Über Castle").unwrap();
+        let bytes = course.to_data_bytes(CourseData::CourseDataSub).unwrap();
+        assert_eq!(Level::from_bytes(&bytes).unwrap().level_name, "Über Castle");
+        assert_eq!(course.level.level_name, course.sub_level.level_name);
+
+        assert_eq!(course.name(), "Über Castle");
+        assert!(course.rename(&"a".repeat(33)).is_err());
+        assert!(course.rename("🍄").is_err());
+        assert_eq!(course.level.level_name, "Über Castle");
+
+        // Control characters only show oddly, the name is still written
+        course.rename("Tab\tCastle").unwrap();
+        assert_eq!(course.sub_level.level_name, "Tab\tCastle");
+        assert!(course.to_data_bytes(CourseData::CourseData).is_ok());
+    }
+
+    #[test]
+    fn test_from_tar_stops_after_members() {
+        let mut tar = minimal_course_tar(GameMode::Mario3, CourseTheme::GhostHouse).unwrap();
+        // Replace the end of archive marker with a corrupt header, which is never reached
+        tar.truncate(tar.len() - 1024);
+        tar.extend_from_slice(&[0xFF; 512]);
+
+        assert_eq!(
+            Course::from_tar(&mut tar::Archive::new(tar.as_slice())).unwrap(),
+            minimal_course(GameMode::Mario3, CourseTheme::GhostHouse)
+        );
+    }
+}
@@ -0,0 +1,220 @@
+// Course creation times. Levels store them as CreationTime, which is chrono's NaiveDateTime with
+// the chrono feature, time's PrimitiveDateTime with only the time feature, and the plain
+// CreationDate below with neither. chrono wins when both are enabled.
+
+use std::{fmt, str::FromStr};
+
+#[cfg(feature = "chrono")]
+pub type CreationTime = chrono::NaiveDateTime;
+#[cfg(all(feature = "time", not(feature = "chrono")))]
+pub type CreationTime = time::PrimitiveDateTime;
+#[cfg(not(any(feature = "chrono", feature = "time")))]
+pub type CreationTime = CreationDate;
+
+// Creation time as stored in the level header, with minute precision
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CreationDate {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+    pub hour: u8,
+    pub minute: u8,
+}
+
+// The unix epoch, matching chrono's default
+impl Default for CreationDate {
+    fn default() -> Self {
+        CreationDate {
+            year: 1970,
+            month: 1,
+            day: 1,
+            hour: 0,
+            minute: 0,
+        }
+    }
+}
+
+impl CreationDate {
+    pub fn new(year: u16, month: u8, day: u8, hour: u8, minute: u8) -> Option<CreationDate> {
+        let date = CreationDate {
+            year,
+            month,
+            day,
+            hour,
+            minute,
+        };
+        date.is_valid().then_some(date)
+    }
+
+    pub fn is_valid(&self) -> bool {
+        (1..=12).contains(&self.month)
+            && (1..=days_in_month(self.year, self.month)).contains(&self.day)
+            && self.hour < 24
+            && self.minute < 60
+    }
+
+    // None when the date isn't valid
+    pub fn to_creation_time(self) -> Option<CreationTime> {
+        if !self.is_valid() {
+            return None;
+        }
+
+        #[cfg(feature = "chrono")]
+        return chrono::NaiveDate::from_ymd_opt(
+            self.year.into(),
+            self.month.into(),
+            self.day.into(),
+        )
+        .and_then(|date| date.and_hms_opt(self.hour.into(), self.minute.into(), 0));
+
+        #[cfg(all(feature = "time", not(feature = "chrono")))]
+        return time::Month::try_from(self.month)
+            .and_then(|month| time::Date::from_calendar_date(self.year.into(), month, self.day))
+            .and_then(|date| date.with_hms(self.hour, self.minute, 0))
+            .ok();
+
+        #[cfg(not(any(feature = "chrono", feature = "time")))]
+        return Some(self);
+    }
+
+    pub fn from_creation_time(time: &CreationTime) -> CreationDate {
+        #[cfg(feature = "chrono")]
+        return {
+            use chrono::{Datelike, Timelike};
+            CreationDate {
+                year: time.year() as u16,
+                month: time.month() as u8,
+                day: time.day() as u8,
+                hour: time.hour() as u8,
+                minute: time.minute() as u8,
+            }
+        };
+
+        #[cfg(all(feature = "time", not(feature = "chrono")))]
+        return CreationDate {
+            year: time.year() as u16,
+            month: time.month().into(),
+            day: time.day(),
+            hour: time.hour(),
+            minute: time.minute(),
+        };
+
+        #[cfg(not(any(feature = "chrono", feature = "time")))]
+        return *time;
+    }
+
+    // Seconds since the unix epoch, treating the date as UTC
+    pub fn unix_timestamp(&self) -> i64 {
+        // Days from civil, see http://howardhinnant.github.io/date_algorithms.html
+        let year = i64::from(self.year) - i64::from(self.month <= 2);
+        let era = year.div_euclid(400);
+        let year_of_era = year - era * 400;
+        let month = i64::from(self.month);
+        let day_of_year =
+            (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + i64::from(self.day) - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+        let days = era * 146097 + day_of_era - 719468;
+
+        days * 86400 + i64::from(self.hour) * 3600 + i64::from(self.minute) * 60
+    }
+
+    // None when the timestamp is outside the years the header can store, seconds are dropped
+    pub fn from_unix_timestamp(timestamp: i64) -> Option<CreationDate> {
+        let days = timestamp.div_euclid(86400);
+        let seconds = timestamp.rem_euclid(86400);
+
+        // Civil from days, the inverse of unix_timestamp
+        let days = days + 719468;
+        let era = days.div_euclid(146097);
+        let day_of_era = days - era * 146097;
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let month_index = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+        let month = if month_index < 10 {
+            month_index + 3
+        } else {
+            month_index - 9
+        };
+        let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+        CreationDate::new(
+            u16::try_from(year).ok()?,
+            month as u8,
+            day as u8,
+            (seconds / 3600) as u8,
+            (seconds % 3600 / 60) as u8,
+        )
+    }
+}
+
+fn days_in_month(year: u16, month: u8) -> u8 {
+    match month {
+        2 if year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400)) => {
+            29
+        }
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+// Formatted as YYYY-MM-DDTHH:MM
+impl fmt::Display for CreationDate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:04}-{:02}-{:02}T{:02}:{:02}",
+            self.year, self.month, self.day, self.hour, self.minute
+        )
+    }
+}
+
+impl FromStr for CreationDate {
+    type Err = crate::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let field = |range: std::ops::Range<usize>| {
+            s.get(range)
+                .filter(|field| field.bytes().all(|byte| byte.is_ascii_digit()))
+                .and_then(|field| field.parse::<u16>().ok())
+                .ok_or(crate::Error::InvalidData)
+        };
+        let separators = [(4, b'-'), (7, b'-'), (10, b'T'), (13, b':')];
+        if s.len() != 16
+            || separators
+                .iter()
+                .any(|&(i, separator)| s.as_bytes()[i] != separator)
+        {
+            return Err(crate::Error::InvalidData);
+        }
+
+        CreationDate::new(
+            field(0..4)?,
+            field(5..7)? as u8,
+            field(8..10)? as u8,
+            field(11..13)? as u8,
+            field(14..16)? as u8,
+        )
+        .ok_or(crate::Error::InvalidData)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unix_timestamp_round_trip() {
+        let date = CreationDate::new(2016, 2, 29, 13, 37).unwrap();
+        assert_eq!(date.unix_timestamp(), 1456753020);
+        assert_eq!(CreationDate::from_unix_timestamp(1456753020), Some(date));
+        assert_eq!(CreationDate::default().unix_timestamp(), 0);
+
+        assert_eq!(date.to_string(), "2016-02-29T13:37");
+        assert_eq!("2016-02-29T13:37".parse::<CreationDate>().unwrap(), date);
+        assert!("2015-02-29T13:37".parse::<CreationDate>().is_err());
+    }
+}
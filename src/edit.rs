@@ -0,0 +1,519 @@
+// Reversible editing of a level. Every change made through an EditSession is recorded so
+// frontends can offer undo and redo without tracking history themselves, and Level::edit groups
+// changes into a transaction that's only applied if the result validates.
+
+use crate::{
+    level::{AutoScroll, CourseTheme, GameMode, Level},
+    objects::Object,
+    sound_effects::SoundEffect,
+    validation::{ValidationIssue, Validator},
+    Error,
+};
+
+// A header value, holding the new value when applied and the old one once swapped into place
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HeaderField {
+    Name(String),
+    GameMode(GameMode),
+    CourseTheme(CourseTheme),
+    TimeLimit(u16),
+    AutoScroll(AutoScroll),
+    Flags(u8),
+    Width(u32),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Edit {
+    AddObject(usize, Object),
+    RemoveObject(usize, Object),
+    // Object index and the position to swap in, in tenths of blocks
+    MoveObject(usize, u32, i16),
+    Header(HeaderField),
+    // Resizes the effect table to the length given last, then fills the slot and attaches the
+    // objects to it
+    PlaceEffect(usize, SoundEffect, Vec<usize>, usize),
+    // Empties the slot and detaches the objects from it, then resizes the effect table to the
+    // length given last
+    RemoveEffect(usize, SoundEffect, Vec<usize>, usize),
+}
+
+// What changed in a level edited through an EditSession, including by undo and redo
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChangeEvent {
+    ObjectAdded { index: usize, object: Object },
+    ObjectRemoved { index: usize, object: Object },
+    // Position in tenths of blocks
+    ObjectMoved { index: usize, x: u32, y: i16 },
+    // Holds the new value
+    HeaderChanged(HeaderField),
+    EffectPlaced { index: usize, effect: SoundEffect },
+    EffectRemoved { index: usize, effect: SoundEffect },
+}
+
+impl Edit {
+    fn event(&self) -> ChangeEvent {
+        match self {
+            Edit::AddObject(index, object) => ChangeEvent::ObjectAdded {
+                index: *index,
+                object: object.clone(),
+            },
+            Edit::RemoveObject(index, object) => ChangeEvent::ObjectRemoved {
+                index: *index,
+                object: object.clone(),
+            },
+            Edit::MoveObject(index, x, y) => ChangeEvent::ObjectMoved {
+                index: *index,
+                x: *x,
+                y: *y,
+            },
+            Edit::Header(field) => ChangeEvent::HeaderChanged(field.clone()),
+            Edit::PlaceEffect(index, effect, _, _) => ChangeEvent::EffectPlaced {
+                index: *index,
+                effect: effect.clone(),
+            },
+            Edit::RemoveEffect(index, effect, _, _) => ChangeEvent::EffectRemoved {
+                index: *index,
+                effect: effect.clone(),
+            },
+        }
+    }
+
+    // Fails with Error::InvalidData for a block too far out to store in tenths of blocks
+    fn move_object(index: usize, x: u32, y: i16) -> Result<Edit, Error> {
+        Ok(Edit::MoveObject(
+            index,
+            x.checked_mul(10).ok_or(Error::InvalidData)?,
+            y.checked_mul(10).ok_or(Error::InvalidData)?,
+        ))
+    }
+
+    // Applies the edit and returns the edit undoing it
+    fn apply(self, level: &mut Level) -> Edit {
+        match self {
+            Edit::AddObject(index, object) => {
+                level.objects.insert(index, object.clone());
+                Edit::RemoveObject(index, object)
+            }
+            Edit::RemoveObject(index, object) => {
+                level.objects.remove(index);
+                Edit::AddObject(index, object)
+            }
+            Edit::MoveObject(index, x, y) => {
+                let object = &mut level.objects[index];
+                let from = (object.x_position, object.y_position);
+                object.x_position = x;
+                object.y_position = y;
+                Edit::MoveObject(index, from.0, from.1)
+            }
+            Edit::Header(mut field) => {
+                match &mut field {
+                    HeaderField::Name(name) => std::mem::swap(name, &mut level.level_name),
+                    HeaderField::GameMode(game_mode) => {
+                        std::mem::swap(game_mode, &mut level.game_mode)
+                    }
+                    HeaderField::CourseTheme(course_theme) => {
+                        std::mem::swap(course_theme, &mut level.course_theme)
+                    }
+                    HeaderField::TimeLimit(time_limit) => {
+                        std::mem::swap(time_limit, &mut level.time_limit)
+                    }
+                    HeaderField::AutoScroll(auto_scroll) => {
+                        std::mem::swap(auto_scroll, &mut level.auto_scroll)
+                    }
+                    HeaderField::Flags(flags) => std::mem::swap(flags, &mut level.flags),
+                    HeaderField::Width(width) => std::mem::swap(width, &mut level.width),
+                }
+                Edit::Header(field)
+            }
+            Edit::PlaceEffect(index, effect, objects, length) => {
+                let from = level.sound_effects.len();
+                level.sound_effects.resize(length, SoundEffect::default());
+                level.sound_effects[index] = effect.clone();
+                for &object in &objects {
+                    level.objects[object].effect_index = index as i16;
+                }
+                Edit::RemoveEffect(index, effect, objects, from)
+            }
+            Edit::RemoveEffect(index, effect, objects, length) => {
+                level.sound_effects[index] = SoundEffect::default();
+                for &object in &objects {
+                    level.objects[object].effect_index = -1;
+                }
+                let from = level.sound_effects.len();
+                level.sound_effects.resize(length, SoundEffect::default());
+                Edit::PlaceEffect(index, effect, objects, from)
+            }
+        }
+    }
+}
+
+type Observer = Box<dyn FnMut(&ChangeEvent)>;
+
+pub struct EditSession {
+    level: Level,
+    undo: Vec<Edit>,
+    redo: Vec<Edit>,
+    observers: Vec<Observer>,
+}
+
+impl std::fmt::Debug for EditSession {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EditSession")
+            .field("level", &self.level)
+            .field("undo", &self.undo)
+            .field("redo", &self.redo)
+            .finish_non_exhaustive()
+    }
+}
+
+impl EditSession {
+    pub fn new(level: Level) -> EditSession {
+        EditSession {
+            level,
+            undo: Vec::new(),
+            redo: Vec::new(),
+            observers: Vec::new(),
+        }
+    }
+
+    // Calls the observer after every change, e.g. to send events down a channel
+    pub fn subscribe(&mut self, observer: impl FnMut(&ChangeEvent) + 'static) {
+        self.observers.push(Box::new(observer));
+    }
+
+    pub fn level(&self) -> &Level {
+        &self.level
+    }
+
+    pub fn into_level(self) -> Level {
+        self.level
+    }
+
+    // Appends the object, returning its index
+    pub fn add_object(&mut self, object: Object) -> usize {
+        let index = self.level.objects.len();
+        self.record(Edit::AddObject(index, object));
+        index
+    }
+
+    pub fn remove_object(&mut self, index: usize) -> Result<Object, Error> {
+        let object = self
+            .level
+            .objects
+            .get(index)
+            .ok_or(Error::InvalidData)?
+            .clone();
+        self.record(Edit::RemoveObject(index, object.clone()));
+        Ok(object)
+    }
+
+    // Moves the object to the given block, failing with Error::InvalidData for a block too far out
+    // to store
+    pub fn move_object(&mut self, index: usize, x: u32, y: i16) -> Result<(), Error> {
+        if index >= self.level.objects.len() {
+            return Err(Error::InvalidData);
+        }
+        self.record(Edit::move_object(index, x, y)?);
+        Ok(())
+    }
+
+    pub fn set_header(&mut self, field: HeaderField) {
+        self.record(Edit::Header(field));
+    }
+
+    // Places the effect with Level::place_sound_effect, returning the slot used
+    pub fn place_sound_effect(&mut self, sound_type: u8, x: u32, y: i16) -> Result<usize, Error> {
+        let mut level = self.level.clone();
+        let index = level.place_sound_effect(sound_type, x, y)?;
+        let objects = (0..level.objects.len())
+            .filter(|&object| {
+                level.objects[object].effect_index != self.level.objects[object].effect_index
+            })
+            .collect();
+        let effect = level.sound_effects[index].clone();
+
+        // The table may have grown to make room, which undoing shrinks back
+        self.record(Edit::PlaceEffect(
+            index,
+            effect,
+            objects,
+            level.sound_effects.len(),
+        ));
+        Ok(index)
+    }
+
+    // Empties the slot, detaching every object referencing it
+    pub fn remove_sound_effect(&mut self, index: usize) -> Result<SoundEffect, Error> {
+        let effect = self
+            .level
+            .sound_effects
+            .get(index)
+            .filter(|effect| !effect.is_empty())
+            .ok_or(Error::InvalidData)?
+            .clone();
+        let objects = (0..self.level.objects.len())
+            .filter(|&object| self.level.objects[object].effect_index == index as i16)
+            .collect();
+
+        let length = self.level.sound_effects.len();
+        self.record(Edit::RemoveEffect(index, effect.clone(), objects, length));
+        Ok(effect)
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    // Reverts the latest edit, false if there's nothing to undo
+    pub fn undo(&mut self) -> bool {
+        match self.undo.pop() {
+            Some(edit) => {
+                let undone = self.apply(edit);
+                self.redo.push(undone);
+                true
+            }
+            None => false,
+        }
+    }
+
+    // Reapplies the latest undone edit, false if there's nothing to redo
+    pub fn redo(&mut self) -> bool {
+        match self.redo.pop() {
+            Some(edit) => {
+                let undone = self.apply(edit);
+                self.undo.push(undone);
+                true
+            }
+            None => false,
+        }
+    }
+
+    // A new edit discards anything that could have been redone
+    fn record(&mut self, edit: Edit) {
+        let undone = self.apply(edit);
+        self.undo.push(undone);
+        self.redo.clear();
+    }
+
+    // Applies the edit and notifies observers, returning the edit undoing it
+    fn apply(&mut self, edit: Edit) -> Edit {
+        let event = (!self.observers.is_empty()).then(|| edit.event());
+        let undone = edit.apply(&mut self.level);
+        if let Some(event) = event {
+            for observer in &mut self.observers {
+                observer(&event);
+            }
+        }
+        undone
+    }
+}
+
+// Changes made inside Level::edit, applied to a working copy of the level
+#[derive(Debug)]
+pub struct Transaction {
+    level: Level,
+}
+
+impl Transaction {
+    // The level as it would be after committing
+    pub fn level(&self) -> &Level {
+        &self.level
+    }
+
+    pub fn add_object(&mut self, object: Object) -> usize {
+        self.level.objects.push(object);
+        self.level.objects.len() - 1
+    }
+
+    pub fn remove_object(&mut self, index: usize) -> Result<Object, Error> {
+        if index >= self.level.objects.len() {
+            return Err(Error::InvalidData);
+        }
+        Ok(self.level.objects.remove(index))
+    }
+
+    // Moves the object to the given block, failing with Error::InvalidData for a block too far out
+    // to store
+    pub fn move_object(&mut self, index: usize, x: u32, y: i16) -> Result<(), Error> {
+        if index >= self.level.objects.len() {
+            return Err(Error::InvalidData);
+        }
+        Edit::move_object(index, x, y)?.apply(&mut self.level);
+        Ok(())
+    }
+
+    pub fn set_header(&mut self, field: HeaderField) {
+        Edit::Header(field).apply(&mut self.level);
+    }
+}
+
+impl Level {
+    // Runs the edits in a transaction and applies them only if the result passes the default
+    // validation rules. On failure the level is left untouched and every issue is returned
+    pub fn edit<F: FnOnce(&mut Transaction)>(
+        &mut self,
+        edits: F,
+    ) -> Result<(), Vec<ValidationIssue>> {
+        self.edit_with(&Validator::default(), edits)
+    }
+
+    pub fn edit_with<F: FnOnce(&mut Transaction)>(
+        &mut self,
+        validator: &Validator,
+        edits: F,
+    ) -> Result<(), Vec<ValidationIssue>> {
+        let mut transaction = Transaction {
+            level: self.clone(),
+        };
+        edits(&mut transaction);
+
+        let issues = validator.validate(&transaction.level);
+        if !issues.is_empty() {
+            return Err(issues);
+        }
+        *self = transaction.level;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixtures::minimal_level;
+
+    use super::*;
+
+    #[test]
+    fn test_undo_redo() {
+        let original = minimal_level(GameMode::MarioWorld, CourseTheme::Underground);
+        let mut session = EditSession::new(original.clone());
+
+        let index = session.add_object(Object::goomba(5, 1));
+        session.move_object(index, 6, 2).unwrap();
+        session.remove_object(0).unwrap();
+        session.set_header(HeaderField::Name("Edited".to_string()));
+        session.set_header(HeaderField::TimeLimit(100));
+        let edited = session.level().clone();
+        assert_eq!(edited.level_name, "Edited");
+        assert_eq!(edited.objects.last(), Some(&Object::goomba(6, 2)));
+
+        while session.undo() {}
+        assert_eq!(session.level(), &original);
+        assert!(!session.can_undo());
+
+        while session.redo() {}
+        assert_eq!(session.level(), &edited);
+
+        session.undo();
+        session.set_header(HeaderField::Width(480));
+        assert!(!session.can_redo());
+        assert!(session.remove_object(edited.objects.len()).is_err());
+
+        let before = session.level().clone();
+        assert!(matches!(
+            session.move_object(0, u32::MAX, 0),
+            Err(Error::InvalidData)
+        ));
+        assert!(session.move_object(0, 0, i16::MAX).is_err());
+        assert_eq!(session.level(), &before);
+    }
+
+    #[test]
+    fn test_undo_effect_table_length() {
+        let mut level = minimal_level(GameMode::Mario3, CourseTheme::Water);
+        level.sound_effects.clear();
+        let mut session = EditSession::new(level.clone());
+
+        session.place_sound_effect(2, 5, 1).unwrap();
+        assert_eq!(session.level().sound_effects.len(), Level::SOUND_EFFECTS);
+        session.undo();
+        assert_eq!(session.level(), &level);
+        session.redo();
+        assert_eq!(session.level().sound_effects.len(), Level::SOUND_EFFECTS);
+    }
+
+    #[test]
+    fn test_change_events() {
+        let mut session = EditSession::new(minimal_level(
+            GameMode::SuperMarioBros,
+            CourseTheme::Airship,
+        ));
+        let (sender, receiver) = std::sync::mpsc::channel();
+        session.subscribe(move |event| sender.send(event.clone()).unwrap());
+
+        let index = session.add_object(Object::goomba(5, 1));
+        let slot = session.place_sound_effect(2, 5, 1).unwrap();
+        assert_eq!(session.level().objects[index].effect_index, slot as i16);
+        let effect = session.remove_sound_effect(slot).unwrap();
+        assert_eq!(session.level().objects[index].effect_index, -1);
+        session.undo();
+        assert_eq!(session.level().objects[index].effect_index, slot as i16);
+        session.set_header(HeaderField::Flags(1));
+
+        assert_eq!(
+            receiver.try_iter().collect::<Vec<_>>(),
+            vec![
+                ChangeEvent::ObjectAdded {
+                    index,
+                    object: Object::goomba(5, 1)
+                },
+                ChangeEvent::EffectPlaced {
+                    index: slot,
+                    effect: effect.clone()
+                },
+                ChangeEvent::EffectRemoved {
+                    index: slot,
+                    effect: effect.clone()
+                },
+                ChangeEvent::EffectPlaced {
+                    index: slot,
+                    effect
+                },
+                ChangeEvent::HeaderChanged(HeaderField::Flags(1)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_transaction() {
+        let original = minimal_level(GameMode::MarioWorld, CourseTheme::Underground);
+        let mut level = original.clone();
+
+        let result = level.edit(|transaction| {
+            transaction.add_object(Object::goomba(5, 1));
+            transaction.add_object(Object::goomba(500, 1));
+        });
+        assert_eq!(
+            result,
+            Err(vec![ValidationIssue::ObjectOutOfBounds {
+                object: original.objects.len() + 1,
+                x: 500,
+                y: 1,
+                suggested_x: 23,
+                suggested_y: 1,
+            }])
+        );
+        assert_eq!(level, original);
+
+        level
+            .edit(|transaction| {
+                let index = transaction.add_object(Object::goomba(5, 1));
+                transaction.move_object(index, 6, 1).unwrap();
+                assert!(transaction.move_object(index, u32::MAX, 1).is_err());
+                transaction.set_header(HeaderField::TimeLimit(200));
+            })
+            .unwrap();
+        assert_eq!(level.time_limit, 200);
+        assert_eq!(level.objects.last(), Some(&Object::goomba(6, 1)));
+
+        // Without rules anything goes
+        level
+            .edit_with(&Validator::new(), |transaction| {
+                transaction.add_object(Object::goomba(500, 1));
+            })
+            .unwrap();
+    }
+}
@@ -0,0 +1,378 @@
+// C interface over Level, headers can be generated with `cbindgen --config cbindgen.toml`.
+// Every function returning Mm1Status leaves its out parameters untouched unless it returns Ok.
+
+use std::{
+    ffi::{c_char, CStr},
+    ptr, slice,
+};
+
+use crate::{
+    level::Level,
+    validation::{validate_level_name, LevelNameIssue},
+    Error,
+};
+
+// Values are part of the ABI and must never be renumbered
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mm1Status {
+    Ok = 0,
+    NullPointer = 1,
+    InvalidData = 2,
+    FileTooLarge = 3,
+    MissingCourseData = 4,
+    Io = 5,
+    BufferTooSmall = 6,
+    OutOfRange = 7,
+    Http = 8,
+    Database = 9,
+    Cancelled = 10,
+    ChecksumMismatch = 11,
+    WrongSize = 12,
+    InvalidLevelName = 13,
+}
+
+impl From<Error> for Mm1Status {
+    fn from(error: Error) -> Self {
+        match error {
+            Error::FileTooLarge => Mm1Status::FileTooLarge,
+            Error::InvalidData => Mm1Status::InvalidData,
+            Error::MissingCourseData(_) | Error::IncompleteTar(_) => {
+                Mm1Status::MissingCourseData
+            }
+            Error::Io(_) => Mm1Status::Io,
+            Error::Http(_) => Mm1Status::Http,
+            Error::Database(_) => Mm1Status::Database,
+            Error::Cancelled => Mm1Status::Cancelled,
+            Error::InvalidLevelName(_) => Mm1Status::InvalidLevelName,
+            Error::ChecksumMismatch { .. } => Mm1Status::ChecksumMismatch,
+            Error::WrongSize { .. } => Mm1Status::WrongSize,
+        }
+    }
+}
+
+// Opaque handle owned by the caller, released with mm1_level_free
+pub struct Mm1Level(Level);
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Mm1Object {
+    pub x_position: u32,
+    pub z_position: u32,
+    pub y_position: i16,
+    pub width: i8,
+    pub height: i8,
+    pub object_flags: u32,
+    pub child_object_flags: u32,
+    pub extended_object_data: u32,
+    pub object_type: i8,
+    pub child_object_type: i8,
+    pub link_id: i16,
+    pub effect_index: i16,
+    pub transformation_id: i8,
+    pub child_object_transformation_id: i8,
+}
+
+/// # Safety
+/// `data` must point to `length` readable bytes and `out` to a writable handle pointer.
+#[no_mangle]
+pub unsafe extern "C" fn mm1_level_parse(
+    data: *const u8,
+    length: usize,
+    out: *mut *mut Mm1Level,
+) -> Mm1Status {
+    if data.is_null() || out.is_null() {
+        return Mm1Status::NullPointer;
+    }
+
+    match Level::from_bytes(slice::from_raw_parts(data, length)) {
+        Ok(level) => {
+            *out = Box::into_raw(Box::new(Mm1Level(level)));
+            Mm1Status::Ok
+        }
+        Err(error) => error.into(),
+    }
+}
+
+/// # Safety
+/// `level` must be null or a handle returned by mm1_level_parse that hasn't been freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn mm1_level_free(level: *mut Mm1Level) {
+    if !level.is_null() {
+        drop(Box::from_raw(level));
+    }
+}
+
+/// # Safety
+/// `level` must be a live handle, `out` and `out_length` must be writable. The buffer written
+/// to `out` must be released with mm1_bytes_free.
+#[no_mangle]
+pub unsafe extern "C" fn mm1_level_serialize(
+    level: *const Mm1Level,
+    out: *mut *mut u8,
+    out_length: *mut usize,
+) -> Mm1Status {
+    let Some(level) = level.as_ref() else {
+        return Mm1Status::NullPointer;
+    };
+    if out.is_null() || out_length.is_null() {
+        return Mm1Status::NullPointer;
+    }
+
+    match level.0.to_bytes() {
+        Ok(bytes) => {
+            let bytes = bytes.into_boxed_slice();
+            *out_length = bytes.len();
+            *out = Box::into_raw(bytes) as *mut u8;
+            Mm1Status::Ok
+        }
+        Err(error) => error.into(),
+    }
+}
+
+/// # Safety
+/// `bytes` must be null or a buffer returned by mm1_level_serialize along with its length.
+#[no_mangle]
+pub unsafe extern "C" fn mm1_bytes_free(bytes: *mut u8, length: usize) {
+    if !bytes.is_null() {
+        drop(Box::from_raw(ptr::slice_from_raw_parts_mut(bytes, length)));
+    }
+}
+
+/// # Safety
+/// `level` must be a live handle and `buffer` must point to `length` writable bytes.
+/// The name is written as NUL terminated UTF-8.
+#[no_mangle]
+pub unsafe extern "C" fn mm1_level_name(
+    level: *const Mm1Level,
+    buffer: *mut c_char,
+    length: usize,
+) -> Mm1Status {
+    let Some(level) = level.as_ref() else {
+        return Mm1Status::NullPointer;
+    };
+    if buffer.is_null() {
+        return Mm1Status::NullPointer;
+    }
+
+    let name = level.0.level_name.as_bytes();
+    if name.len() >= length {
+        return Mm1Status::BufferTooSmall;
+    }
+
+    ptr::copy_nonoverlapping(name.as_ptr(), buffer as *mut u8, name.len());
+    *buffer.add(name.len()) = 0;
+    Mm1Status::Ok
+}
+
+/// # Safety
+/// `level` must be a live handle and `name` a NUL terminated UTF-8 string. Names that can't be
+/// written fail with InvalidLevelName and leave the level unchanged.
+#[no_mangle]
+pub unsafe extern "C" fn mm1_level_set_name(
+    level: *mut Mm1Level,
+    name: *const c_char,
+) -> Mm1Status {
+    let Some(level) = level.as_mut() else {
+        return Mm1Status::NullPointer;
+    };
+    if name.is_null() {
+        return Mm1Status::NullPointer;
+    }
+
+    let Ok(name) = CStr::from_ptr(name).to_str() else {
+        return Mm1Status::InvalidData;
+    };
+    if let Some(issue) = validate_level_name(name)
+        .into_iter()
+        .find(LevelNameIssue::is_fatal)
+    {
+        return Error::InvalidLevelName(issue).into();
+    }
+
+    level.0.level_name = name.to_string();
+    Mm1Status::Ok
+}
+
+/// # Safety
+/// `level` must be a live handle and `out` must be writable.
+#[no_mangle]
+pub unsafe extern "C" fn mm1_level_object(
+    level: *const Mm1Level,
+    index: usize,
+    out: *mut Mm1Object,
+) -> Mm1Status {
+    let Some(level) = level.as_ref() else {
+        return Mm1Status::NullPointer;
+    };
+    if out.is_null() {
+        return Mm1Status::NullPointer;
+    }
+
+    let Some(object) = level.0.objects.get(index) else {
+        return Mm1Status::OutOfRange;
+    };
+
+    *out = Mm1Object {
+        x_position: object.x_position,
+        z_position: object.z_position,
+        y_position: object.y_position,
+        width: object.width,
+        height: object.height,
+        object_flags: object.object_flags,
+        child_object_flags: object.child_object_flags,
+        extended_object_data: object.extended_object_data,
+        object_type: object.object_type,
+        child_object_type: object.child_object_type,
+        link_id: object.link_id,
+        effect_index: object.effect_index,
+        transformation_id: object.transformation_id,
+        child_object_transformation_id: object.child_object_transformation_id,
+    };
+    Mm1Status::Ok
+}
+
+macro_rules! getter {
+    ($name:ident, $type:ty, |$level:ident| $value:expr) => {
+        /// # Safety
+        /// `level` must be a live handle, a null handle reads as zero.
+        #[no_mangle]
+        pub unsafe extern "C" fn $name(level: *const Mm1Level) -> $type {
+            match level.as_ref() {
+                Some(Mm1Level($level)) => $value,
+                None => 0,
+            }
+        }
+    };
+}
+
+getter!(mm1_level_version, u64, |level| level.version);
+getter!(mm1_level_game_mode, u8, |level| level.game_mode.into());
+getter!(mm1_level_course_theme, u8, |level| level.course_theme.into());
+getter!(mm1_level_time_limit, u16, |level| level.time_limit);
+getter!(mm1_level_auto_scroll, u8, |level| level.auto_scroll.into());
+getter!(mm1_level_flags, u8, |level| level.flags);
+getter!(mm1_level_width, u32, |level| level.width);
+getter!(mm1_level_object_count, usize, |level| level.objects.len());
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        fixtures::minimal_level,
+        level::{CourseTheme, GameMode},
+        objects::Object,
+    };
+
+    use super::*;
+
+    fn parse(bytes: &[u8]) -> Result<*mut Mm1Level, Mm1Status> {
+        let mut level = ptr::null_mut();
+        match unsafe { mm1_level_parse(bytes.as_ptr(), bytes.len(), &mut level) } {
+            Mm1Status::Ok => Ok(level),
+            status => Err(status),
+        }
+    }
+
+    #[test]
+    fn test_parse_and_serialize() {
+        let mut level = minimal_level(GameMode::Mario3, CourseTheme::Airship);
+        level.objects.push(Object::goomba(4, 1));
+        let bytes = level.to_bytes().unwrap();
+
+        let handle = parse(&bytes).unwrap();
+        unsafe {
+            assert_eq!(mm1_level_width(handle), level.width);
+            assert_eq!(mm1_level_game_mode(handle), u8::from(level.game_mode));
+            assert_eq!(mm1_level_object_count(handle), level.objects.len());
+
+            let mut object = std::mem::zeroed();
+            let last = level.objects.len() - 1;
+            assert_eq!(mm1_level_object(handle, last, &mut object), Mm1Status::Ok);
+            assert_eq!(object.object_type, level.objects[last].object_type);
+            assert_eq!(
+                mm1_level_object(handle, level.objects.len(), &mut object),
+                Mm1Status::OutOfRange
+            );
+
+            let mut out = ptr::null_mut();
+            let mut out_length = 0;
+            assert_eq!(
+                mm1_level_serialize(handle, &mut out, &mut out_length),
+                Mm1Status::Ok
+            );
+            assert_eq!(slice::from_raw_parts(out, out_length), bytes.as_slice());
+            mm1_bytes_free(out, out_length);
+            mm1_level_free(handle);
+        }
+    }
+
+    #[test]
+    fn test_name() {
+        let bytes = minimal_level(GameMode::MarioWorld, CourseTheme::Castle)
+            .to_bytes()
+            .unwrap();
+        let handle = parse(&bytes).unwrap();
+        unsafe {
+            assert_eq!(
+                mm1_level_set_name(handle, c"Castle Run".as_ptr()),
+                Mm1Status::Ok
+            );
+            let mut buffer = [0 as c_char; 32];
+            assert_eq!(
+                mm1_level_name(handle, buffer.as_mut_ptr(), buffer.len()),
+                Mm1Status::Ok
+            );
+            assert_eq!(CStr::from_ptr(buffer.as_ptr()), c"Castle Run");
+            assert_eq!(
+                mm1_level_name(handle, buffer.as_mut_ptr(), 10),
+                Mm1Status::BufferTooSmall
+            );
+
+            let long = std::ffi::CString::new("a".repeat(33)).unwrap();
+            assert_eq!(
+                mm1_level_set_name(handle, long.as_ptr()),
+                Mm1Status::InvalidLevelName
+            );
+            assert_eq!(
+                mm1_level_set_name(handle, c"\xFF".as_ptr()),
+                Mm1Status::InvalidData
+            );
+            assert_eq!(
+                mm1_level_name(handle, buffer.as_mut_ptr(), 32),
+                Mm1Status::Ok
+            );
+            assert_eq!(CStr::from_ptr(buffer.as_ptr()), c"Castle Run");
+            mm1_level_free(handle);
+        }
+    }
+
+    #[test]
+    fn test_bad_input() {
+        assert_eq!(parse(&[0; 16]), Err(Mm1Status::WrongSize));
+        assert_eq!(parse(&[]), Err(Mm1Status::WrongSize));
+
+        let mut out = ptr::null_mut();
+        let mut out_length = 0;
+        unsafe {
+            assert_eq!(
+                mm1_level_parse(ptr::null(), 0, &mut out),
+                Mm1Status::NullPointer
+            );
+            assert_eq!(
+                mm1_level_serialize(ptr::null(), &mut ptr::null_mut(), &mut out_length),
+                Mm1Status::NullPointer
+            );
+            assert_eq!(
+                mm1_level_name(ptr::null(), ptr::null_mut(), 0),
+                Mm1Status::NullPointer
+            );
+            assert_eq!(
+                mm1_level_set_name(ptr::null_mut(), c"".as_ptr()),
+                Mm1Status::NullPointer
+            );
+            assert_eq!(mm1_level_width(ptr::null()), 0);
+            mm1_level_free(ptr::null_mut());
+            mm1_bytes_free(ptr::null_mut(), 0);
+        }
+    }
+}
@@ -0,0 +1,149 @@
+// Small but console-valid courses built in memory, so tests don't need to ship sample files
+// taken from the game.
+
+use image::{codecs::jpeg::JpegEncoder, Rgb, RgbImage};
+
+use crate::{
+    course::{Course, CourseData},
+    date::CreationDate,
+    level::{AutoScroll, CourseTheme, GameMode, Level},
+    objects::{Object, ObjectType},
+    sound_effects::SoundEffect,
+    thumbnail::Thumbnail,
+    Error,
+};
+
+pub use crate::thumbnail::{PREVIEW_SIZE, THUMBNAIL_SIZE};
+
+// Narrowest course the editor allows, a single screen
+pub const MINIMAL_BLOCK_WIDTH: u32 = 24;
+
+const GOAL_AREA: u32 = 10;
+
+pub fn object(object_type: ObjectType, x: u32, y: i16, width: i8, height: i8) -> Object {
+    Object::new(
+        x * 10,
+        0,
+        y * 10,
+        width,
+        height,
+        0,
+        0,
+        0,
+        object_type.into(),
+        -1,
+        -1,
+        -1,
+        -1,
+        -1,
+    )
+}
+
+// Header shared by every fixture, with no objects
+pub fn empty_level(game_mode: GameMode, course_theme: CourseTheme, block_width: u32) -> Level {
+    Level::new(
+        0xB,
+        CreationDate::new(2016, 1, 1, 0, 0)
+            .and_then(CreationDate::to_creation_time)
+            .unwrap(),
+        "Fixture".to_string(),
+        game_mode,
+        course_theme,
+        300,
+        AutoScroll::None,
+        0,
+        block_width * 16,
+        [0; 0x60],
+        Vec::new(),
+        vec![SoundEffect::default(); 300],
+    )
+}
+
+// A flat row of ground from the start to the goal
+pub fn minimal_level(game_mode: GameMode, course_theme: CourseTheme) -> Level {
+    let mut level = empty_level(game_mode, course_theme, MINIMAL_BLOCK_WIDTH);
+    let goal_x = MINIMAL_BLOCK_WIDTH - GOAL_AREA;
+
+    level.objects = (0..goal_x)
+        .map(|x| object(ObjectType::Ground, x, 0, 1, 1))
+        .collect();
+    level.objects.push(object(
+        ObjectType::GoalGround,
+        goal_x,
+        0,
+        GOAL_AREA as i8,
+        1,
+    ));
+    level
+        .objects
+        .push(object(ObjectType::Goal, goal_x + 4, 1, 1, 11));
+
+    level
+}
+
+// Sub areas have no start or goal, just ground to stand on
+pub fn minimal_sub_level(game_mode: GameMode, course_theme: CourseTheme) -> Level {
+    let mut level = empty_level(game_mode, course_theme, MINIMAL_BLOCK_WIDTH);
+    level.objects = (0..MINIMAL_BLOCK_WIDTH)
+        .map(|x| object(ObjectType::Ground, x, 0, 1, 1))
+        .collect();
+    level
+}
+
+// A solid color JPEG of the given size
+pub fn thumbnail(size: (u32, u32), color: [u8; 3]) -> Thumbnail {
+    let image = RgbImage::from_pixel(size.0, size.1, Rgb(color));
+    let mut jpeg_data = Vec::new();
+    // Encoding into memory can't fail
+    JpegEncoder::new(&mut jpeg_data)
+        .encode_image(&image)
+        .unwrap();
+    Thumbnail { jpeg_data }
+}
+
+pub fn minimal_course(game_mode: GameMode, course_theme: CourseTheme) -> Course {
+    Course::new(
+        minimal_level(game_mode, course_theme),
+        minimal_sub_level(game_mode, course_theme),
+        thumbnail(PREVIEW_SIZE, [92, 148, 252]),
+        thumbnail(THUMBNAIL_SIZE, [92, 148, 252]),
+    )
+}
+
+// The four files of a course as they're stored on the console
+pub fn course_files(course: &Course) -> Result<Vec<(CourseData, Vec<u8>)>, Error> {
+    CourseData::ALL
+        .iter()
+        .map(|data| Ok((*data, course.to_data_bytes(*data)?)))
+        .collect()
+}
+
+pub fn minimal_course_tar(
+    game_mode: GameMode,
+    course_theme: CourseTheme,
+) -> Result<Vec<u8>, Error> {
+    let mut builder = tar::Builder::new(Vec::new());
+    minimal_course(game_mode, course_theme).to_tar(&mut builder)?;
+    builder.into_inner().map_err(Error::Io)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_minimal_course_round_trip() {
+        let course = minimal_course(GameMode::MarioWorld, CourseTheme::Castle);
+        let files = course_files(&course).unwrap();
+        assert_eq!(
+            Course::from_bytes(&files[0].1, &files[1].1, &files[2].1, &files[3].1).unwrap(),
+            course
+        );
+
+        let tar = minimal_course_tar(GameMode::MarioWorld, CourseTheme::Castle).unwrap();
+        assert_eq!(
+            Course::from_tar(&mut tar::Archive::new(tar.as_slice())).unwrap(),
+            course
+        );
+    }
+}
@@ -0,0 +1,170 @@
+// Course data formats behind one interface, so downstream code can load levels without knowing
+// which console they came from. Only the Wii U format is implemented. The 3DS format is not: its
+// layout hasn't been checked against real 3DS data, so a CourseFormat for it has to come from
+// downstream code through FormatRegistry::register.
+
+use crate::{level::Level, migrate::CURRENT_VERSION, Error};
+
+pub trait CourseFormat: Send + Sync {
+    // Unique among registered formats
+    fn name(&self) -> &'static str;
+
+    // A cheap check whether the data looks like this format, parse may still fail
+    fn detect(&self, bytes: &[u8]) -> bool;
+
+    fn parse(&self, bytes: &[u8]) -> Result<Level, Error>;
+
+    fn serialize(&self, level: &Level) -> Result<Vec<u8>, Error>;
+}
+
+// Big endian course data as written by the Wii U, what Level::from_bytes and Level::to_bytes use
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct WiiU;
+
+impl CourseFormat for WiiU {
+    fn name(&self) -> &'static str {
+        "wiiu"
+    }
+
+    // Byte swapped data fails on the version, which reads as far beyond any known one
+    fn detect(&self, bytes: &[u8]) -> bool {
+        bytes.len() == Level::SIZE
+            && u64::from_be_bytes(bytes[..8].try_into().unwrap()) <= CURRENT_VERSION
+    }
+
+    fn parse(&self, bytes: &[u8]) -> Result<Level, Error> {
+        Level::from_bytes(bytes)
+    }
+
+    fn serialize(&self, level: &Level) -> Result<Vec<u8>, Error> {
+        level.to_bytes()
+    }
+}
+
+pub struct FormatRegistry {
+    formats: Vec<Box<dyn CourseFormat>>,
+}
+
+// Holds the Wii U format
+impl Default for FormatRegistry {
+    fn default() -> Self {
+        FormatRegistry {
+            formats: vec![Box::new(WiiU)],
+        }
+    }
+}
+
+impl FormatRegistry {
+    // A registry without any formats
+    pub fn new() -> FormatRegistry {
+        FormatRegistry {
+            formats: Vec::new(),
+        }
+    }
+
+    // Replaces a registered format of the same name. Formats are detected in the order they were
+    // first registered
+    pub fn register(&mut self, format: Box<dyn CourseFormat>) {
+        match self
+            .formats
+            .iter()
+            .position(|registered| registered.name() == format.name())
+        {
+            Some(index) => self.formats[index] = format,
+            None => self.formats.push(format),
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&dyn CourseFormat> {
+        self.formats
+            .iter()
+            .find(|format| format.name() == name)
+            .map(|format| format.as_ref())
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.formats.iter().map(|format| format.name())
+    }
+
+    pub fn detect(&self, bytes: &[u8]) -> Option<&dyn CourseFormat> {
+        self.formats
+            .iter()
+            .find(|format| format.detect(bytes))
+            .map(|format| format.as_ref())
+    }
+
+    // Parses with the first format detecting the data, failing with Error::InvalidData if none does
+    pub fn parse(&self, bytes: &[u8]) -> Result<(&'static str, Level), Error> {
+        let format = self.detect(bytes).ok_or(Error::InvalidData)?;
+        Ok((format.name(), format.parse(bytes)?))
+    }
+
+    // Fails with Error::InvalidData for a format that isn't registered
+    pub fn serialize(&self, name: &str, level: &Level) -> Result<Vec<u8>, Error> {
+        self.get(name).ok_or(Error::InvalidData)?.serialize(level)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        fixtures::minimal_level,
+        level::{CourseTheme, GameMode},
+    };
+
+    use super::*;
+
+    // The Wii U layout with every byte of each 8 byte word reversed. Only stands in for a second
+    // format, it isn't the 3DS layout
+    struct Swapped;
+
+    impl CourseFormat for Swapped {
+        fn name(&self) -> &'static str {
+            "swapped"
+        }
+
+        fn detect(&self, bytes: &[u8]) -> bool {
+            WiiU.detect(&swap(bytes))
+        }
+
+        fn parse(&self, bytes: &[u8]) -> Result<Level, Error> {
+            WiiU.parse(&swap(bytes))
+        }
+
+        fn serialize(&self, level: &Level) -> Result<Vec<u8>, Error> {
+            Ok(swap(&WiiU.serialize(level)?))
+        }
+    }
+
+    fn swap(bytes: &[u8]) -> Vec<u8> {
+        bytes
+            .chunks(8)
+            .flat_map(|word| word.iter().rev().copied())
+            .collect()
+    }
+
+    #[test]
+    fn test_format_registry() {
+        let level = minimal_level(GameMode::MarioWorld, CourseTheme::Underground);
+        let mut registry = FormatRegistry::default();
+        registry.register(Box::new(Swapped));
+        assert_eq!(
+            registry.names().collect::<Vec<_>>(),
+            vec!["wiiu", "swapped"]
+        );
+
+        let bytes = level.to_bytes().unwrap();
+        assert_eq!(registry.parse(&bytes).unwrap(), ("wiiu", level.clone()));
+
+        let swapped = registry.serialize("swapped", &level).unwrap();
+        assert_eq!(swapped, swap(&bytes));
+        assert_eq!(
+            registry.parse(&swapped).unwrap(),
+            ("swapped", level.clone())
+        );
+
+        assert!(registry.parse(&bytes[..0x100]).is_err());
+        assert!(registry.serialize("3ds", &level).is_err());
+        assert!(FormatRegistry::new().parse(&bytes).is_err());
+    }
+}
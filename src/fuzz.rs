@@ -0,0 +1,158 @@
+// Arbitrary implementations for structured fuzzing. Generated values always pack and unpack
+// back to themselves, so fuzzers spend their time on course logic rather than parse errors.
+
+use arbitrary::{Arbitrary, Result, Unstructured};
+
+use crate::{
+    date::CreationDate,
+    level::{AutoScroll, CourseTheme, GameMode, Level},
+    objects::Object,
+    sound_effects::SoundEffect,
+    thumbnail::{Thumbnail, MAX_JPEG_SIZE},
+};
+
+const MAX_OBJECTS: usize = 2600;
+const SOUND_EFFECTS: usize = 300;
+const MAX_NAME_LENGTH: usize = 32;
+const MAX_BLOCK_WIDTH: u32 = 240;
+
+impl<'a> Arbitrary<'a> for GameMode {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(*u.choose(&[
+            GameMode::SuperMarioBros,
+            GameMode::Mario3,
+            GameMode::MarioWorld,
+            GameMode::NewSuperMarioBrosU,
+        ])?)
+    }
+}
+
+impl<'a> Arbitrary<'a> for CourseTheme {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(*u.choose(&[
+            CourseTheme::Overworld,
+            CourseTheme::Underground,
+            CourseTheme::Castle,
+            CourseTheme::Airship,
+            CourseTheme::Water,
+            CourseTheme::GhostHouse,
+        ])?)
+    }
+}
+
+impl<'a> Arbitrary<'a> for AutoScroll {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(*u.choose(&[
+            AutoScroll::None,
+            AutoScroll::Slow,
+            AutoScroll::Medium,
+            AutoScroll::Fast,
+        ])?)
+    }
+}
+
+impl<'a> Arbitrary<'a> for Object {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(Object::new(
+            u.arbitrary()?,
+            u.arbitrary()?,
+            u.arbitrary()?,
+            u.arbitrary()?,
+            u.arbitrary()?,
+            u.arbitrary()?,
+            u.arbitrary()?,
+            u.arbitrary()?,
+            u.arbitrary()?,
+            u.arbitrary()?,
+            u.arbitrary()?,
+            u.arbitrary()?,
+            u.arbitrary()?,
+            u.arbitrary()?,
+        ))
+    }
+}
+
+impl<'a> Arbitrary<'a> for SoundEffect {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(SoundEffect::from_raw(u.arbitrary()?))
+    }
+}
+
+impl<'a> Arbitrary<'a> for Thumbnail {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let length = u.int_in_range(0..=MAX_JPEG_SIZE.min(u.len()))?;
+        Ok(Thumbnail {
+            jpeg_data: u.bytes(length)?.to_vec(),
+        })
+    }
+}
+
+impl<'a> Arbitrary<'a> for Level {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        // Seconds aren't stored and every month has at least 28 days
+        let creation_time = CreationDate::new(
+            u.int_in_range(0..=9999)?,
+            u.int_in_range(1..=12)?,
+            u.int_in_range(1..=28)?,
+            u.int_in_range(0..=23)?,
+            u.int_in_range(0..=59)?,
+        )
+        .and_then(CreationDate::to_creation_time)
+        .ok_or(arbitrary::Error::IncorrectFormat)?;
+
+        // Names are UCS-2, so characters outside the basic multilingual plane and the zero
+        // terminator can't be stored
+        let name_length = u.int_in_range(0..=MAX_NAME_LENGTH)?;
+        let mut level_name = String::new();
+        for _ in 0..name_length {
+            let character: char = u.arbitrary()?;
+            if character != '\0' && character.len_utf16() == 1 {
+                level_name.push(character);
+            }
+        }
+
+        let object_count = u.int_in_range(0..=MAX_OBJECTS)?;
+        let objects = (0..object_count)
+            .map(|_| u.arbitrary())
+            .collect::<Result<_>>()?;
+        let sound_effects = (0..SOUND_EFFECTS)
+            .map(|_| u.arbitrary())
+            .collect::<Result<_>>()?;
+
+        Ok(Level::new(
+            0xB,
+            creation_time,
+            level_name,
+            u.arbitrary()?,
+            u.arbitrary()?,
+            u.arbitrary()?,
+            u.arbitrary()?,
+            u.arbitrary()?,
+            u.int_in_range(0..=MAX_BLOCK_WIDTH)? * 16,
+            u.arbitrary()?,
+            objects,
+            sound_effects,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_arbitrary_levels_round_trip() {
+        let data: Vec<u8> = (0..0x4000u32)
+            .map(|i| (i.wrapping_mul(2654435761) >> 13) as u8)
+            .collect();
+        let mut u = Unstructured::new(&data);
+
+        while !u.is_empty() {
+            let level = Level::arbitrary(&mut u).unwrap();
+            assert_eq!(
+                Level::from_bytes(&level.to_bytes().unwrap()).unwrap(),
+                level
+            );
+        }
+    }
+}
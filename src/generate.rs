@@ -0,0 +1,189 @@
+// Seeded random levels for testing tools and random course bots. The same seed and options always
+// give the same level, and every level has a start area, a goal and ground that can be crossed by
+// running and jumping.
+
+use crate::{
+    date::CreationDate,
+    level::{AutoScroll, CourseTheme, GameMode, Level},
+    objects::{Object, ObjectType},
+    sound_effects::SoundEffect,
+};
+
+const START_AREA: u32 = 7;
+const GOAL_AREA: u32 = 10;
+// Ground never rises above this, leaving room for blocks and coins
+const MAX_GROUND_HEIGHT: i16 = 4;
+// Ground enemies that walk off ledges or stay put, nothing that flies or needs water
+const ENEMIES: [ObjectType; 5] = [
+    ObjectType::Goomba,
+    ObjectType::Koopa,
+    ObjectType::BuzzyBeetle,
+    ObjectType::Spiny,
+    ObjectType::DryBones,
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GenerateOptions {
+    pub game_mode: GameMode,
+    pub course_theme: CourseTheme,
+    // Clamped to the editor's limits
+    pub block_width: u32,
+    // Chance of a gap after each stretch of ground
+    pub gap_chance: f32,
+    // Widest gap in blocks, limited to what a running jump clears
+    pub max_gap: u32,
+    // Most a stretch of ground rises or falls from the previous one, in blocks
+    pub max_step: u32,
+    // Average enemies per screen
+    pub enemy_density: f32,
+    // Chance of a ? block holding a power-up above each stretch of ground
+    pub powerup_chance: f32,
+}
+
+impl Default for GenerateOptions {
+    fn default() -> Self {
+        GenerateOptions {
+            game_mode: GameMode::SuperMarioBros,
+            course_theme: CourseTheme::Overworld,
+            block_width: 120,
+            gap_chance: 0.3,
+            max_gap: 3,
+            max_step: 2,
+            enemy_density: 2.0,
+            powerup_chance: 0.2,
+        }
+    }
+}
+
+// splitmix64, small and good enough for level layouts
+struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E3779B97F4A7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^ (z >> 31)
+    }
+
+    // Uniform in [low, high]
+    fn range(&mut self, low: i64, high: i64) -> i64 {
+        low + (self.next() % (high - low + 1) as u64) as i64
+    }
+
+    fn chance(&mut self, probability: f32) -> bool {
+        ((self.next() >> 40) as f32 / (1u64 << 24) as f32) < probability
+    }
+}
+
+pub fn generate(seed: u64, options: &GenerateOptions) -> Level {
+    let mut rng = Rng { state: seed };
+    let block_width = options
+        .block_width
+        .clamp(Level::MIN_BLOCK_WIDTH, Level::MAX_BLOCK_WIDTH);
+    let goal_x = block_width - GOAL_AREA;
+    // A gap this wide can still be jumped after climbing the highest step
+    let max_gap = options
+        .max_gap
+        .min(options.game_mode.jump_length().saturating_sub(2));
+    let max_step = options
+        .max_step
+        .min(options.game_mode.jump_height().saturating_sub(1)) as i64;
+    let enemy_chance = options.enemy_density / 24.0;
+
+    let mut objects: Vec<Object> = (0..START_AREA).map(|x| Object::ground(x, 0)).collect();
+    let mut x = START_AREA;
+    let mut height = 0i16;
+    let mut after_gap = false;
+    while x < goal_x {
+        // Gaps never follow each other, they'd add up to more than a jump can clear
+        if !after_gap && max_gap > 0 && rng.chance(options.gap_chance) {
+            x += rng.range(1, max_gap as i64) as u32;
+            after_gap = true;
+            continue;
+        }
+        after_gap = false;
+
+        height = (height as i64 + rng.range(-max_step, max_step)).clamp(0, MAX_GROUND_HEIGHT as i64)
+            as i16;
+        let length = (rng.range(3, 8) as u32).min(goal_x - x);
+        for column in x..x + length {
+            objects.extend((0..=height).map(|y| Object::ground(column, y)));
+            if rng.chance(enemy_chance) {
+                let enemy = ENEMIES[rng.range(0, ENEMIES.len() as i64 - 1) as usize];
+                objects.push(Object::with_type(enemy).at(column, height + 1));
+            }
+        }
+
+        if rng.chance(options.powerup_chance) {
+            let contents = if rng.chance(0.5) {
+                ObjectType::SuperMushroom
+            } else {
+                ObjectType::FireFlower
+            };
+            objects.push(Object::question_block(
+                x + length / 2,
+                height + 4,
+                Some(contents),
+            ));
+        } else if length > 4 {
+            objects.extend((x + 1..x + length - 1).map(|column| Object::coin(column, height + 3)));
+        }
+        x += length;
+    }
+
+    let mut goal_ground = Object::with_type(ObjectType::GoalGround).at(goal_x, 0);
+    goal_ground.width = GOAL_AREA as i8;
+    objects.push(goal_ground);
+    let mut goal = Object::with_type(ObjectType::Goal).at(goal_x + 4, 1);
+    goal.height = 11;
+    objects.push(goal);
+
+    Level::new(
+        0xB,
+        CreationDate::new(2016, 1, 1, 0, 0)
+            .and_then(CreationDate::to_creation_time)
+            .unwrap(),
+        format!("Random {:016X}", seed),
+        options.game_mode,
+        options.course_theme,
+        300,
+        AutoScroll::None,
+        0,
+        block_width * 16,
+        [0; 0x60],
+        objects,
+        vec![SoundEffect::default(); Level::SOUND_EFFECTS],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{analysis::reachability, validation::validate};
+
+    use super::*;
+
+    #[test]
+    fn test_generate() {
+        let options = GenerateOptions {
+            gap_chance: 0.5,
+            max_gap: 20,
+            max_step: 10,
+            ..Default::default()
+        };
+        for seed in 0..32 {
+            let level = generate(seed, &options);
+            assert_eq!(level, generate(seed, &options));
+            assert!(validate(&level).is_empty());
+            assert!(reachability(&level).goal_reachable);
+            assert_eq!(
+                Level::from_bytes(&level.to_bytes().unwrap()).unwrap(),
+                level
+            );
+        }
+        assert_ne!(generate(0, &options), generate(1, &options));
+    }
+}
@@ -0,0 +1,379 @@
+// SQLite index over a directory of course dumps. Courses are either course directories or tars,
+// and are only re-parsed when their modification time changes.
+
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+    time::UNIX_EPOCH,
+};
+
+use packed_struct::prelude::*;
+use rusqlite::{params, Connection, OptionalExtension, Row};
+
+use crate::{
+    course::Course,
+    date::{CreationDate, CreationTime},
+    progress::{NoProgress, ProgressSink},
+    level::{CourseTheme, GameMode},
+    Error,
+};
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS courses (
+        path TEXT PRIMARY KEY,
+        modified INTEGER NOT NULL,
+        name TEXT NOT NULL,
+        game_mode TEXT NOT NULL,
+        course_theme INTEGER NOT NULL,
+        sub_course_theme INTEGER NOT NULL,
+        content_hash INTEGER NOT NULL,
+        object_count INTEGER NOT NULL,
+        sub_object_count INTEGER NOT NULL,
+        creation_time TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS courses_content_hash ON courses (content_hash);
+";
+
+const COLUMNS: &str = "path, name, game_mode, course_theme, sub_course_theme, content_hash, \
+    object_count, sub_object_count, creation_time";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexEntry {
+    pub path: PathBuf,
+    pub name: String,
+    pub game_mode: GameMode,
+    pub course_theme: CourseTheme,
+    pub sub_course_theme: CourseTheme,
+    pub content_hash: u64,
+    pub object_count: u32,
+    pub sub_object_count: u32,
+    pub creation_time: CreationTime,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct RefreshSummary {
+    pub added: usize,
+    pub updated: usize,
+    pub removed: usize,
+    pub unchanged: usize,
+    // Courses that couldn't be parsed and were left out of the index. One that was indexed before
+    // is removed from it and also counted in removed
+    pub failed: Vec<PathBuf>,
+}
+
+pub struct LibraryIndex {
+    connection: Connection,
+}
+
+fn to_error(error: rusqlite::Error) -> Error {
+    Error::Database(error.to_string())
+}
+
+impl LibraryIndex {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<LibraryIndex, Error> {
+        LibraryIndex::with_connection(Connection::open(path).map_err(to_error)?)
+    }
+
+    pub fn open_in_memory() -> Result<LibraryIndex, Error> {
+        LibraryIndex::with_connection(Connection::open_in_memory().map_err(to_error)?)
+    }
+
+    fn with_connection(connection: Connection) -> Result<LibraryIndex, Error> {
+        connection.execute_batch(SCHEMA).map_err(to_error)?;
+        Ok(LibraryIndex { connection })
+    }
+
+    // Brings the index in line with the courses directly inside the directory
+    pub fn refresh<P: AsRef<Path>>(&mut self, directory: P) -> Result<RefreshSummary, Error> {
+        self.refresh_with_progress(directory, &mut NoProgress)
+    }
+
+    // Like refresh, cancelling leaves the index as it was before the refresh
+    pub fn refresh_with_progress<P: AsRef<Path>>(
+        &mut self,
+        directory: P,
+        progress: &mut impl ProgressSink,
+    ) -> Result<RefreshSummary, Error> {
+        let mut summary = RefreshSummary::default();
+        let mut seen = HashSet::new();
+
+        let mut paths = Vec::new();
+        for entry in std::fs::read_dir(directory).map_err(Error::Io)? {
+            let path = entry.map_err(Error::Io)?.path();
+            let is_course = if path.is_dir() {
+                path.join("course_data.cdt").exists()
+            } else {
+                path.extension().is_some_and(|extension| extension == "tar")
+            };
+            if is_course {
+                paths.push(path);
+            }
+        }
+        paths.sort();
+        let total = paths.len();
+
+        let transaction = self.connection.transaction().map_err(to_error)?;
+        for (done, path) in paths.into_iter().enumerate() {
+            if !progress.progress(done, total, Some(&path)) {
+                return Err(Error::Cancelled);
+            }
+
+            let key = path.to_string_lossy().to_string();
+            let modified = modified_time(&path)?;
+            seen.insert(key.clone());
+
+            let indexed: Option<i64> = transaction
+                .query_row(
+                    "SELECT modified FROM courses WHERE path = ?1",
+                    [&key],
+                    |row| row.get(0),
+                )
+                .optional()
+                .map_err(to_error)?;
+            if indexed == Some(modified) {
+                summary.unchanged += 1;
+                continue;
+            }
+
+            let course = match Course::from_path(&path) {
+                Ok(course) => course,
+                Err(_) => {
+                    // Don't keep listing what the file held before it stopped parsing
+                    if indexed.is_some() {
+                        transaction
+                            .execute("DELETE FROM courses WHERE path = ?1", [&key])
+                            .map_err(to_error)?;
+                        summary.removed += 1;
+                    }
+                    summary.failed.push(path);
+                    continue;
+                }
+            };
+
+            transaction
+                .execute(
+                    "INSERT OR REPLACE INTO courses (path, modified, name, game_mode, course_theme, \
+                     sub_course_theme, content_hash, object_count, sub_object_count, creation_time) \
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+                    params![
+                        key,
+                        modified,
+                        course.level.level_name,
+                        String::from_utf8_lossy(&course.level.game_mode.pack().unwrap_or_default()),
+                        u8::from(course.level.course_theme),
+                        u8::from(course.sub_level.course_theme),
+                        course.content_hash()? as i64,
+                        course.level.objects.len() as u32,
+                        course.sub_level.objects.len() as u32,
+                        CreationDate::from_creation_time(&course.level.creation_time).to_string(),
+                    ],
+                )
+                .map_err(to_error)?;
+
+            if indexed.is_some() {
+                summary.updated += 1;
+            } else {
+                summary.added += 1;
+            }
+        }
+
+        let indexed: Vec<String> = transaction
+            .prepare("SELECT path FROM courses")
+            .and_then(|mut statement| {
+                statement
+                    .query_map([], |row| row.get(0))?
+                    .collect::<Result<_, _>>()
+            })
+            .map_err(to_error)?;
+        for path in indexed.iter().filter(|path| !seen.contains(*path)) {
+            transaction
+                .execute("DELETE FROM courses WHERE path = ?1", [path])
+                .map_err(to_error)?;
+            summary.removed += 1;
+        }
+
+        transaction.commit().map_err(to_error)?;
+        progress.progress(total, total, None);
+        Ok(summary)
+    }
+
+    pub fn entries(&self) -> Result<Vec<IndexEntry>, Error> {
+        self.query(
+            &format!("SELECT {} FROM courses ORDER BY name", COLUMNS),
+            [],
+        )
+    }
+
+    // Courses whose name contains the given text, ignoring ASCII case
+    pub fn search_name(&self, text: &str) -> Result<Vec<IndexEntry>, Error> {
+        let pattern = format!(
+            "%{}%",
+            text.replace('\\', "\\\\")
+                .replace('%', "\\%")
+                .replace('_', "\\_")
+        );
+        self.query(
+            &format!(
+                "SELECT {} FROM courses WHERE name LIKE ?1 ESCAPE '\\' ORDER BY name",
+                COLUMNS
+            ),
+            [pattern],
+        )
+    }
+
+    pub fn find_by_hash(&self, content_hash: u64) -> Result<Vec<IndexEntry>, Error> {
+        self.query(
+            &format!(
+                "SELECT {} FROM courses WHERE content_hash = ?1 ORDER BY path",
+                COLUMNS
+            ),
+            [content_hash as i64],
+        )
+    }
+
+    fn query<P: rusqlite::Params>(&self, sql: &str, params: P) -> Result<Vec<IndexEntry>, Error> {
+        let mut statement = self.connection.prepare(sql).map_err(to_error)?;
+        let entries = statement
+            .query_map(params, entry_from_row)
+            .map_err(to_error)?
+            .collect::<Result<_, _>>()
+            .map_err(to_error)?;
+        Ok(entries)
+    }
+}
+
+fn entry_from_row(row: &Row) -> rusqlite::Result<IndexEntry> {
+    let invalid = |column: usize| {
+        rusqlite::Error::FromSqlConversionFailure(
+            column,
+            rusqlite::types::Type::Integer,
+            "invalid course value".into(),
+        )
+    };
+
+    let game_mode: String = row.get(2)?;
+    let course_theme: u8 = row.get(3)?;
+    let sub_course_theme: u8 = row.get(4)?;
+    let content_hash: i64 = row.get(5)?;
+    let creation_time: String = row.get(8)?;
+
+    Ok(IndexEntry {
+        path: PathBuf::from(row.get::<_, String>(0)?),
+        name: row.get(1)?,
+        game_mode: game_mode
+            .as_bytes()
+            .try_into()
+            .ok()
+            .and_then(|code| GameMode::unpack(code).ok())
+            .ok_or_else(|| invalid(2))?,
+        course_theme: CourseTheme::try_from(course_theme).map_err(|_| invalid(3))?,
+        sub_course_theme: CourseTheme::try_from(sub_course_theme).map_err(|_| invalid(4))?,
+        content_hash: content_hash as u64,
+        object_count: row.get(6)?,
+        sub_object_count: row.get(7)?,
+        creation_time: creation_time
+            .parse::<CreationDate>()
+            .ok()
+            .and_then(CreationDate::to_creation_time)
+            .ok_or_else(|| invalid(8))?,
+    })
+}
+
+// Directory courses count as modified whenever any of their files are
+fn modified_time(path: &Path) -> Result<i64, Error> {
+    let mut paths = vec![path.to_path_buf()];
+    if path.is_dir() {
+        for entry in std::fs::read_dir(path).map_err(Error::Io)? {
+            paths.push(entry.map_err(Error::Io)?.path());
+        }
+    }
+
+    let mut latest = 0;
+    for path in paths {
+        let modified = std::fs::metadata(&path)
+            .and_then(|metadata| metadata.modified())
+            .map_err(Error::Io)?;
+        let nanos = modified
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_nanos() as i64);
+        latest = latest.max(nanos);
+    }
+
+    Ok(latest)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analysis::tests::{level, object};
+    use crate::objects::ObjectType;
+    use crate::thumbnail::Thumbnail;
+
+    use super::*;
+
+    #[test]
+    fn test_incremental_refresh() {
+        let directory = std::env::temp_dir().join(format!("mm1-index-{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+
+        let mut main = level(40, vec![object(ObjectType::Goomba, 4, 2, 1, 1)]);
+        main.level_name = "Indexed course".to_string();
+        let course = Course::new(
+            main,
+            level(24, Vec::new()),
+            Thumbnail {
+                jpeg_data: Vec::new(),
+            },
+            Thumbnail {
+                jpeg_data: Vec::new(),
+            },
+        );
+        let mut builder = tar::Builder::new(Vec::new());
+        course.to_tar(&mut builder).unwrap();
+        let tar_path = directory.join("course.tar");
+        let tar = builder.into_inner().unwrap();
+        std::fs::write(&tar_path, &tar).unwrap();
+        std::fs::write(directory.join("broken.tar"), b"not a tar").unwrap();
+
+        let mut index = LibraryIndex::open_in_memory().unwrap();
+        let cancel = &mut |_, _, _: Option<&Path>| false;
+        assert!(matches!(
+            index.refresh_with_progress(&directory, cancel),
+            Err(Error::Cancelled)
+        ));
+        assert!(index.entries().unwrap().is_empty());
+
+        let summary = index.refresh(&directory).unwrap();
+        assert_eq!(summary.added, 1);
+        assert_eq!(summary.failed, vec![directory.join("broken.tar")]);
+
+        let entries = index.search_name("indexed").unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].path, tar_path);
+        assert_eq!(entries[0].object_count, 1);
+        assert_eq!(
+            index.find_by_hash(course.content_hash().unwrap()).unwrap(),
+            entries
+        );
+
+        assert_eq!(index.refresh(&directory).unwrap().unchanged, 1);
+        std::fs::write(&tar_path, b"no longer a tar").unwrap();
+        std::fs::File::options()
+            .write(true)
+            .open(&tar_path)
+            .unwrap()
+            .set_modified(UNIX_EPOCH)
+            .unwrap();
+        let summary = index.refresh(&directory).unwrap();
+        assert_eq!(summary.removed, 1);
+        assert!(summary.failed.contains(&tar_path));
+        assert!(index.entries().unwrap().is_empty());
+
+        std::fs::write(&tar_path, &tar).unwrap();
+        assert_eq!(index.refresh(&directory).unwrap().added, 1);
+        std::fs::remove_file(&tar_path).unwrap();
+        assert_eq!(index.refresh(&directory).unwrap().removed, 1);
+        assert!(index.entries().unwrap().is_empty());
+
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+}
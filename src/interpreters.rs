@@ -0,0 +1,179 @@
+// Caller provided knowledge about parts, keyed by the raw object type byte. Lets tools decode parts
+// the crate hasn't typed yet, or override how known ones are named and checked, without forking.
+
+use std::collections::HashMap;
+
+use crate::{level::Level, objects::Object};
+
+pub trait ObjectInterpreter: Send + Sync {
+    // The Object::object_type value handled
+    fn object_type(&self) -> i8;
+
+    // Shown instead of ObjectType::display_name
+    fn display_name(&self, object: &Object) -> Option<String> {
+        let _ = object;
+        None
+    }
+
+    // Named fields worked out from the flags and extended data
+    fn decode(&self, object: &Object) -> Vec<(String, String)> {
+        let _ = object;
+        Vec::new()
+    }
+
+    // Problems with one object of the type, as messages
+    fn validate(&self, level: &Level, object: &Object) -> Vec<String> {
+        let _ = (level, object);
+        Vec::new()
+    }
+}
+
+// An issue reported by an installed interpreter
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InterpreterIssue {
+    pub object: usize,
+    pub object_type: i8,
+    pub message: String,
+}
+
+#[derive(Default)]
+pub struct InterpreterRegistry {
+    interpreters: HashMap<i8, Box<dyn ObjectInterpreter>>,
+}
+
+impl InterpreterRegistry {
+    pub fn new() -> InterpreterRegistry {
+        InterpreterRegistry::default()
+    }
+
+    // Returns the interpreter previously installed for the type
+    pub fn install(
+        &mut self,
+        interpreter: Box<dyn ObjectInterpreter>,
+    ) -> Option<Box<dyn ObjectInterpreter>> {
+        self.interpreters
+            .insert(interpreter.object_type(), interpreter)
+    }
+
+    pub fn uninstall(&mut self, object_type: i8) -> Option<Box<dyn ObjectInterpreter>> {
+        self.interpreters.remove(&object_type)
+    }
+
+    pub fn get(&self, object_type: i8) -> Option<&dyn ObjectInterpreter> {
+        self.interpreters
+            .get(&object_type)
+            .map(|interpreter| interpreter.as_ref())
+    }
+
+    // The installed interpreter's name, falling back to the crate's. None for unknown types without
+    // an interpreter naming them
+    pub fn display_name(&self, object: &Object) -> Option<String> {
+        self.get(object.object_type)
+            .and_then(|interpreter| interpreter.display_name(object))
+            .or_else(|| {
+                object
+                    .get_object_type()
+                    .map(|object_type| object_type.display_name().to_string())
+            })
+    }
+
+    // Empty without an interpreter for the type
+    pub fn decode(&self, object: &Object) -> Vec<(String, String)> {
+        self.get(object.object_type)
+            .map(|interpreter| interpreter.decode(object))
+            .unwrap_or_default()
+    }
+
+    // Runs the installed rules over every object, alongside validation::validate
+    pub fn validate(&self, level: &Level) -> Vec<InterpreterIssue> {
+        level
+            .objects
+            .iter()
+            .enumerate()
+            .flat_map(|(index, object)| {
+                self.get(object.object_type)
+                    .map(|interpreter| interpreter.validate(level, object))
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(move |message| InterpreterIssue {
+                        object: index,
+                        object_type: object.object_type,
+                        message,
+                    })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        fixtures::minimal_level,
+        level::{CourseTheme, GameMode},
+    };
+
+    use super::*;
+
+    struct Mystery;
+
+    impl ObjectInterpreter for Mystery {
+        fn object_type(&self) -> i8 {
+            100
+        }
+
+        fn display_name(&self, _: &Object) -> Option<String> {
+            Some("Mystery Part".to_string())
+        }
+
+        fn decode(&self, object: &Object) -> Vec<(String, String)> {
+            vec![("speed".to_string(), object.extended_object_data.to_string())]
+        }
+
+        fn validate(&self, _: &Level, object: &Object) -> Vec<String> {
+            if object.extended_object_data > 3 {
+                vec!["speed above 3".to_string()]
+            } else {
+                Vec::new()
+            }
+        }
+    }
+
+    #[test]
+    fn test_interpreter_registry() {
+        let mut level = minimal_level(GameMode::SuperMarioBros, CourseTheme::Overworld);
+        let objects = level.objects.len();
+        let mut mystery = Object::coin(4, 2);
+        mystery.object_type = 100;
+        mystery.extended_object_data = 5;
+        level.objects.push(mystery.clone());
+
+        let mut registry = InterpreterRegistry::new();
+        assert_eq!(registry.display_name(&mystery), None);
+        assert_eq!(
+            registry.display_name(&Object::goomba(0, 0)),
+            Some("Goomba".to_string())
+        );
+        assert!(registry.validate(&level).is_empty());
+
+        assert!(registry.install(Box::new(Mystery)).is_none());
+        assert_eq!(
+            registry.display_name(&mystery),
+            Some("Mystery Part".to_string())
+        );
+        assert_eq!(
+            registry.decode(&mystery),
+            vec![("speed".to_string(), "5".to_string())]
+        );
+        assert_eq!(
+            registry.validate(&level),
+            vec![InterpreterIssue {
+                object: objects,
+                object_type: 100,
+                message: "speed above 3".to_string(),
+            }]
+        );
+
+        assert!(registry.uninstall(100).is_some());
+        assert!(registry.decode(&mystery).is_empty());
+    }
+}
@@ -0,0 +1,234 @@
+// Import and export of a JSON course layout naming objects by their internal editor name
+// (EditKuribo, EditDokan, ...) rather than by ID, as older community tools did. The layout is this
+// crate's own: it hasn't been checked against an export from OpenCourseWorld, Reggie or any other
+// tool, so archives from those need converting to it first.
+
+use num_enum::TryFromPrimitive;
+use packed_struct::prelude::*;
+use serde_json::{json, Map, Value};
+
+use crate::{
+    date::CreationDate,
+    level::{AutoScroll, CourseTheme, GameMode, Level},
+    objects::{Object, ObjectType},
+    sound_effects::SoundEffect,
+    Error,
+};
+
+// Names accepted besides the editor's own, see ObjectType::internal_name. Not taken from any
+// particular tool's exports
+pub const LEGACY_ALIASES: &[(&str, ObjectType)] = &[
+    ("EditBlock", ObjectType::Brick),
+    ("EditGoal", ObjectType::Goal),
+    ("EditKillerCannon", ObjectType::BillBlaster),
+];
+
+// Objects are exported by ObjectType::internal_name, any name ObjectType::from_name knows or an
+// alias is accepted back
+pub fn from_legacy_name(name: &str) -> Option<ObjectType> {
+    ObjectType::from_name(name).or_else(|| {
+        LEGACY_ALIASES
+            .iter()
+            .find(|(alias, _)| alias.eq_ignore_ascii_case(name))
+            .map(|(_, object_type)| *object_type)
+    })
+}
+
+pub fn level_to_json(level: &Level) -> String {
+    let objects: Vec<Value> = level.objects.iter().map(object_to_json).collect();
+    let sound_effects: Vec<Value> = level
+        .sound_effects
+        .iter()
+        .map(|effect| json!(effect.raw()))
+        .collect();
+
+    json!({
+        "version": level.version,
+        "created": CreationDate::from_creation_time(&level.creation_time).to_string(),
+        "name": level.level_name,
+        // Packing a game mode can't fail
+        "style": String::from_utf8_lossy(&level.game_mode.pack().unwrap_or_default()),
+        "theme": u8::from(level.course_theme),
+        "time": level.time_limit,
+        "autoscroll": u8::from(level.auto_scroll),
+        "flags": level.flags,
+        "width": level.width,
+        "mii": level.mii_data.iter().map(|byte| format!("{:02x}", byte)).collect::<String>(),
+        "objects": objects,
+        "sounds": sound_effects,
+    })
+    .to_string()
+}
+
+pub fn level_from_json(json: &str) -> Result<Level, Error> {
+    let value: Value = serde_json::from_str(json).map_err(|_| Error::InvalidData)?;
+    let level = value.as_object().ok_or(Error::InvalidData)?;
+
+    let style = string(level, "style")?;
+    let game_mode = GameMode::unpack(
+        style
+            .as_bytes()
+            .try_into()
+            .map_err(|_| Error::InvalidData)?,
+    )
+    .map_err(|_| Error::InvalidData)?;
+
+    let mii = string(level, "mii").unwrap_or_default();
+    let mut mii_data = [0; 0x60];
+    for (i, byte) in mii_data.iter_mut().enumerate() {
+        if let Some(hex) = mii.get(i * 2..i * 2 + 2) {
+            *byte = u8::from_str_radix(hex, 16).map_err(|_| Error::InvalidData)?;
+        }
+    }
+
+    let objects = array(level, "objects")?
+        .iter()
+        .map(object_from_json)
+        .collect::<Result<_, _>>()?;
+    let sound_effects = array(level, "sounds")
+        .unwrap_or(&Vec::new())
+        .iter()
+        .map(|effect| {
+            Ok(SoundEffect::from_raw(narrow(
+                effect.as_u64().ok_or(Error::InvalidData)? as i64,
+            )?))
+        })
+        .collect::<Result<_, Error>>()?;
+
+    Ok(Level::new(
+        number(level, "version").unwrap_or(0xB) as u64,
+        string(level, "created")?
+            .parse::<CreationDate>()?
+            .to_creation_time()
+            .ok_or(Error::InvalidData)?,
+        string(level, "name")?,
+        game_mode,
+        CourseTheme::try_from_primitive(narrow(number(level, "theme")?)?)
+            .map_err(|_| Error::InvalidData)?,
+        narrow(number(level, "time")?)?,
+        AutoScroll::try_from_primitive(narrow(number(level, "autoscroll").unwrap_or(0))?)
+            .map_err(|_| Error::InvalidData)?,
+        narrow(number(level, "flags").unwrap_or(0))?,
+        narrow(number(level, "width")?)?,
+        mii_data,
+        objects,
+        sound_effects,
+    ))
+}
+
+fn object_to_json(object: &Object) -> Value {
+    let mut value = json!({
+        "x": object.x_position,
+        "y": object.y_position,
+        "z": object.z_position,
+        "w": object.width,
+        "h": object.height,
+        "flags": object.object_flags,
+        "child_flags": object.child_object_flags,
+        "extended_data": object.extended_object_data,
+        "link_id": object.link_id,
+        "effect_index": object.effect_index,
+        "transformation_id": object.transformation_id,
+        "child_transformation_id": object.child_object_transformation_id,
+    });
+
+    // Types without a known name are written by ID so they survive a round trip
+    let name = |id: i8| match ObjectType::try_from_primitive(id) {
+        Ok(object_type) => json!(object_type.internal_name()),
+        Err(_) => json!(id),
+    };
+    value["name"] = name(object.object_type);
+    if object.child_object_type >= 0 {
+        value["child"] = name(object.child_object_type);
+    }
+
+    value
+}
+
+fn object_from_json(value: &Value) -> Result<Object, Error> {
+    let object = value.as_object().ok_or(Error::InvalidData)?;
+
+    let id = |value: Option<&Value>| -> Result<i8, Error> {
+        match value {
+            None | Some(Value::Null) => Ok(-1),
+            Some(Value::String(name)) => {
+                Ok(from_legacy_name(name).ok_or(Error::InvalidData)?.into())
+            }
+            Some(value) => narrow(value.as_i64().ok_or(Error::InvalidData)?),
+        }
+    };
+
+    Ok(Object::new(
+        narrow(number(object, "x")?)?,
+        narrow(number(object, "z").unwrap_or(0))?,
+        narrow(number(object, "y")?)?,
+        narrow(number(object, "w").unwrap_or(1))?,
+        narrow(number(object, "h").unwrap_or(1))?,
+        narrow(number(object, "flags").unwrap_or(0))?,
+        narrow(number(object, "child_flags").unwrap_or(0))?,
+        narrow(number(object, "extended_data").unwrap_or(0))?,
+        id(object.get("name"))?,
+        id(object.get("child"))?,
+        narrow(number(object, "link_id").unwrap_or(-1))?,
+        narrow(number(object, "effect_index").unwrap_or(-1))?,
+        narrow(number(object, "transformation_id").unwrap_or(-1))?,
+        narrow(number(object, "child_transformation_id").unwrap_or(-1))?,
+    ))
+}
+
+fn number(object: &Map<String, Value>, key: &str) -> Result<i64, Error> {
+    object
+        .get(key)
+        .and_then(Value::as_i64)
+        .ok_or(Error::InvalidData)
+}
+
+fn string(object: &Map<String, Value>, key: &str) -> Result<String, Error> {
+    object
+        .get(key)
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .ok_or(Error::InvalidData)
+}
+
+fn array<'a>(object: &'a Map<String, Value>, key: &str) -> Result<&'a Vec<Value>, Error> {
+    object
+        .get(key)
+        .and_then(Value::as_array)
+        .ok_or(Error::InvalidData)
+}
+
+fn narrow<T: TryFrom<i64>>(value: i64) -> Result<T, Error> {
+    T::try_from(value).map_err(|_| Error::InvalidData)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analysis::tests::{level, object};
+
+    use super::*;
+
+    #[test]
+    fn test_legacy_json_round_trip() {
+        let mut level = level(40, vec![object(ObjectType::QuestionBlock, 4, 4, 1, 1)]);
+        level.objects[0].child_object_type = ObjectType::SuperMushroom.into();
+        level.objects.push(object(ObjectType::Goomba, 8, 1, 1, 1));
+        level.objects[1].object_type = 120;
+
+        let json = level_to_json(&level);
+        assert!(json.contains("\"EditHatenaBlock\""));
+        assert!(json.contains("\"EditKinoko\""));
+        assert_eq!(level_from_json(&json).unwrap(), level);
+    }
+
+    #[test]
+    fn test_from_legacy_name() {
+        assert_eq!(
+            from_legacy_name("EditKinokoFunny"),
+            Some(ObjectType::MysteryMushroom)
+        );
+        assert_eq!(from_legacy_name("editblock"), Some(ObjectType::Brick));
+        assert_eq!(from_legacy_name("EditRengaBlock"), Some(ObjectType::Brick));
+        assert_eq!(from_legacy_name("EditKatainaBlock"), None);
+    }
+}
@@ -1,13 +1,21 @@
 use std::io::{Cursor, Seek, Write};
 
-use chrono::prelude::*;
 use num_enum::{IntoPrimitive, TryFromPrimitive};
 use packed_struct::prelude::*;
 
-use crate::{objects::Object, sound_effects::SoundEffect, Error};
+use crate::{
+    checksum::{ChecksumKind, ChecksumPolicy},
+    convert::ConversionReport,
+    date::{CreationDate, CreationTime},
+    objects::{Object, ObjectType},
+    sound_effects::SoundEffect,
+    validation::{validate_level_name, LevelNameIssue},
+    Error, FileKind,
+};
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy, IntoPrimitive, TryFromPrimitive, Default)]
 #[repr(u8)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum GameMode {
     #[default]
     SuperMarioBros,
@@ -39,8 +47,28 @@ impl PackedStruct for GameMode {
     }
 }
 
+impl GameMode {
+    // Highest ledge in blocks the player can reach with a running jump, ignoring powerups
+    pub fn jump_height(&self) -> u32 {
+        match self {
+            GameMode::SuperMarioBros => 4,
+            GameMode::Mario3 | GameMode::MarioWorld | GameMode::NewSuperMarioBrosU => 5,
+        }
+    }
+
+    // Widest horizontal distance in blocks covered by a running jump between equal heights
+    pub fn jump_length(&self) -> u32 {
+        match self {
+            GameMode::SuperMarioBros => 9,
+            GameMode::Mario3 | GameMode::MarioWorld => 10,
+            GameMode::NewSuperMarioBrosU => 11,
+        }
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, Clone, Copy, IntoPrimitive, TryFromPrimitive, Default)]
 #[repr(u8)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum CourseTheme {
     #[default]
     Overworld = 0,
@@ -53,6 +81,7 @@ pub enum CourseTheme {
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy, IntoPrimitive, TryFromPrimitive, Default)]
 #[repr(u8)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum AutoScroll {
     #[default]
     None = 0,
@@ -61,6 +90,18 @@ pub enum AutoScroll {
     Fast = 3,
 }
 
+impl AutoScroll {
+    // Rough camera speed in blocks per second, None when the level doesn't scroll
+    pub fn blocks_per_second(&self) -> Option<f32> {
+        match self {
+            AutoScroll::None => None,
+            AutoScroll::Slow => Some(2.0),
+            AutoScroll::Medium => Some(4.0),
+            AutoScroll::Fast => Some(8.0),
+        }
+    }
+}
+
 // 00 	u64 	Assumed to be a version number. Always 0xB (11) right now
 // 08 	u32 	Checksum. Standard CRC32 of the entire file from offset 0x10 onwards.
 // 0C 	padding 	4 unused bytes
@@ -94,9 +135,10 @@ pub enum AutoScroll {
 // 14F50 	padding 	0xB0 unused bytes
 
 #[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Level {
     pub version: u64,
-    pub creation_time: chrono::NaiveDateTime,
+    pub creation_time: CreationTime,
     pub level_name: String,
     pub game_mode: GameMode,
     pub course_theme: CourseTheme,
@@ -104,16 +146,97 @@ pub struct Level {
     pub auto_scroll: AutoScroll,
     pub flags: u8,
     pub width: u32,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_base64"))]
     pub mii_data: [u8; 0x60],
     pub objects: Vec<Object>,
     pub sound_effects: Vec<SoundEffect>,
 }
 
+// Reads big endian fields in order, failing instead of panicking when the buffer runs out
+struct ByteReader<'a> {
+    src: &'a [u8],
+}
+
+impl<'a> ByteReader<'a> {
+    fn new(src: &'a [u8]) -> Self {
+        ByteReader { src }
+    }
+
+    fn take(&mut self, length: usize) -> packed_struct::PackingResult<&'a [u8]> {
+        if self.src.len() < length {
+            return Err(packed_struct::PackingError::BufferTooSmall);
+        }
+        let (bytes, rest) = self.src.split_at(length);
+        self.src = rest;
+        Ok(bytes)
+    }
+
+    fn skip(&mut self, length: usize) -> packed_struct::PackingResult<()> {
+        self.take(length).map(|_| ())
+    }
+
+    fn array<const N: usize>(&mut self) -> packed_struct::PackingResult<[u8; N]> {
+        // take returns exactly N bytes
+        Ok(self.take(N)?.try_into().unwrap())
+    }
+
+    fn u8(&mut self) -> packed_struct::PackingResult<u8> {
+        Ok(self.array::<1>()?[0])
+    }
+
+    fn u16(&mut self) -> packed_struct::PackingResult<u16> {
+        self.array().map(u16::from_be_bytes)
+    }
+
+    fn u32(&mut self) -> packed_struct::PackingResult<u32> {
+        self.array().map(u32::from_be_bytes)
+    }
+
+    fn u64(&mut self) -> packed_struct::PackingResult<u64> {
+        self.array().map(u64::from_be_bytes)
+    }
+}
+
+// Packing works on borrowed buffers so the 84KB level never has to live on the stack, the
+// PackedStruct impl is kept for compatibility
 impl PackedStruct for Level {
     type ByteArray = [u8; 0x15000];
 
     fn pack(&self) -> packed_struct::PackingResult<Self::ByteArray> {
         let mut bytes = [0; 0x15000];
+        self.pack_into(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    fn unpack(src: &Self::ByteArray) -> packed_struct::PackingResult<Self> {
+        Level::unpack_from(src)
+    }
+}
+
+impl Level {
+    pub const SIZE: usize = 0x15000;
+    pub const MAX_OBJECTS: usize = 2600;
+    pub const SOUND_EFFECTS: usize = 300;
+    // In UCS-2 units, not counting the zero terminator
+    pub const MAX_NAME_LENGTH: usize = 32;
+    // Checkpoint flags allowed in each area
+    pub const MAX_CHECKPOINTS: usize = 2;
+    // Door pairs and pipes leading somewhere allowed in each area
+    pub const MAX_DOOR_PAIRS: usize = 10;
+    pub const MAX_WARP_PIPES: usize = 10;
+    // The editor's limits, from a single screen up to 240 blocks
+    pub const MIN_BLOCK_WIDTH: u32 = 24;
+    pub const MAX_BLOCK_WIDTH: u32 = 240;
+
+    // Writes the level into a buffer of Level::SIZE bytes
+    fn pack_into(&self, bytes: &mut [u8]) -> packed_struct::PackingResult<()> {
+        if bytes.len() != Level::SIZE {
+            return Err(packed_struct::PackingError::BufferSizeMismatch {
+                expected: Level::SIZE,
+                actual: bytes.len(),
+            });
+        }
+        bytes.fill(0);
         let mut cursor = Cursor::new(&mut bytes[..]);
 
         // 00 	u64 	Assumed to be a version number. Always 0xB (11) right now
@@ -127,28 +250,29 @@ impl PackedStruct for Level {
         cursor
             .seek(std::io::SeekFrom::Start(0x10))
             .map_err(|_| packed_struct::PackingError::InternalError)?;
+        let creation_date = CreationDate::from_creation_time(&self.creation_time);
         // 10 	u16 	Creation year
-        let year = self.creation_time.year() as u16;
+        let year = creation_date.year;
         cursor
             .write_all(&year.to_be_bytes())
             .map_err(|_| packed_struct::PackingError::InternalError)?;
         // 12 	u8 	Creation month
-        let month = self.creation_time.month() as u8;
+        let month = creation_date.month;
         cursor
             .write_all(&[month])
             .map_err(|_| packed_struct::PackingError::InternalError)?;
         // 13 	u8 	Creation day
-        let day = self.creation_time.day() as u8;
+        let day = creation_date.day;
         cursor
             .write_all(&[day])
             .map_err(|_| packed_struct::PackingError::InternalError)?;
         // 14 	u8 	Creation hour
-        let hour = self.creation_time.hour() as u8;
+        let hour = creation_date.hour;
         cursor
             .write_all(&[hour])
             .map_err(|_| packed_struct::PackingError::InternalError)?;
         // 15 	u8 	Creation minute
-        let minute = self.creation_time.minute() as u8;
+        let minute = creation_date.minute;
         cursor
             .write_all(&[minute])
             .map_err(|_| packed_struct::PackingError::InternalError)?;
@@ -168,8 +292,7 @@ impl PackedStruct for Level {
             .map_err(|_| packed_struct::PackingError::InternalError)?;
         let mut name_bytes = [0; 0x42];
         for i in 0..0x21 {
-            name_bytes[i * 2] = name[i] as u8;
-            name_bytes[i * 2 + 1] = 0;
+            name_bytes[i * 2..i * 2 + 2].copy_from_slice(&name[i].to_be_bytes());
         }
         cursor
             .write_all(&name_bytes)
@@ -259,162 +382,179 @@ impl PackedStruct for Level {
         let checksum = crc32fast::hash(&bytes[0x10..]);
 
         let mut cursor = Cursor::new(&mut bytes[..]);
+        cursor
+            .seek(std::io::SeekFrom::Start(0x8))
+            .map_err(|_| packed_struct::PackingError::InternalError)?;
         cursor
             .write_all(&checksum.to_be_bytes())
             .map_err(|_| packed_struct::PackingError::InternalError)?;
 
-        Ok(bytes)
+        Ok(())
     }
 
-    fn unpack(src: &Self::ByteArray) -> packed_struct::PackingResult<Self> {
-        // 00 	u64 	Assumed to be a version number. Always 0xB (11) right now
-        let version = u64::from_be_bytes(
-            src[0..8]
-                .try_into()
-                .map_err(|_| packed_struct::PackingError::InvalidValue)?,
+    // Reads a level from a buffer of Level::SIZE bytes
+    fn unpack_from(src: &[u8]) -> packed_struct::PackingResult<Level> {
+        let mut level = Level::new(
+            0,
+            CreationDate::default()
+                .to_creation_time()
+                .ok_or(packed_struct::PackingError::InternalError)?,
+            String::new(),
+            GameMode::default(),
+            CourseTheme::default(),
+            0,
+            AutoScroll::default(),
+            0,
+            0,
+            [0; 0x60],
+            Vec::new(),
+            Vec::new(),
         );
+        level.read_from(src)?;
+        Ok(level)
+    }
 
-        // 08 	u32 	Checksum. Standard CRC32 of the entire file from offset 0x10 onwards. (Will write this later)
-        // 0C 	padding 	4 unused bytes
+    // Fills an existing level, reusing its name and object buffers. The level is only touched
+    // once the header has been validated
+    fn read_from(&mut self, src: &[u8]) -> packed_struct::PackingResult<()> {
+        if src.len() != Level::SIZE {
+            return Err(packed_struct::PackingError::BufferSizeMismatch {
+                expected: Level::SIZE,
+                actual: src.len(),
+            });
+        }
+        let mut reader = ByteReader::new(src);
 
-        // 10 	u16 	Creation year
-        let year = u16::from_be_bytes(
-            src[0x10..0x12]
-                .try_into()
-                .map_err(|_| packed_struct::PackingError::InvalidValue)?,
-        );
-        // 12 	u8 	Creation month
-        let month = src[0x12];
-        // 13 	u8 	Creation day
-        let day = src[0x13];
-        // 14 	u8 	Creation hour
-        let hour = src[0x14];
-        // 15 	u8 	Creation minute
-        let minute = src[0x15];
+        // Each phase gets its own block so the spans are siblings rather than nested
+        let object_count = {
+            trace_span!("header");
+            // 00 	u64 	Assumed to be a version number. Always 0xB (11) right now
+            let version = reader.u64()?;
 
-        let creation_time = chrono::NaiveDateTime::new(
-            chrono::NaiveDate::from_ymd_opt(year as i32, month as u32, day as u32)
-                .ok_or(packed_struct::PackingError::InvalidValue)?,
-            chrono::NaiveTime::from_hms_opt(hour as u32, minute as u32, 0)
-                .ok_or(packed_struct::PackingError::InvalidValue)?,
-        );
+            // 08 	u32 	Checksum. Standard CRC32 of the entire file from offset 0x10 onwards. (Will write this later)
+            // 0C 	padding 	4 unused bytes
+            reader.skip(8)?;
 
-        // 16 	u8 	Unknown
-        // 17 	u8 	Unknown
-        // 18 	u64 Unknown
-        // 20 	u8 	Unknown
-        // 21 	padding 	7 unused bytes
+            // 10 	u16 	Creation year
+            // 12 	u8 	Creation month
+            // 13 	u8 	Creation day
+            // 14 	u8 	Creation hour
+            // 15 	u8 	Creation minute
+            let creation_time = CreationDate {
+                year: reader.u16()?,
+                month: reader.u8()?,
+                day: reader.u8()?,
+                hour: reader.u8()?,
+                minute: reader.u8()?,
+            }
+            .to_creation_time()
+            .ok_or(packed_struct::PackingError::InvalidValue)?;
 
-        // 28 	u16[0x21] 	UCS-2 course name - 32 characters long plus zero terminator
-        let mut name_chars = [0u16; 0x21];
+            // 16 	u8 	Unknown
+            // 17 	u8 	Unknown
+            // 18 	u64 Unknown
+            // 20 	u8 	Unknown
+            // 21 	padding 	7 unused bytes
+            reader.skip(0x12)?;
 
-        for i in 0..0x21 {
-            name_chars[i] = u16::from_be_bytes(
-                src[(0x28 + i * 2)..(0x28 + i * 2 + 2)]
-                    .try_into()
-                    .map_err(|_| packed_struct::PackingError::InvalidValue)?,
-            );
-        }
+            // 28 	u16[0x21] 	UCS-2 course name - 32 characters long plus zero terminator
+            let mut name_chars = [0u16; 0x21];
+            for c in &mut name_chars {
+                *c = reader.u16()?;
+            }
 
-        let mut name_bytes = [0u8; 0x21 * 4];
-        ucs2::decode(&name_chars, &mut name_bytes)
-            .map_err(|_| packed_struct::PackingError::InternalError)?;
-        
-        let name = String::from_utf8(name_bytes.to_vec()).map_err(|_| packed_struct::PackingError::InternalError)?;
+            // The name ends at the zero terminator
+            let name_length = name_chars.iter().position(|&c| c == 0).unwrap_or(0x21);
+            let name = &name_chars[..name_length];
 
-        // 6A 	char[2] 	Game mode ('M1', 'M3', 'MW', 'WU')
-        let game_mode = GameMode::unpack(
-            &src[0x6A..0x6C]
-                .try_into()
-                .map_err(|_| packed_struct::PackingError::InvalidValue)?,
-        )?;
+            // 6A 	char[2] 	Game mode ('M1', 'M3', 'MW', 'WU')
+            let game_mode = GameMode::unpack(&reader.array()?)?;
 
-        // 6C 	u8 	Unknown
+            // 6C 	u8 	Unknown
+            reader.skip(1)?;
 
-        // 6D 	u8 	Course theme (0 = overworld, 1 = underground, 2 = castle, 3 = airship, 4 = water, 5 = ghost house)
-        let course_theme = CourseTheme::try_from_primitive(src[0x6D]).map_err(|_| packed_struct::PackingError::InvalidValue)?;
+            // 6D 	u8 	Course theme (0 = overworld, 1 = underground, 2 = castle, 3 = airship, 4 = water, 5 = ghost house)
+            let course_theme = CourseTheme::try_from_primitive(reader.u8()?)
+                .map_err(|_| packed_struct::PackingError::InvalidValue)?;
 
-        // 6E 	u8 	Unknown
-        // 6F 	u8 	Unknown
+            // 6E 	u8 	Unknown
+            // 6F 	u8 	Unknown
+            reader.skip(2)?;
 
-        // 70 	u16 	Time limit
-        let time_limit = u16::from_be_bytes(
-            src[0x70..0x72]
-                .try_into()
-                .map_err(|_| packed_struct::PackingError::InvalidValue)?,
-        );
-        // 72 	u8 	Autoscroll (0 = none, 1 = slow, 2 = medium, 3 = fast)
-        let auto_scroll = AutoScroll::try_from_primitive(src[0x72]).map_err(|_| packed_struct::PackingError::InvalidValue)?;
+            // 70 	u16 	Time limit
+            let time_limit = reader.u16()?;
+            // 72 	u8 	Autoscroll (0 = none, 1 = slow, 2 = medium, 3 = fast)
+            let auto_scroll = AutoScroll::try_from_primitive(reader.u8()?)
+                .map_err(|_| packed_struct::PackingError::InvalidValue)?;
 
-        // 73 	u8 	Flags
-        let flags = src[0x73];
-        // 74 	u32 	    Width
-        let width = u32::from_be_bytes(
-            src[0x74..0x78]
-                .try_into()
-                .map_err(|_| packed_struct::PackingError::InvalidValue)?,
-        );
-        // 78 	u8[0x60] 	    Mii data
-        let mut mii_data = [0; 0x60];
-        mii_data.copy_from_slice(&src[0x78..0xD8]);
+            // 73 	u8 	Flags
+            let flags = reader.u8()?;
+            // 74 	u32 	    Width
+            let width = reader.u32()?;
+            // 78 	u8[0x60] 	    Mii data
+            let mii_data = reader.array()?;
 
-        // D8 	u32 	Unknown
-        // DC 	u32 	Unknown
-        // E0 	padding 	0xC unused bytes
+            // D8 	u32 	Unknown
+            // DC 	u32 	Unknown
+            // E0 	padding 	0xC unused bytes
+            reader.skip(0x14)?;
 
-        // EC 	u32 	Object count
-        let object_count = u32::from_be_bytes(
-            src[0xEC..0xF0]
-                .try_into()
-                .map_err(|_| packed_struct::PackingError::InvalidValue)?,
-        );
+            // EC 	u32 	Object count
+            let object_count = reader.u32()?;
 
-        // F0 	obj_t[2600] 	Objects (note that the full size is reserved even if the course has less than 2600 objects)
-        let mut objects = Vec::new();
-        for i in 0..object_count {
-            let object = Object::unpack(
-                &src[(0xF0 + i as usize * 0x20)..(0xF0 + (i + 1) as usize * 0x20)]
-                    .try_into()
-                    .map_err(|_| packed_struct::PackingError::InvalidValue)?,
-            )?;
-            objects.push(object);
+            // F0 	obj_t[2600] 	Objects (note that the full size is reserved even if the course has less than 2600 objects)
+            if object_count as usize > Level::MAX_OBJECTS {
+                return Err(packed_struct::PackingError::InvalidValue);
+            }
+
+            self.version = version;
+            self.creation_time = creation_time;
+            self.level_name.clear();
+            // Each unit is a single character, surrogates can't be stored so they become U+FFFD
+            self.level_name.extend(
+                name.iter()
+                    .map(|&c| char::from_u32(c.into()).unwrap_or(char::REPLACEMENT_CHARACTER)),
+            );
+            self.game_mode = game_mode;
+            self.course_theme = course_theme;
+            self.time_limit = time_limit;
+            self.auto_scroll = auto_scroll;
+            self.flags = flags;
+            self.width = width;
+            self.mii_data = mii_data;
+
+            object_count
+        };
+
+        {
+            trace_span!("objects", count = object_count);
+            self.objects.clear();
+            self.objects.reserve(object_count as usize);
+            let mut objects = ByteReader::new(reader.take(Level::MAX_OBJECTS * 0x20)?);
+            for _ in 0..object_count {
+                self.objects.push(Object::unpack(&objects.array()?)?);
+            }
         }
 
         // 145F0 	effect_t[300] 	Sound effects
-        let mut sound_effects = Vec::new();
-        for i in 0..300 {
-            let effect = SoundEffect::unpack(
-                &src[(0x145F0 + i as usize * 0x8)..(0x145F0 + (i + 1) as usize * 0x8)]
-                    .try_into()
-                    .map_err(|_| packed_struct::PackingError::InvalidValue)?,
-            )?;
-            sound_effects.push(effect);
+        self.sound_effects.clear();
+        self.sound_effects.reserve(Level::SOUND_EFFECTS);
+        for _ in 0..Level::SOUND_EFFECTS {
+            self.sound_effects
+                .push(SoundEffect::unpack(&reader.array()?)?);
         }
-        
 
         // 14F50 	padding 	0xB0 unused bytes
 
-        Ok(Level {
-            version,
-            creation_time,
-            level_name: name,
-            game_mode,
-            course_theme,
-            time_limit,
-            auto_scroll,
-            flags,
-            width,
-            mii_data,
-            objects,
-            sound_effects,
-        })
+        Ok(())
     }
 }
 
 impl Level {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         version: u64,
-        creation_time: chrono::NaiveDateTime,
+        creation_time: CreationTime,
         level_name: String,
         game_mode: GameMode,
         course_theme: CourseTheme,
@@ -442,17 +582,51 @@ impl Level {
         }
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
     pub fn from_bytes(bytes: &[u8]) -> Result<Level, Error> {
-        Level::unpack(
-            &bytes
-                .try_into()
-                .map_err(|_| Error::InvalidData)?,
-        ).map_err(|_| Error::InvalidData)
+        Level::from_bytes_with_policy(bytes, ChecksumPolicy::default())
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub fn from_bytes_with_policy(bytes: &[u8], policy: ChecksumPolicy) -> Result<Level, Error> {
+        Level::check_size(bytes)?;
+        policy.check(ChecksumKind::CourseData, bytes)?;
+        Level::unpack_from(bytes).map_err(|_| Error::InvalidData)
     }
 
+    // Parses into an existing level so hot loops can reuse its allocations
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub fn unpack_into(&mut self, bytes: &[u8]) -> Result<(), Error> {
+        Level::check_size(bytes)?;
+        ChecksumPolicy::default().check(ChecksumKind::CourseData, bytes)?;
+
+        self.read_from(bytes).map_err(|_| Error::InvalidData)
+    }
+
+    fn check_size(bytes: &[u8]) -> Result<(), Error> {
+        if bytes.len() != Level::SIZE {
+            return Err(Error::WrongSize {
+                expected: Level::SIZE,
+                actual: bytes.len(),
+                kind: FileKind::CourseData,
+            });
+        }
+        Ok(())
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
     pub fn to_bytes(&self) -> Result<Vec<u8>, Error> {
-        let packed = self.pack().map_err(|_| Error::InvalidData)?;
-        Ok(packed.to_vec())
+        // Names that don't fit would otherwise be cut short or fail deep inside packing
+        if let Some(issue) = validate_level_name(&self.level_name)
+            .into_iter()
+            .find(LevelNameIssue::is_fatal)
+        {
+            return Err(Error::InvalidLevelName(issue));
+        }
+
+        let mut bytes = vec![0; Level::SIZE];
+        self.pack_into(&mut bytes).map_err(|_| Error::InvalidData)?;
+        Ok(bytes)
     }
 
     // Width in file / 16, in range of [0, 240]
@@ -464,4 +638,480 @@ impl Level {
     pub fn block_height(&self) -> u32 {
         27
     }
-}
\ No newline at end of file
+
+    // Objects in the order the game draws them, back to front, see Layer
+    pub fn objects_sorted_by_layer(&self) -> Vec<&Object> {
+        let mut objects: Vec<&Object> = self.objects.iter().collect();
+        // Stable, so ties keep their stored order
+        objects.sort_by_key(|object| (object.layer(), object.z_position));
+        objects
+    }
+
+    // Checkpoint flags as (object index, x, y), positions in blocks
+    pub fn checkpoints(&self) -> Vec<(usize, i32, i32)> {
+        self.objects
+            .iter()
+            .enumerate()
+            .filter(|(_, object)| object.get_object_type() == Some(ObjectType::CheckpointFlag))
+            .map(|(index, object)| {
+                let (x, y, _, _) = object.get_block_bounds();
+                (index, x, y)
+            })
+            .collect()
+    }
+
+    // Shrinks or grows the level to the narrowest width covering every object, within the editor's
+    // limits. Returns the new width in blocks
+    pub fn fit_width(&mut self) -> u32 {
+        let right = self
+            .objects
+            .iter()
+            .map(|object| {
+                let (left, _, width, _) = object.get_block_bounds();
+                (left.max(0) as u32).saturating_add(width)
+            })
+            .max()
+            .unwrap_or(0);
+        let block_width = right.clamp(Level::MIN_BLOCK_WIDTH, Level::MAX_BLOCK_WIDTH);
+
+        self.width = block_width * 16;
+        block_width
+    }
+
+    // Swaps the type of every matching object and child, e.g. contents of ? blocks, returning how
+    // many were changed. Flags are kept unless a mapping is given
+    pub fn replace_objects(
+        &mut self,
+        from: ObjectType,
+        to: ObjectType,
+        flag_map: Option<&dyn Fn(u32) -> u32>,
+    ) -> usize {
+        let map = |flags: u32| flag_map.map_or(flags, |flag_map| flag_map(flags));
+        let mut count = 0;
+        for object in &mut self.objects {
+            if object.object_type == i8::from(from) {
+                object.object_type = to.into();
+                object.object_flags = map(object.object_flags);
+                count += 1;
+            }
+            if object.child_object_type == i8::from(from) {
+                object.child_object_type = to.into();
+                object.child_object_flags = map(object.child_object_flags);
+                count += 1;
+            }
+        }
+        count
+    }
+
+    // Rewrites every object, dropping those mapped to None. Returns how many were changed or dropped
+    pub fn map_objects(&mut self, mut f: impl FnMut(Object) -> Option<Object>) -> usize {
+        let mut count = 0;
+        let objects = std::mem::take(&mut self.objects);
+        for object in objects {
+            match f(object.clone()) {
+                Some(mapped) => {
+                    count += usize::from(mapped != object);
+                    self.objects.push(mapped);
+                }
+                None => count += 1,
+            }
+        }
+        count
+    }
+
+    pub fn wing_all_enemies(&mut self) -> usize {
+        self.map_objects(|mut object| {
+            if is_enemy(&object) {
+                object.set_wings(true);
+            }
+            Some(object)
+        })
+    }
+
+    pub fn grow_all_enemies(&mut self) -> usize {
+        self.map_objects(|mut object| {
+            if is_enemy(&object) {
+                object.set_big(true);
+            }
+            Some(object)
+        })
+    }
+
+    // Removes power-ups placed directly and empties blocks holding them
+    pub fn remove_all_powerups(&mut self) -> usize {
+        let is_powerup = |object_type: i8| {
+            ObjectType::try_from(object_type).is_ok_and(|object_type| object_type.is_powerup())
+        };
+        self.map_objects(|mut object| {
+            if is_powerup(object.object_type) {
+                return None;
+            }
+            if is_powerup(object.child_object_type) {
+                object.child_object_type = -1;
+                object.child_object_flags = 0;
+                object.child_object_transformation_id = -1;
+            }
+            Some(object)
+        })
+    }
+
+    // Puts an effect in the first free slot of the table and attaches it to any object at that
+    // block without one. Returns the slot used. Effects store their block in a byte, so one past
+    // block 255 of an over-wide level fails with Error::InvalidData
+    pub fn place_sound_effect(&mut self, sound_type: u8, x: u32, y: i16) -> Result<usize, Error> {
+        if x >= self.block_width() || !(0..self.block_height() as i16).contains(&y) {
+            return Err(Error::InvalidData);
+        }
+        let effect = SoundEffect {
+            sound_type,
+            variation: 0,
+            x_position: u8::try_from(x).map_err(|_| Error::InvalidData)?,
+            y_position: y as u8,
+        };
+        // A type 0 effect at the origin would read back as an empty slot
+        if effect.get_variation().is_none() || effect.is_empty() {
+            return Err(Error::InvalidData);
+        }
+
+        self.sound_effects.resize(
+            self.sound_effects.len().max(Level::SOUND_EFFECTS),
+            SoundEffect::default(),
+        );
+        let index = self.sound_effects[..Level::SOUND_EFFECTS]
+            .iter()
+            .position(SoundEffect::is_empty)
+            .ok_or(Error::FileTooLarge)?;
+        self.sound_effects[index] = effect;
+
+        for object in &mut self.objects {
+            if object.effect_index < 0 && object.get_x_block() == x && object.get_y_block() == y {
+                object.effect_index = index as i16;
+            }
+        }
+
+        Ok(index)
+    }
+
+    // Moves the placed effects to the start of the table, merging duplicates and rewriting every
+    // object's effect_index to match. Objects referencing an empty slot are detached. Returns the
+    // number of duplicates merged
+    pub fn compact_sound_effects(&mut self) -> usize {
+        let mut effects: Vec<SoundEffect> = Vec::with_capacity(Level::SOUND_EFFECTS);
+        let mut remap = vec![-1; self.sound_effects.len()];
+        let mut duplicates = 0;
+
+        for (index, effect) in self.sound_effects.iter().enumerate() {
+            if effect.is_empty() {
+                continue;
+            }
+            remap[index] = match effects.iter().position(|existing| existing == effect) {
+                Some(existing) => {
+                    duplicates += 1;
+                    existing as i16
+                }
+                None => {
+                    effects.push(effect.clone());
+                    effects.len() as i16 - 1
+                }
+            };
+        }
+
+        for object in &mut self.objects {
+            if object.effect_index >= 0 {
+                object.effect_index = remap
+                    .get(object.effect_index as usize)
+                    .copied()
+                    .unwrap_or(-1);
+            }
+        }
+
+        effects.resize(
+            self.sound_effects.len().max(Level::SOUND_EFFECTS),
+            SoundEffect::default(),
+        );
+        self.sound_effects = effects;
+        duplicates
+    }
+
+    // Empties the slot and detaches every object referencing it, returning the effect that was
+    // there
+    pub fn remove_sound_effect(&mut self, index: usize) -> Option<SoundEffect> {
+        let slot = self.sound_effects.get_mut(index)?;
+        if slot.is_empty() {
+            return None;
+        }
+        let effect = std::mem::take(slot);
+
+        for object in &mut self.objects {
+            if object.effect_index == index as i16 {
+                object.effect_index = -1;
+            }
+        }
+
+        Some(effect)
+    }
+
+    // Mystery Mushroom costumes are dropped along with the mushrooms, as are flags the new style
+    // doesn't offer on a part, such as the Big Mushroom's FLAG_BIG
+    pub fn set_game_mode(&mut self, game_mode: GameMode) -> ConversionReport {
+        let mut report = ConversionReport {
+            from: self.game_mode,
+            to: game_mode,
+            replaced: Vec::new(),
+            removed_objects: Vec::new(),
+            removed_children: Vec::new(),
+        };
+
+        let objects = std::mem::take(&mut self.objects);
+        for (index, mut object) in objects.into_iter().enumerate() {
+            let new_index = self.objects.len();
+            if let Some(object_type) = object.get_object_type() {
+                object.object_flags &= !object_type.flags_unavailable_in(game_mode);
+                match object_type.replacement_in(game_mode) {
+                    Some(replacement) if replacement == object_type => {}
+                    Some(replacement) => {
+                        object.object_type = replacement.into();
+                        object.transformation_id = -1;
+                        report.replaced.push((new_index, object_type, replacement));
+                    }
+                    None => {
+                        report.removed_objects.push((index, object));
+                        continue;
+                    }
+                }
+            }
+
+            if let Ok(child_type) = ObjectType::try_from(object.child_object_type) {
+                object.child_object_flags &= !child_type.flags_unavailable_in(game_mode);
+                match child_type.replacement_in(game_mode) {
+                    Some(replacement) if replacement == child_type => {}
+                    Some(replacement) => {
+                        object.child_object_type = replacement.into();
+                        object.child_object_transformation_id = -1;
+                        report.replaced.push((new_index, child_type, replacement));
+                    }
+                    None => {
+                        object.child_object_type = -1;
+                        object.child_object_flags = 0;
+                        object.child_object_transformation_id = -1;
+                        report.removed_children.push(new_index);
+                    }
+                }
+            }
+            self.objects.push(object);
+        }
+        self.game_mode = game_mode;
+
+        report
+    }
+}
+
+fn is_enemy(object: &Object) -> bool {
+    object
+        .get_object_type()
+        .is_some_and(|object_type| object_type.is_enemy())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        fixtures::{minimal_level, minimal_sub_level},
+        thumbnail::Thumbnail,
+        validation::validate,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_unpack_into_reuses_level() {
+        let main = minimal_level(GameMode::Mario3, CourseTheme::Airship);
+        let sub = minimal_sub_level(GameMode::Mario3, CourseTheme::Underground);
+
+        let mut level = Level::from_bytes(&main.to_bytes().unwrap()).unwrap();
+        level.unpack_into(&sub.to_bytes().unwrap()).unwrap();
+        assert_eq!(level, sub);
+        level.unpack_into(&main.to_bytes().unwrap()).unwrap();
+        assert_eq!(level, main);
+
+        let mut bytes = sub.to_bytes().unwrap();
+        bytes[0xEC..0xF0].copy_from_slice(&2601u32.to_be_bytes());
+        assert!(level.unpack_into(&bytes).is_err());
+        assert_eq!(level, main);
+    }
+
+    #[test]
+    fn test_name_decoding() {
+        let mut level = minimal_level(GameMode::SuperMarioBros, CourseTheme::Overworld);
+        let mut bytes = level.to_bytes().unwrap();
+        // "A", a lone surrogate, "B", then garbage after the terminator
+        for (i, c) in [0x41u16, 0xD800, 0x42, 0, 0xDC00].iter().enumerate() {
+            bytes[0x28 + i * 2..0x2A + i * 2].copy_from_slice(&c.to_be_bytes());
+        }
+
+        level.unpack_into(&bytes).unwrap();
+        assert_eq!(level.level_name, "A\u{FFFD}B");
+    }
+
+    #[test]
+    fn test_fit_width() {
+        let mut level = minimal_level(GameMode::SuperMarioBros, CourseTheme::Overworld);
+        level.width = 100 * 16;
+        assert_eq!(level.fit_width(), 24);
+
+        level.objects.push(Object::coin(60, 5));
+        assert_eq!(level.fit_width(), 61);
+        assert_eq!(level.width, 61 * 16);
+
+        level.objects.push(Object::coin(300, 5));
+        assert_eq!(level.fit_width(), Level::MAX_BLOCK_WIDTH);
+    }
+
+    #[test]
+    fn test_replace_objects() {
+        let mut level = minimal_level(GameMode::MarioWorld, CourseTheme::Castle);
+        let mut goomba = Object::goomba(5, 1);
+        goomba.object_flags = 0x6000_0040;
+        level.objects.push(goomba);
+        level
+            .objects
+            .push(Object::question_block(7, 4, Some(ObjectType::Goomba)));
+
+        let replaced = level.replace_objects(ObjectType::Goomba, ObjectType::DryBones, None);
+        assert_eq!(replaced, 2);
+        let dry_bones = &level.objects[level.objects.len() - 2];
+        assert_eq!(dry_bones.get_object_type(), Some(ObjectType::DryBones));
+        assert_eq!(dry_bones.object_flags, 0x6000_0040);
+        assert_eq!(
+            level.objects.last().unwrap().child_object_type,
+            i8::from(ObjectType::DryBones)
+        );
+
+        let clear = |flags: u32| flags & !0x40;
+        level.replace_objects(ObjectType::DryBones, ObjectType::Boo, Some(&clear));
+        assert_eq!(
+            level.objects[level.objects.len() - 2].object_flags,
+            0x6000_0000
+        );
+    }
+
+    #[test]
+    fn test_wrong_size() {
+        let level = minimal_level(GameMode::SuperMarioBros, CourseTheme::Overworld);
+        let bytes = level.to_bytes().unwrap();
+        assert!(matches!(
+            Level::from_bytes(&bytes[..0x1000]),
+            Err(Error::WrongSize {
+                expected: Level::SIZE,
+                actual: 0x1000,
+                kind: FileKind::CourseData
+            })
+        ));
+
+        let thumbnail = Thumbnail::from_bytes_with_policy(&bytes, ChecksumPolicy::Ignore);
+        assert!(matches!(
+            thumbnail,
+            Err(Error::WrongSize {
+                kind: FileKind::Thumbnail,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_objects_sorted_by_layer() {
+        let mut level = minimal_level(GameMode::MarioWorld, CourseTheme::Overworld);
+        level.objects = vec![
+            Object::goomba(3, 1),
+            Object::ground(3, 0),
+            Object::of(ObjectType::SemisolidPlatform).unwrap().at(2, 0),
+            Object::coin(3, 3),
+        ];
+        level.objects[0].z_position = 10;
+
+        let sorted = level.objects_sorted_by_layer();
+        let expected = [2, 1, 3, 0].map(|index| &level.objects[index]);
+        assert_eq!(sorted, expected);
+    }
+
+    #[test]
+    fn test_bulk_edits() {
+        let mut level = minimal_level(GameMode::Mario3, CourseTheme::Overworld);
+        let objects = level.objects.len();
+        level.objects.push(Object::goomba(5, 1));
+        level.objects.push(Object::koopa(6, 1));
+        level
+            .objects
+            .push(Object::of(ObjectType::SuperMushroom).unwrap().at(7, 1));
+        level
+            .objects
+            .push(Object::question_block(8, 4, Some(ObjectType::FireFlower)));
+
+        assert_eq!(level.wing_all_enemies(), 2);
+        assert_eq!(level.grow_all_enemies(), 2);
+        assert!(level.objects[objects].has_wings() && level.objects[objects].is_big());
+        assert!(!level.objects[0].has_wings());
+
+        assert_eq!(level.remove_all_powerups(), 2);
+        assert_eq!(level.objects.len(), objects + 3);
+        assert_eq!(level.objects.last().unwrap().child_object_type, -1);
+        assert_eq!(level.remove_all_powerups(), 0);
+    }
+
+    #[test]
+    fn test_place_sound_effect() {
+        let mut level = minimal_level(GameMode::NewSuperMarioBrosU, CourseTheme::Castle);
+        level.objects.push(Object::goomba(5, 1));
+
+        assert_eq!(level.place_sound_effect(3, 5, 1).unwrap(), 0);
+        assert_eq!(level.place_sound_effect(4, 6, 2).unwrap(), 1);
+        assert_eq!(level.objects.last().unwrap().effect_index, 0);
+        assert!(validate(&level).is_empty());
+        assert!(level.place_sound_effect(3, 100, 1).is_err());
+
+        let mut wide = level.clone();
+        wide.width = 300 * 16;
+        assert!(matches!(
+            wide.place_sound_effect(3, 256, 1),
+            Err(Error::InvalidData)
+        ));
+        assert_eq!(wide.sound_effects, level.sound_effects);
+
+        assert_eq!(level.remove_sound_effect(0).unwrap().sound_type, 3);
+        assert_eq!(level.remove_sound_effect(0), None);
+        assert_eq!(level.objects.last().unwrap().effect_index, -1);
+        assert_eq!(level.place_sound_effect(5, 7, 1).unwrap(), 0);
+
+        for effect in &mut level.sound_effects {
+            effect.sound_type = 1;
+        }
+        assert!(matches!(
+            level.place_sound_effect(5, 7, 1),
+            Err(Error::FileTooLarge)
+        ));
+    }
+
+    #[test]
+    fn test_compact_sound_effects() {
+        let mut level = minimal_level(GameMode::Mario3, CourseTheme::Water);
+        let effect = SoundEffect::from_raw(0x01000501);
+        level.sound_effects[3] = effect.clone();
+        level.sound_effects[7] = effect.clone();
+        level.sound_effects[9] = SoundEffect::from_raw(0x02000601);
+        for (x, effect_index) in [(5, 7), (6, 9), (8, 4)] {
+            let mut goomba = Object::goomba(x, 1);
+            goomba.effect_index = effect_index;
+            level.objects.push(goomba);
+        }
+
+        assert_eq!(level.compact_sound_effects(), 1);
+        assert_eq!(level.sound_effects.len(), Level::SOUND_EFFECTS);
+        assert_eq!(level.sound_effects[0], effect);
+        assert_eq!(level.sound_effects[1].raw(), 0x02000601);
+        assert!(level.sound_effects[2..].iter().all(SoundEffect::is_empty));
+        let effect_indices: Vec<i16> = level.objects[level.objects.len() - 3..]
+            .iter()
+            .map(|object| object.effect_index)
+            .collect();
+        assert_eq!(effect_indices, [0, 1, -1]);
+    }
+}
@@ -1,33 +1,109 @@
 use course::CourseData;
 
+#[macro_use]
+mod trace;
+
+pub mod analysis;
+pub mod animation;
+pub mod bundle;
+pub mod checksum;
+pub mod container;
+pub mod convert;
+pub mod date;
+pub mod edit;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod fixtures;
+pub mod format;
+#[cfg(feature = "arbitrary")]
+pub mod fuzz;
+pub mod generate;
+#[cfg(feature = "sqlite")]
+pub mod index;
+pub mod interpreters;
+#[cfg(feature = "legacy-json")]
+pub mod legacy;
 pub mod level;
+pub mod migrate;
+#[cfg(feature = "uniffi")]
+pub mod mobile;
+#[cfg(feature = "node")]
+pub mod node;
+pub mod object_table;
 pub mod objects;
+pub mod progress;
+pub mod render;
+pub mod repair;
+pub mod salvage;
+pub mod save;
+#[cfg(feature = "server")]
+pub mod server;
+#[cfg(feature = "serde")]
+mod serde_base64;
+#[cfg(feature = "smmdb")]
+pub mod smmdb;
 pub mod sound_effects;
+pub mod spawners;
+pub mod stacks;
+pub mod terrain;
+#[cfg(feature = "test_support")]
+pub mod test_support;
+pub mod tiles;
 pub mod thumbnail;
 pub mod course;
+pub mod validation;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+#[cfg(feature = "uniffi")]
+uniffi::setup_scaffolding!();
 
 #[derive(Debug)]
 pub enum Error {
     FileTooLarge,
     InvalidData,
     MissingCourseData(CourseData),
+    // A tar without every course file, with what it did hold
+    IncompleteTar(course::TarReport),
+    Io(std::io::Error),
+    Http(String),
+    Database(String),
+    Cancelled,
+    InvalidLevelName(validation::LevelNameIssue),
+    // Only returned under ChecksumPolicy::Require
+    ChecksumMismatch {
+        expected: u32,
+        actual: u32,
+    },
+    // e.g. a partial download, or a compressed file
+    WrongSize {
+        expected: usize,
+        actual: usize,
+        kind: FileKind,
+    },
+}
+
+// The kinds of file the crate reads, for errors
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileKind {
+    // course_data.cdt or course_data_sub.cdt
+    CourseData,
+    // thumbnail0.tnl or thumbnail1.tnl
+    Thumbnail,
+    // save.dat
+    SaveData,
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::course::Course;
-    use crate::level::Level;
-
-    use super::*;
-    use std::fs::File;
-    use std::io::Read;
+    use crate::fixtures::{minimal_course, course_files};
+    use crate::level::{CourseTheme, GameMode, Level};
 
     #[test]
     fn test_course() {
-        let mut file = File::open("test/course_data.cdt").unwrap();
-        let mut buffer = Vec::new();
-        file.read_to_end(&mut buffer).unwrap();
-        let level = Level::from_bytes(&buffer).unwrap();
+        let course = minimal_course(GameMode::SuperMarioBros, CourseTheme::Overworld);
+        let buffer = &course_files(&course).unwrap()[0].1;
+        let level = Level::from_bytes(buffer).unwrap();
         println!("{:?}", level);
     }
 }
\ No newline at end of file
@@ -0,0 +1,194 @@
+// Moving levels between the format versions written by different game updates. Every course seen
+// so far is version 0xB, earlier versions are assumed to share its layout minus the parts later
+// updates added.
+
+use std::fmt;
+
+use crate::{
+    level::Level,
+    objects::{Object, ObjectType},
+    sound_effects::SoundEffect,
+    Error,
+};
+
+pub const CURRENT_VERSION: u64 = 0xB;
+
+// Parts added by game updates, along with the first version able to store them
+const ADDED_PARTS: &[(ObjectType, u64)] = &[(ObjectType::CheckpointFlag, 0xB)];
+
+// A console update of the game, e.g. 1.10
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct GameVersion {
+    pub major: u8,
+    pub minor: u8,
+}
+
+impl GameVersion {
+    pub const LAUNCH: GameVersion = GameVersion { major: 1, minor: 0 };
+}
+
+impl fmt::Display for GameVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{:02}", self.major, self.minor)
+    }
+}
+
+// Parts the launch version can't load, with the update adding them. Incomplete: only the
+// checkpoint flag is listed, and its version hasn't been checked against the official patch notes.
+// Parts added by other updates, and any sound effects, count as launch parts
+const GAME_UPDATES: &[(ObjectType, GameVersion)] = &[(
+    ObjectType::CheckpointFlag,
+    GameVersion {
+        major: 1,
+        minor: 10,
+    },
+)];
+
+impl Level {
+    // A lower bound on the update needed to load the level, going by the parts and children it
+    // uses. Only as good as GAME_UPDATES, which is incomplete
+    pub fn required_game_version(&self) -> GameVersion {
+        let required = |object_type: i8| {
+            GAME_UPDATES
+                .iter()
+                .find(|(added, _)| i8::from(*added) == object_type)
+                .map_or(GameVersion::LAUNCH, |(_, version)| *version)
+        };
+
+        self.objects
+            .iter()
+            .flat_map(|object| [object.object_type, object.child_object_type])
+            .map(required)
+            .max()
+            .unwrap_or(GameVersion::LAUNCH)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MigrationReport {
+    pub from: u64,
+    pub to: u64,
+    // Objects the target version can't store, by their index before migrating
+    pub removed_objects: Vec<(usize, Object)>,
+    // Objects that lost a child the target version can't store, by their index after migrating
+    pub removed_children: Vec<usize>,
+}
+
+impl MigrationReport {
+    pub fn is_lossless(&self) -> bool {
+        self.removed_objects.is_empty() && self.removed_children.is_empty()
+    }
+}
+
+// Whether levels of the given version can hold the object type
+pub fn supports(version: u64, object_type: ObjectType) -> bool {
+    ADDED_PARTS
+        .iter()
+        .find(|(added, _)| *added == object_type)
+        .is_none_or(|(_, since)| version >= *since)
+}
+
+// Brings an older level up to CURRENT_VERSION, padding the sound effect table to its full size
+pub fn upgrade(level: &mut Level) -> Result<MigrationReport, Error> {
+    if level.version > CURRENT_VERSION {
+        return Err(Error::InvalidData);
+    }
+
+    let report = MigrationReport {
+        from: level.version,
+        to: CURRENT_VERSION,
+        ..Default::default()
+    };
+    if level.sound_effects.len() < Level::SOUND_EFFECTS {
+        level
+            .sound_effects
+            .resize(Level::SOUND_EFFECTS, SoundEffect::default());
+    }
+    level.version = CURRENT_VERSION;
+
+    Ok(report)
+}
+
+// Takes a level back to an older version, removing the parts that version can't store
+pub fn downgrade(level: &mut Level, version: u64) -> Result<MigrationReport, Error> {
+    if version > level.version {
+        return Err(Error::InvalidData);
+    }
+
+    let mut report = MigrationReport {
+        from: level.version,
+        to: version,
+        ..Default::default()
+    };
+    let supported = |object_type: i8| {
+        ObjectType::try_from(object_type).map_or(true, |object_type| supports(version, object_type))
+    };
+
+    let objects = std::mem::take(&mut level.objects);
+    for (index, mut object) in objects.into_iter().enumerate() {
+        if !supported(object.object_type) {
+            report.removed_objects.push((index, object));
+            continue;
+        }
+        if !supported(object.child_object_type) {
+            object.child_object_type = -1;
+            object.child_object_flags = 0;
+            object.child_object_transformation_id = -1;
+            report.removed_children.push(level.objects.len());
+        }
+        level.objects.push(object);
+    }
+    level.version = version;
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        fixtures::minimal_level,
+        level::{CourseTheme, GameMode},
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_downgrade_and_upgrade() {
+        let mut level = minimal_level(GameMode::Mario3, CourseTheme::Castle);
+        let checkpoint = Object::of(ObjectType::CheckpointFlag).unwrap().at(5, 1);
+        level.objects.insert(0, checkpoint.clone());
+        level.objects.push(Object::question_block(
+            6,
+            4,
+            Some(ObjectType::CheckpointFlag),
+        ));
+
+        let report = downgrade(&mut level, 0xA).unwrap();
+        assert_eq!(report.removed_objects, vec![(0, checkpoint)]);
+        assert_eq!(report.removed_children, vec![level.objects.len() - 1]);
+        assert_eq!(level.objects.last().unwrap().child_object_type, -1);
+        assert_eq!(level.version, 0xA);
+        assert!(downgrade(&mut level, 0xB).is_err());
+
+        level.sound_effects.truncate(10);
+        let report = upgrade(&mut level).unwrap();
+        assert!(report.is_lossless());
+        assert_eq!((report.from, report.to), (0xA, CURRENT_VERSION));
+        assert_eq!(level.sound_effects.len(), Level::SOUND_EFFECTS);
+    }
+
+    #[test]
+    fn test_required_game_version() {
+        let mut level = minimal_level(GameMode::NewSuperMarioBrosU, CourseTheme::Overworld);
+        assert_eq!(level.required_game_version(), GameVersion::LAUNCH);
+
+        level.objects.push(Object::question_block(
+            6,
+            4,
+            Some(ObjectType::CheckpointFlag),
+        ));
+        let version = level.required_game_version();
+        assert!(version > GameVersion::LAUNCH);
+        assert_eq!(version.to_string(), "1.10");
+    }
+}
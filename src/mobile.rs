@@ -0,0 +1,261 @@
+// UniFFI exports for the Kotlin and Swift course viewers. Objects are immutable on the foreign side,
+// every call returns fresh values.
+
+use std::{fmt, sync::Arc};
+
+use crate::{
+    course::Course,
+    level::Level,
+    thumbnail::Thumbnail,
+    validation::{consistency_issues, validate},
+    Error,
+};
+
+#[derive(Debug, uniffi::Error)]
+pub enum MobileError {
+    // Bad or truncated course data, with the crate's error for details
+    InvalidData { message: String },
+    Io { message: String },
+}
+
+impl fmt::Display for MobileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MobileError::InvalidData { message } | MobileError::Io { message } => {
+                write!(f, "{}", message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for MobileError {}
+
+impl From<Error> for MobileError {
+    fn from(error: Error) -> Self {
+        match error {
+            Error::Io(error) => MobileError::Io {
+                message: error.to_string(),
+            },
+            error => MobileError::InvalidData {
+                message: format!("{:?}", error),
+            },
+        }
+    }
+}
+
+#[derive(uniffi::Record)]
+pub struct LevelObject {
+    pub x: u32,
+    pub y: i16,
+    pub z: u32,
+    pub width: i8,
+    pub height: i8,
+    pub object_type: i8,
+    pub child_object_type: i8,
+    pub flags: u32,
+    pub link_id: i16,
+    // None for types that haven't been identified
+    pub name: Option<String>,
+}
+
+#[derive(uniffi::Object)]
+pub struct MobileLevel {
+    level: Level,
+}
+
+#[uniffi::export]
+impl MobileLevel {
+    // Parses the bytes of a course_data.cdt or course_data_sub.cdt
+    #[uniffi::constructor]
+    pub fn new(bytes: Vec<u8>) -> Result<Arc<MobileLevel>, MobileError> {
+        Ok(Arc::new(MobileLevel {
+            level: Level::from_bytes(&bytes)?,
+        }))
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>, MobileError> {
+        Ok(self.level.to_bytes()?)
+    }
+
+    pub fn name(&self) -> String {
+        self.level.level_name.clone()
+    }
+
+    pub fn creation_time(&self) -> String {
+        self.level.creation_time.to_string()
+    }
+
+    pub fn game_mode(&self) -> u8 {
+        self.level.game_mode.into()
+    }
+
+    pub fn course_theme(&self) -> u8 {
+        self.level.course_theme.into()
+    }
+
+    pub fn time_limit(&self) -> u16 {
+        self.level.time_limit
+    }
+
+    pub fn width(&self) -> u32 {
+        self.level.width
+    }
+
+    pub fn objects(&self) -> Vec<LevelObject> {
+        self.level
+            .objects
+            .iter()
+            .map(|object| LevelObject {
+                x: object.x_position,
+                y: object.y_position,
+                z: object.z_position,
+                width: object.width,
+                height: object.height,
+                object_type: object.object_type,
+                child_object_type: object.child_object_type,
+                flags: object.object_flags,
+                link_id: object.link_id,
+                name: object
+                    .get_object_type()
+                    .map(|object_type| object_type.display_name().to_string()),
+            })
+            .collect()
+    }
+
+    // Every issue the default validator finds, one description each
+    pub fn validate(&self) -> Vec<String> {
+        validate(&self.level)
+            .iter()
+            .map(|issue| format!("{:?}", issue))
+            .collect()
+    }
+}
+
+#[derive(uniffi::Object)]
+pub struct MobileThumbnail {
+    thumbnail: Thumbnail,
+}
+
+#[uniffi::export]
+impl MobileThumbnail {
+    // Parses the bytes of a thumbnail0.tnl or thumbnail1.tnl
+    #[uniffi::constructor]
+    pub fn new(bytes: Vec<u8>) -> Result<Arc<MobileThumbnail>, MobileError> {
+        Ok(Arc::new(MobileThumbnail {
+            thumbnail: Thumbnail::from_bytes(&bytes)?,
+        }))
+    }
+
+    pub fn jpeg(&self) -> Vec<u8> {
+        self.thumbnail.jpeg_data.clone()
+    }
+}
+
+#[derive(uniffi::Object)]
+pub struct MobileCourse {
+    course: Course,
+}
+
+#[uniffi::export]
+impl MobileCourse {
+    // Parses a tar holding the four course files
+    #[uniffi::constructor]
+    pub fn from_tar(bytes: Vec<u8>) -> Result<Arc<MobileCourse>, MobileError> {
+        Ok(Arc::new(MobileCourse {
+            course: Course::from_tar(&mut tar::Archive::new(bytes.as_slice()))?,
+        }))
+    }
+
+    pub fn to_tar(&self) -> Result<Vec<u8>, MobileError> {
+        let mut builder = tar::Builder::new(Vec::new());
+        self.course.to_tar(&mut builder)?;
+        Ok(builder.into_inner().map_err(Error::Io)?)
+    }
+
+    pub fn level(&self) -> Arc<MobileLevel> {
+        Arc::new(MobileLevel {
+            level: self.course.level.clone(),
+        })
+    }
+
+    pub fn sub_level(&self) -> Arc<MobileLevel> {
+        Arc::new(MobileLevel {
+            level: self.course.sub_level.clone(),
+        })
+    }
+
+    pub fn preview(&self) -> Arc<MobileThumbnail> {
+        Arc::new(MobileThumbnail {
+            thumbnail: self.course.level_preview.clone(),
+        })
+    }
+
+    pub fn thumbnail(&self) -> Arc<MobileThumbnail> {
+        Arc::new(MobileThumbnail {
+            thumbnail: self.course.level_thumbnail.clone(),
+        })
+    }
+
+    // Disagreements between the two areas, see validation::ConsistencyIssue
+    pub fn check_consistency(&self) -> Vec<String> {
+        consistency_issues(&self.course.level, &self.course.sub_level)
+            .iter()
+            .map(|issue| format!("{:?}", issue))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        fixtures::minimal_course,
+        level::{CourseTheme, GameMode},
+        objects::Object,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let mut course = minimal_course(GameMode::NewSuperMarioBrosU, CourseTheme::Airship);
+        course.level.objects.push(Object::goomba(6, 1));
+        let mut tar = Vec::new();
+        course.to_tar(&mut tar::Builder::new(&mut tar)).unwrap();
+
+        let mobile = MobileCourse::from_tar(tar).unwrap();
+        assert_eq!(
+            MobileCourse::from_tar(mobile.to_tar().unwrap())
+                .unwrap()
+                .course,
+            course
+        );
+        assert!(mobile.check_consistency().is_empty());
+
+        let level = MobileLevel::new(mobile.level().to_bytes().unwrap()).unwrap();
+        assert_eq!(level.level, course.level);
+        let goomba = level.objects().pop().unwrap();
+        assert_eq!((goomba.x, goomba.name.as_deref()), (60, Some("Goomba")));
+
+        let thumbnail = course.level_thumbnail.to_bytes().unwrap();
+        assert_eq!(
+            MobileThumbnail::new(thumbnail).unwrap().jpeg(),
+            mobile.thumbnail().jpeg()
+        );
+    }
+
+    #[test]
+    fn test_bad_input() {
+        assert!(matches!(
+            MobileLevel::new(vec![0; 16]),
+            Err(MobileError::InvalidData { .. })
+        ));
+        assert!(matches!(
+            MobileThumbnail::new(Vec::new()),
+            Err(MobileError::InvalidData { .. })
+        ));
+        assert!(matches!(
+            MobileCourse::from_tar(vec![0; 0x200]),
+            Err(MobileError::InvalidData { .. })
+        ));
+    }
+}
@@ -0,0 +1,214 @@
+use napi::bindgen_prelude::{Buffer, Error as NapiError, Result, Status};
+use napi_derive::napi;
+
+use crate::{
+    course::Course,
+    level::Level,
+    validation::{validate, validate_level_name, LevelNameIssue},
+    Error,
+};
+
+fn to_napi_error(error: Error) -> NapiError {
+    NapiError::new(Status::GenericFailure, format!("{:?}", error))
+}
+
+#[napi(js_name = "Level")]
+pub struct NodeLevel {
+    level: Level,
+}
+
+#[napi]
+impl NodeLevel {
+    // Parses the bytes of a course_data.cdt or course_data_sub.cdt
+    #[napi(constructor)]
+    pub fn new(bytes: Buffer) -> Result<NodeLevel> {
+        Ok(NodeLevel {
+            level: Level::from_bytes(&bytes).map_err(to_napi_error)?,
+        })
+    }
+
+    #[napi]
+    pub fn to_bytes(&self) -> Result<Buffer> {
+        Ok(self.level.to_bytes().map_err(to_napi_error)?.into())
+    }
+
+    #[napi(getter)]
+    pub fn name(&self) -> String {
+        self.level.level_name.clone()
+    }
+
+    #[napi(setter)]
+    pub fn set_name(&mut self, name: String) -> Result<()> {
+        if let Some(issue) = validate_level_name(&name)
+            .into_iter()
+            .find(LevelNameIssue::is_fatal)
+        {
+            return Err(to_napi_error(Error::InvalidLevelName(issue)));
+        }
+        self.level.level_name = name;
+        Ok(())
+    }
+
+    #[napi(getter)]
+    pub fn creation_time(&self) -> String {
+        self.level.creation_time.to_string()
+    }
+
+    #[napi(getter)]
+    pub fn game_mode(&self) -> u8 {
+        self.level.game_mode.into()
+    }
+
+    #[napi(getter)]
+    pub fn course_theme(&self) -> u8 {
+        self.level.course_theme.into()
+    }
+
+    #[napi(getter)]
+    pub fn time_limit(&self) -> u16 {
+        self.level.time_limit
+    }
+
+    #[napi(setter)]
+    pub fn set_time_limit(&mut self, time_limit: u16) {
+        self.level.time_limit = time_limit;
+    }
+
+    #[napi(getter)]
+    pub fn width(&self) -> u32 {
+        self.level.width
+    }
+
+    #[napi(getter)]
+    pub fn object_count(&self) -> u32 {
+        self.level.objects.len() as u32
+    }
+
+    // Every issue the default validator finds, one description each
+    #[napi]
+    pub fn validate(&self) -> Vec<String> {
+        validate(&self.level)
+            .iter()
+            .map(|issue| format!("{:?}", issue))
+            .collect()
+    }
+}
+
+#[napi(js_name = "Course")]
+pub struct NodeCourse {
+    course: Course,
+}
+
+#[napi]
+impl NodeCourse {
+    // Parses a tar holding the four course files
+    #[napi(factory)]
+    pub fn from_tar(bytes: Buffer) -> Result<NodeCourse> {
+        Ok(NodeCourse {
+            course: Course::from_tar(&mut tar::Archive::new(bytes.as_ref()))
+                .map_err(to_napi_error)?,
+        })
+    }
+
+    #[napi]
+    pub fn to_tar(&self) -> Result<Buffer> {
+        let mut builder = tar::Builder::new(Vec::new());
+        self.course.to_tar(&mut builder).map_err(to_napi_error)?;
+        Ok(builder
+            .into_inner()
+            .map_err(|error| to_napi_error(Error::Io(error)))?
+            .into())
+    }
+
+    #[napi(getter)]
+    pub fn level(&self) -> NodeLevel {
+        NodeLevel {
+            level: self.course.level.clone(),
+        }
+    }
+
+    #[napi(getter)]
+    pub fn sub_level(&self) -> NodeLevel {
+        NodeLevel {
+            level: self.course.sub_level.clone(),
+        }
+    }
+
+    #[napi(getter)]
+    pub fn preview_jpeg(&self) -> Buffer {
+        self.course.level_preview.jpeg_data.clone().into()
+    }
+
+    #[napi(getter)]
+    pub fn thumbnail_jpeg(&self) -> Buffer {
+        self.course.level_thumbnail.jpeg_data.clone().into()
+    }
+
+    // Issues in either area, followed by disagreements between them
+    #[napi]
+    pub fn validate(&self) -> Vec<String> {
+        let level = validate(&self.course.level)
+            .into_iter()
+            .map(|issue| format!("main: {:?}", issue));
+        let sub_level = validate(&self.course.sub_level)
+            .into_iter()
+            .map(|issue| format!("sub: {:?}", issue));
+        let consistency = self
+            .course
+            .check_consistency()
+            .into_iter()
+            .map(|issue| format!("{:?}", issue));
+        level.chain(sub_level).chain(consistency).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        fixtures::minimal_course,
+        level::{CourseTheme, GameMode},
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let course = minimal_course(GameMode::Mario3, CourseTheme::Castle);
+        let mut tar = Vec::new();
+        course.to_tar(&mut tar::Builder::new(&mut tar)).unwrap();
+
+        let node_course = NodeCourse::from_tar(tar.into()).unwrap();
+        assert_eq!(node_course.course, course);
+        let tar = node_course.to_tar().unwrap();
+        assert_eq!(NodeCourse::from_tar(tar).unwrap().course, course);
+
+        let mut level = node_course.level();
+        level.set_name("Renamed".to_string()).unwrap();
+        level.set_time_limit(100);
+        let level = NodeLevel::new(level.to_bytes().unwrap()).unwrap();
+        assert_eq!(
+            (level.name().as_str(), level.time_limit()),
+            ("Renamed", 100)
+        );
+        assert_eq!(level.object_count() as usize, course.level.objects.len());
+        assert!(node_course.validate().is_empty());
+    }
+
+    #[test]
+    fn test_bad_input() {
+        assert!(NodeLevel::new(vec![0; 16].into()).is_err());
+        assert!(NodeCourse::from_tar(vec![0; 0x200].into()).is_err());
+
+        let mut level = NodeCourse::from_tar({
+            let mut tar = Vec::new();
+            minimal_course(GameMode::MarioWorld, CourseTheme::Water)
+                .to_tar(&mut tar::Builder::new(&mut tar))
+                .unwrap();
+            tar.into()
+        })
+        .unwrap()
+        .level();
+        assert!(level.set_name("a".repeat(33)).is_err());
+        level.set_name("Tab\tCastle".to_string()).unwrap();
+    }
+}
@@ -0,0 +1,196 @@
+// Objects stored as parallel columns rather than a Vec<Object>, so scans over a single field across
+// many levels stay in cache and can be vectorized. Row i of every column is the object at index i.
+
+use crate::objects::{Object, ObjectType};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ObjectTable {
+    pub x_position: Vec<u32>,
+    pub z_position: Vec<u32>,
+    pub y_position: Vec<i16>,
+    pub width: Vec<i8>,
+    pub height: Vec<i8>,
+    pub object_flags: Vec<u32>,
+    pub child_object_flags: Vec<u32>,
+    pub extended_object_data: Vec<u32>,
+    pub object_type: Vec<i8>,
+    pub child_object_type: Vec<i8>,
+    pub link_id: Vec<i16>,
+    pub effect_index: Vec<i16>,
+    pub transformation_id: Vec<i8>,
+    pub child_object_transformation_id: Vec<i8>,
+}
+
+impl ObjectTable {
+    pub fn new() -> ObjectTable {
+        ObjectTable::default()
+    }
+
+    pub fn with_capacity(capacity: usize) -> ObjectTable {
+        ObjectTable {
+            x_position: Vec::with_capacity(capacity),
+            z_position: Vec::with_capacity(capacity),
+            y_position: Vec::with_capacity(capacity),
+            width: Vec::with_capacity(capacity),
+            height: Vec::with_capacity(capacity),
+            object_flags: Vec::with_capacity(capacity),
+            child_object_flags: Vec::with_capacity(capacity),
+            extended_object_data: Vec::with_capacity(capacity),
+            object_type: Vec::with_capacity(capacity),
+            child_object_type: Vec::with_capacity(capacity),
+            link_id: Vec::with_capacity(capacity),
+            effect_index: Vec::with_capacity(capacity),
+            transformation_id: Vec::with_capacity(capacity),
+            child_object_transformation_id: Vec::with_capacity(capacity),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.object_type.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.object_type.is_empty()
+    }
+
+    pub fn push(&mut self, object: &Object) {
+        self.x_position.push(object.x_position);
+        self.z_position.push(object.z_position);
+        self.y_position.push(object.y_position);
+        self.width.push(object.width);
+        self.height.push(object.height);
+        self.object_flags.push(object.object_flags);
+        self.child_object_flags.push(object.child_object_flags);
+        self.extended_object_data.push(object.extended_object_data);
+        self.object_type.push(object.object_type);
+        self.child_object_type.push(object.child_object_type);
+        self.link_id.push(object.link_id);
+        self.effect_index.push(object.effect_index);
+        self.transformation_id.push(object.transformation_id);
+        self.child_object_transformation_id
+            .push(object.child_object_transformation_id);
+    }
+
+    pub fn clear(&mut self) {
+        self.truncate(0);
+    }
+
+    pub fn truncate(&mut self, length: usize) {
+        self.x_position.truncate(length);
+        self.z_position.truncate(length);
+        self.y_position.truncate(length);
+        self.width.truncate(length);
+        self.height.truncate(length);
+        self.object_flags.truncate(length);
+        self.child_object_flags.truncate(length);
+        self.extended_object_data.truncate(length);
+        self.object_type.truncate(length);
+        self.child_object_type.truncate(length);
+        self.link_id.truncate(length);
+        self.effect_index.truncate(length);
+        self.transformation_id.truncate(length);
+        self.child_object_transformation_id.truncate(length);
+    }
+
+    pub fn get(&self, index: usize) -> Option<Object> {
+        (index < self.len()).then(|| {
+            Object::new(
+                self.x_position[index],
+                self.z_position[index],
+                self.y_position[index],
+                self.width[index],
+                self.height[index],
+                self.object_flags[index],
+                self.child_object_flags[index],
+                self.extended_object_data[index],
+                self.object_type[index],
+                self.child_object_type[index],
+                self.link_id[index],
+                self.effect_index[index],
+                self.transformation_id[index],
+                self.child_object_transformation_id[index],
+            )
+        })
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Object> + '_ {
+        (0..self.len()).filter_map(|index| self.get(index))
+    }
+
+    pub fn extend_from_slice(&mut self, objects: &[Object]) {
+        for object in objects {
+            self.push(object);
+        }
+    }
+
+    // Number of objects of the given type, only touching the type column
+    pub fn count_type(&self, object_type: ObjectType) -> usize {
+        let object_type = i8::from(object_type);
+        self.object_type
+            .iter()
+            .filter(|&&id| id == object_type)
+            .count()
+    }
+
+    pub fn to_objects(&self) -> Vec<Object> {
+        self.iter().collect()
+    }
+}
+
+impl From<&[Object]> for ObjectTable {
+    fn from(objects: &[Object]) -> Self {
+        let mut table = ObjectTable::with_capacity(objects.len());
+        table.extend_from_slice(objects);
+        table
+    }
+}
+
+impl From<Vec<Object>> for ObjectTable {
+    fn from(objects: Vec<Object>) -> Self {
+        ObjectTable::from(objects.as_slice())
+    }
+}
+
+impl From<&ObjectTable> for Vec<Object> {
+    fn from(table: &ObjectTable) -> Self {
+        table.to_objects()
+    }
+}
+
+impl From<ObjectTable> for Vec<Object> {
+    fn from(table: ObjectTable) -> Self {
+        table.to_objects()
+    }
+}
+
+impl<'a> FromIterator<&'a Object> for ObjectTable {
+    fn from_iter<I: IntoIterator<Item = &'a Object>>(iter: I) -> Self {
+        let mut table = ObjectTable::new();
+        for object in iter {
+            table.push(object);
+        }
+        table
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        fixtures::minimal_level,
+        level::{CourseTheme, GameMode},
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_object_table_round_trip() {
+        let level = minimal_level(GameMode::MarioWorld, CourseTheme::Overworld);
+        let table = ObjectTable::from(level.objects.as_slice());
+
+        assert_eq!(table.len(), level.objects.len());
+        assert_eq!(table.count_type(ObjectType::Goal), 1);
+        assert_eq!(table.get(table.len()), None);
+        assert_eq!(Vec::from(&table), level.objects);
+        assert_eq!(level.objects.iter().collect::<ObjectTable>(), table);
+    }
+}
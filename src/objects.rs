@@ -1,5 +1,11 @@
+use num_enum::{IntoPrimitive, TryFromPrimitive};
 use packed_struct::prelude::*;
 
+use crate::level::{CourseTheme, GameMode};
+
+mod compat;
+mod names;
+
 // 00 	u32 	X position (* 10)
 // 04 	u32 	Z position (* 10)
 // 08 	s16 	Y position (* 10)
@@ -16,6 +22,7 @@ use packed_struct::prelude::*;
 // 1F 	s8 	Child object's transformation ID (used by EditKinokoFunny)
 #[derive(Debug, PackedStruct, Clone, PartialEq, Eq)]
 #[packed_struct(bit_numbering = "msb0", endian = "msb")]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Object {
     #[packed_field(bytes = "0x00..=0x03")]
     pub x_position: u32,
@@ -48,7 +55,131 @@ pub struct Object {
     pub child_object_transformation_id: i8,
 }
 
+// A Mystery Mushroom costume by its raw ID, held in the mushroom's transformation ID. There's no
+// table of characters, display names or amiibo and unlock requirements: which character each ID
+// stands for hasn't been checked against the game, so pickers have to bring their own
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Costume(pub u8);
+
+// Size and look of a semisolid platform, mushroom platform or bridge. The position is the bottom
+// left tile, the platform's top being the highest row. Stored sizes below the part's minimum are
+// shown at the minimum by the game, so they decode to it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SemisolidConfig {
+    // In blocks
+    pub width: u8,
+    pub height: u8,
+    // The object flags, which pick the variant of the look. Which bit does what hasn't been worked
+    // out, so they're kept as they are
+    pub variant: u32,
+}
+
+impl SemisolidConfig {
+    // (type, narrowest width, shortest height) in blocks. Semisolid and mushroom platforms need
+    // room for both edges and a middle
+    const MIN_SIZES: [(ObjectType, u8, u8); 3] = [
+        (ObjectType::SemisolidPlatform, 3, 1),
+        (ObjectType::MushroomPlatform, 3, 1),
+        (ObjectType::Bridge, 1, 1),
+    ];
+    const MAX_HEIGHT: u8 = 27;
+
+    // None for other types
+    pub fn min_size(object_type: ObjectType) -> Option<(u8, u8)> {
+        SemisolidConfig::MIN_SIZES
+            .iter()
+            .find(|(semisolid, _, _)| *semisolid == object_type)
+            .map(|(_, width, height)| (*width, *height))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ConveyorDirection {
+    #[default]
+    Right,
+    Left,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ConveyorSpeed {
+    #[default]
+    Normal,
+    Fast,
+}
+
+// Which way the top of a conveyor belt moves and how quickly
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ConveyorConfig {
+    pub direction: ConveyorDirection,
+    pub speed: ConveyorSpeed,
+}
+
+// The way a burner's flame, a cannon's barrel or a bill blaster's opening points
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Orientation {
+    Up,
+    UpRight,
+    Right,
+    DownRight,
+    Down,
+    DownLeft,
+    Left,
+    UpLeft,
+}
+
+impl Orientation {
+    // Clockwise from up, the order the flag bits count in
+    const ALL: [Orientation; 8] = [
+        Orientation::Up,
+        Orientation::UpRight,
+        Orientation::Right,
+        Orientation::DownRight,
+        Orientation::Down,
+        Orientation::DownLeft,
+        Orientation::Left,
+        Orientation::UpLeft,
+    ];
+    const STRAIGHT: [Orientation; 4] = [
+        Orientation::Up,
+        Orientation::Right,
+        Orientation::Down,
+        Orientation::Left,
+    ];
+
+    // One block along the orientation as (x, y), y pointing up
+    pub fn offset(&self) -> (i32, i32) {
+        match self {
+            Orientation::Up => (0, 1),
+            Orientation::UpRight => (1, 1),
+            Orientation::Right => (1, 0),
+            Orientation::DownRight => (1, -1),
+            Orientation::Down => (0, -1),
+            Orientation::DownLeft => (-1, -1),
+            Orientation::Left => (-1, 0),
+            Orientation::UpLeft => (-1, 1),
+        }
+    }
+}
+
+// Object flag bits, as far as they've been worked out
+pub const FLAG_WINGS: u32 = 0x2;
+// Enlarged by a super mushroom in the editor
+pub const FLAG_BIG: u32 = 0x4000;
+// Conveyor belts only, set by the editor's turn and speed up actions
+pub const FLAG_CONVEYOR_LEFT: u32 = 0x8;
+pub const FLAG_CONVEYOR_FAST: u32 = 0x40000;
+// Burners, cannons and bill blasters, an index into Orientation::ALL
+pub const ORIENTATION_MASK: u32 = 0x0700_0000;
+const ORIENTATION_SHIFT: u32 = 24;
+
 impl Object {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         x_position: u32,
         z_position: u32,
@@ -83,6 +214,59 @@ impl Object {
         }
     }
 
+    // A one block object of the given type or name at the origin, with no child object. None if
+    // the name isn't known
+    pub fn of<T: TryInto<ObjectType>>(object_type: T) -> Option<Object> {
+        object_type.try_into().ok().map(Object::with_type)
+    }
+
+    // Moves the object to the given block
+    pub fn at(mut self, x: u32, y: i16) -> Object {
+        self.x_position = x * 10;
+        self.y_position = y * 10;
+        self
+    }
+
+    pub fn goomba(x: u32, y: i16) -> Object {
+        Object::with_type(ObjectType::Goomba).at(x, y)
+    }
+
+    pub fn koopa(x: u32, y: i16) -> Object {
+        Object::with_type(ObjectType::Koopa).at(x, y)
+    }
+
+    pub fn coin(x: u32, y: i16) -> Object {
+        Object::with_type(ObjectType::Coin).at(x, y)
+    }
+
+    pub fn ground(x: u32, y: i16) -> Object {
+        Object::with_type(ObjectType::Ground).at(x, y)
+    }
+
+    pub fn brick(x: u32, y: i16) -> Object {
+        Object::with_type(ObjectType::Brick).at(x, y)
+    }
+
+    // An empty ? Block gives a coin
+    pub fn question_block(x: u32, y: i16, contents: Option<ObjectType>) -> Object {
+        let mut object = Object::with_type(ObjectType::QuestionBlock).at(x, y);
+        object.child_object_type = contents.map_or(-1, i8::from);
+        object
+    }
+
+    // Pipes are two blocks wide, contents are spawned from the top
+    pub fn pipe(x: u32, y: i16, height: i8, contents: Option<ObjectType>) -> Object {
+        let mut object = Object::with_type(ObjectType::Pipe).at(x, y);
+        object.width = 2;
+        object.height = height;
+        object.child_object_type = contents.map_or(-1, i8::from);
+        object
+    }
+
+    pub(crate) fn with_type(object_type: ObjectType) -> Object {
+        Object::new(0, 0, 0, 1, 1, 0, 0, 0, object_type.into(), -1, -1, -1, -1, -1)
+    }
+
     pub fn get_x_block(&self) -> u32 {
         self.x_position / 10
     }
@@ -94,4 +278,652 @@ impl Object {
     pub fn get_y_block(&self) -> i16 {
         self.y_position / 10
     }
+
+    // Position in blocks as (x, y), keeping the offset within the block
+    pub fn position(&self) -> (f32, f32) {
+        (self.x_position as f32 / 10.0, self.y_position as f32 / 10.0)
+    }
+
+    // Rounded to the nearest tenth of a block, the precision positions are stored with. Negative x
+    // positions are moved to 0
+    pub fn set_position(&mut self, x: f32, y: f32) {
+        self.x_position = (x * 10.0).round() as u32;
+        self.y_position = (y * 10.0).round() as i16;
+    }
+
+    // The costume of a Mystery Mushroom, placed loose or held by this object. None for anything
+    // else, or when no costume is set
+    pub fn costume(&self) -> Option<Costume> {
+        let mystery_mushroom = i8::from(ObjectType::MysteryMushroom);
+        let id = if self.object_type == mystery_mushroom {
+            self.transformation_id
+        } else if self.child_object_type == mystery_mushroom {
+            self.child_object_transformation_id
+        } else {
+            return None;
+        };
+        u8::try_from(id).ok().map(Costume)
+    }
+
+    // Costume IDs have to fit the signed transformation ID
+    pub fn set_costume(&mut self, costume: Costume) -> Result<(), crate::Error> {
+        let mystery_mushroom = i8::from(ObjectType::MysteryMushroom);
+        let id = i8::try_from(costume.0).map_err(|_| crate::Error::InvalidData)?;
+        if self.object_type == mystery_mushroom {
+            self.transformation_id = id;
+        } else if self.child_object_type == mystery_mushroom {
+            self.child_object_transformation_id = id;
+        } else {
+            return Err(crate::Error::InvalidData);
+        }
+        Ok(())
+    }
+
+    // None if the object isn't a semisolid with a configurable size
+    pub fn semisolid_config(&self) -> Option<SemisolidConfig> {
+        let (min_width, min_height) = SemisolidConfig::min_size(self.get_object_type()?)?;
+        Some(SemisolidConfig {
+            width: (self.width.max(0) as u8).max(min_width),
+            height: (self.height.max(0) as u8).max(min_height),
+            variant: self.object_flags,
+        })
+    }
+
+    // Fails if the object isn't a semisolid or the size is outside what the editor allows
+    pub fn set_semisolid_config(&mut self, config: SemisolidConfig) -> Result<(), crate::Error> {
+        let (min_width, min_height) = self
+            .get_object_type()
+            .and_then(SemisolidConfig::min_size)
+            .ok_or(crate::Error::InvalidData)?;
+        let width = i8::try_from(config.width).map_err(|_| crate::Error::InvalidData)?;
+        if config.width < min_width
+            || config.height < min_height
+            || config.height > SemisolidConfig::MAX_HEIGHT
+        {
+            return Err(crate::Error::InvalidData);
+        }
+
+        self.width = width;
+        self.height = config.height as i8;
+        self.object_flags = config.variant;
+        Ok(())
+    }
+
+    // None if the object isn't a conveyor belt
+    pub fn conveyor_config(&self) -> Option<ConveyorConfig> {
+        if self.get_object_type()? != ObjectType::ConveyorBelt {
+            return None;
+        }
+        Some(ConveyorConfig {
+            direction: if self.object_flags & FLAG_CONVEYOR_LEFT != 0 {
+                ConveyorDirection::Left
+            } else {
+                ConveyorDirection::Right
+            },
+            speed: if self.object_flags & FLAG_CONVEYOR_FAST != 0 {
+                ConveyorSpeed::Fast
+            } else {
+                ConveyorSpeed::Normal
+            },
+        })
+    }
+
+    // Leaves the other flags alone. Fails if the object isn't a conveyor belt
+    pub fn set_conveyor_config(&mut self, config: ConveyorConfig) -> Result<(), crate::Error> {
+        if self.get_object_type() != Some(ObjectType::ConveyorBelt) {
+            return Err(crate::Error::InvalidData);
+        }
+        self.set_flag(
+            FLAG_CONVEYOR_LEFT,
+            config.direction == ConveyorDirection::Left,
+        );
+        self.set_flag(FLAG_CONVEYOR_FAST, config.speed == ConveyorSpeed::Fast);
+        Ok(())
+    }
+
+    // None if the object can't be turned
+    pub fn orientation(&self) -> Option<Orientation> {
+        if self.get_object_type()?.orientations().is_empty() {
+            return None;
+        }
+        let index = (self.object_flags & ORIENTATION_MASK) >> ORIENTATION_SHIFT;
+        Some(Orientation::ALL[index as usize])
+    }
+
+    // Fails if the object can't face that way
+    pub fn set_orientation(&mut self, orientation: Orientation) -> Result<(), crate::Error> {
+        let object_type = self.get_object_type().ok_or(crate::Error::InvalidData)?;
+        if !object_type.orientations().contains(&orientation) {
+            return Err(crate::Error::InvalidData);
+        }
+        let index = Orientation::ALL
+            .iter()
+            .position(|&other| other == orientation)
+            .unwrap() as u32;
+        self.object_flags = (self.object_flags & !ORIENTATION_MASK) | (index << ORIENTATION_SHIFT);
+        Ok(())
+    }
+
+    pub fn has_wings(&self) -> bool {
+        self.object_flags & FLAG_WINGS != 0
+    }
+
+    pub fn set_wings(&mut self, wings: bool) {
+        self.set_flag(FLAG_WINGS, wings);
+    }
+
+    pub fn is_big(&self) -> bool {
+        self.object_flags & FLAG_BIG != 0
+    }
+
+    pub fn set_big(&mut self, big: bool) {
+        self.set_flag(FLAG_BIG, big);
+    }
+
+    fn set_flag(&mut self, flag: u32, set: bool) {
+        if set {
+            self.object_flags |= flag;
+        } else {
+            self.object_flags &= !flag;
+        }
+    }
+
+    pub fn get_object_type(&self) -> Option<ObjectType> {
+        ObjectType::try_from_primitive(self.object_type).ok()
+    }
+
+    // Whether the style offers the part and the part it holds, flags included. See
+    // ObjectType::available_with_flags_in, unidentified types count as offered
+    pub fn available_in(&self, game_mode: GameMode) -> bool {
+        let child_type = ObjectType::try_from(self.child_object_type).ok();
+        [
+            (self.get_object_type(), self.object_flags),
+            (child_type, self.child_object_flags),
+        ]
+        .into_iter()
+        .all(|(object_type, flags)| {
+            object_type
+                .is_none_or(|object_type| object_type.available_with_flags_in(flags, game_mode))
+        })
+    }
+
+    // Unknown types are drawn with the actors, the layer most parts are on
+    pub fn layer(&self) -> Layer {
+        match self.get_object_type() {
+            Some(object_type) if object_type.is_semisolid() => Layer::Background,
+            Some(object_type) if object_type.is_solid() => Layer::Terrain,
+            _ => Layer::Actors,
+        }
+    }
+
+    // Tiles covered by the object as (left, bottom, width, height), the position being its bottom left tile
+    pub fn get_block_bounds(&self) -> (i32, i32, u32, u32) {
+        (
+            self.get_x_block() as i32,
+            self.get_y_block() as i32,
+            self.width.max(1) as u32,
+            self.height.max(1) as u32,
+        )
+    }
+}
+
+// Object type IDs as stored in the Object type byte. Not every ID has been identified yet,
+// unknown values are still preserved in Object::object_type.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, IntoPrimitive, TryFromPrimitive)]
+#[repr(i8)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ObjectType {
+    Goomba = 0,
+    Koopa = 1,
+    PiranhaPlant = 2,
+    HammerBro = 3,
+    Brick = 4,
+    QuestionBlock = 5,
+    HardBlock = 6,
+    Ground = 7,
+    Coin = 8,
+    Pipe = 9,
+    Trampoline = 10,
+    Lift = 11,
+    Thwomp = 12,
+    BillBlaster = 13,
+    MushroomPlatform = 14,
+    BobOmb = 15,
+    SemisolidPlatform = 16,
+    Bridge = 17,
+    PSwitch = 18,
+    Pow = 19,
+    SuperMushroom = 20,
+    DonutBlock = 21,
+    Cloud = 22,
+    NoteBlock = 23,
+    FireBar = 24,
+    Spiny = 25,
+    GoalGround = 26,
+    Goal = 27,
+    BuzzyBeetle = 28,
+    HiddenBlock = 29,
+    Lakitu = 30,
+    LakituCloud = 31,
+    BanzaiBill = 32,
+    OneUp = 33,
+    FireFlower = 34,
+    SuperStar = 35,
+    LavaLift = 36,
+    StartBlock = 37,
+    StartArrow = 38,
+    Magikoopa = 39,
+    SpikeTop = 40,
+    Boo = 41,
+    ClownCar = 42,
+    Spikes = 43,
+    MysteryMushroom = 44,
+    ShoeGoomba = 45,
+    DryBones = 46,
+    Cannon = 47,
+    Blooper = 48,
+    CastleBridge = 49,
+    HopChops = 50,
+    Skipsqueak = 51,
+    Wiggler = 52,
+    ConveyorBelt = 53,
+    Burner = 54,
+    Door = 55,
+    CheepCheep = 56,
+    Muncher = 57,
+    RockyWrench = 58,
+    Track = 59,
+    LavaBubble = 60,
+    ChainChomp = 61,
+    Bowser = 62,
+    IceBlock = 63,
+    Vine = 64,
+    ArrowSign = 66,
+    OneWayWall = 67,
+    Grinder = 68,
+    BoomBoom = 77,
+    CheckpointFlag = 90,
+}
+
+impl ObjectType {
+    // The editor's actor name, e.g. EditDokan for pipes
+    pub fn internal_name(&self) -> &'static str {
+        names::NAMES[self.name_index()].1
+    }
+
+    pub fn display_name(&self) -> &'static str {
+        names::NAMES[self.name_index()].2
+    }
+
+    // Looks up an internal or display name, ignoring case
+    pub fn from_name(name: &str) -> Option<ObjectType> {
+        names::NAMES
+            .iter()
+            .find(|(_, internal_name, display_name)| {
+                internal_name.eq_ignore_ascii_case(name) || display_name.eq_ignore_ascii_case(name)
+            })
+            .map(|(object_type, _, _)| *object_type)
+    }
+
+    // Whether the editor offers the part in the style, either placed or held by another part. Only
+    // the Mystery Mushroom is limited to a style
+    pub fn available_in(&self, game_mode: GameMode) -> bool {
+        compat::STYLE_EXCLUSIVE
+            .iter()
+            .find(|(object_type, _)| object_type == self)
+            .is_none_or(|(_, game_modes)| game_modes.contains(&game_mode))
+    }
+
+    // Like available_in, for the part with the object flags set. Flags turning the part into a
+    // different one, such as FLAG_BIG on a Mystery Mushroom, are checked against that part's styles
+    pub fn available_with_flags_in(&self, flags: u32, game_mode: GameMode) -> bool {
+        self.available_in(game_mode) && self.flags_unavailable_in(game_mode) & flags == 0
+    }
+
+    // The flags turning the part into one the style doesn't offer, cleared when converting
+    pub(crate) fn flags_unavailable_in(&self, game_mode: GameMode) -> u32 {
+        compat::FLAG_EXCLUSIVE
+            .iter()
+            .filter(|(object_type, _, game_modes)| {
+                object_type == self && !game_modes.contains(&game_mode)
+            })
+            .fold(0, |flags, (_, flag, _)| flags | flag)
+    }
+
+    // The part itself when the style offers it, otherwise the closest part it does. None when
+    // there's nothing close
+    pub fn replacement_in(&self, game_mode: GameMode) -> Option<ObjectType> {
+        if self.available_in(game_mode) {
+            return Some(*self);
+        }
+        compat::REPLACEMENTS
+            .iter()
+            .find(|(object_type, _)| object_type == self)
+            .map(|(_, replacement)| *replacement)
+            .filter(|replacement| replacement.available_in(game_mode))
+    }
+
+    // Whether the part acts differently in the theme than in the others, beyond its look
+    pub fn behaves_differently_in(&self, course_theme: CourseTheme) -> bool {
+        compat::THEME_DEPENDENT
+            .iter()
+            .find(|(object_type, _)| object_type == self)
+            .is_some_and(|(_, course_themes)| course_themes.contains(&course_theme))
+    }
+
+    fn name_index(&self) -> usize {
+        // Every variant has an entry
+        names::NAMES
+            .iter()
+            .position(|(object_type, _, _)| object_type == self)
+            .unwrap()
+    }
+
+    // Blocks the player can stand on and that can't be passed through from any side
+    pub fn is_solid(&self) -> bool {
+        matches!(
+            self,
+            ObjectType::Brick
+                | ObjectType::QuestionBlock
+                | ObjectType::HardBlock
+                | ObjectType::Ground
+                | ObjectType::Pipe
+                | ObjectType::BillBlaster
+                | ObjectType::NoteBlock
+                | ObjectType::GoalGround
+                | ObjectType::StartBlock
+                | ObjectType::Cannon
+                | ObjectType::ConveyorBelt
+                | ObjectType::Burner
+                | ObjectType::IceBlock
+        )
+    }
+
+    pub fn is_enemy(&self) -> bool {
+        matches!(
+            self,
+            ObjectType::Goomba
+                | ObjectType::Koopa
+                | ObjectType::PiranhaPlant
+                | ObjectType::HammerBro
+                | ObjectType::Thwomp
+                | ObjectType::BobOmb
+                | ObjectType::Spiny
+                | ObjectType::BuzzyBeetle
+                | ObjectType::Lakitu
+                | ObjectType::BanzaiBill
+                | ObjectType::Magikoopa
+                | ObjectType::SpikeTop
+                | ObjectType::Boo
+                | ObjectType::ShoeGoomba
+                | ObjectType::DryBones
+                | ObjectType::Blooper
+                | ObjectType::HopChops
+                | ObjectType::Skipsqueak
+                | ObjectType::Wiggler
+                | ObjectType::CheepCheep
+                | ObjectType::Muncher
+                | ObjectType::RockyWrench
+                | ObjectType::LavaBubble
+                | ObjectType::ChainChomp
+                | ObjectType::Bowser
+                | ObjectType::BoomBoom
+        )
+    }
+
+    // The ways the part can be turned in the editor, empty for parts that can't. Burners turn
+    // to the four sides, cannons in eighths, bill blasters stand up or hang upside down
+    pub fn orientations(&self) -> &'static [Orientation] {
+        match self {
+            ObjectType::Burner => &Orientation::STRAIGHT,
+            ObjectType::Cannon => &Orientation::ALL,
+            ObjectType::BillBlaster => &[Orientation::Up, Orientation::Down],
+            _ => &[],
+        }
+    }
+
+    // Parts that hurt on touch without being enemies that can be defeated, Munchers included
+    pub fn is_hazard(&self) -> bool {
+        matches!(
+            self,
+            ObjectType::Spikes
+                | ObjectType::Muncher
+                | ObjectType::FireBar
+                | ObjectType::Burner
+                | ObjectType::Grinder
+        )
+    }
+
+    pub fn is_powerup(&self) -> bool {
+        matches!(
+            self,
+            ObjectType::SuperMushroom
+                | ObjectType::OneUp
+                | ObjectType::FireFlower
+                | ObjectType::SuperStar
+                | ObjectType::MysteryMushroom
+        )
+    }
+
+    // Platforms the player can stand on but jump through from below, including moving ones
+    pub fn is_semisolid(&self) -> bool {
+        matches!(
+            self,
+            ObjectType::Lift
+                | ObjectType::MushroomPlatform
+                | ObjectType::SemisolidPlatform
+                | ObjectType::Bridge
+                | ObjectType::DonutBlock
+                | ObjectType::Cloud
+                | ObjectType::LavaLift
+                | ObjectType::CastleBridge
+        )
+    }
+}
+
+// Layers the game composites objects on, back to front. Within a layer Z position orders objects,
+// larger values in front, and objects with the same Z position are drawn in the order they're stored
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Layer {
+    // Semisolid platforms, bridges and lifts, which everything else passes in front of
+    Background,
+    // Solid blocks, pipes and other terrain
+    Terrain,
+    // Enemies, items and everything else
+    Actors,
+}
+
+impl TryFrom<&str> for ObjectType {
+    type Error = crate::Error;
+
+    fn try_from(name: &str) -> Result<Self, Self::Error> {
+        ObjectType::from_name(name).ok_or(crate::Error::InvalidData)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_names() {
+        for id in i8::MIN..=i8::MAX {
+            if let Ok(object_type) = ObjectType::try_from_primitive(id) {
+                assert_eq!(
+                    ObjectType::from_name(object_type.internal_name()),
+                    Some(object_type)
+                );
+                assert_eq!(
+                    ObjectType::from_name(object_type.display_name()),
+                    Some(object_type)
+                );
+            }
+        }
+        assert_eq!(ObjectType::from_name("editdokan"), Some(ObjectType::Pipe));
+        assert_eq!(ObjectType::Pipe.display_name(), "Pipe");
+        assert_eq!(ObjectType::from_name("Pipes"), None);
+    }
+
+    #[test]
+    fn test_construct_by_name() {
+        assert_eq!(
+            Object::of("Goomba").map(|object| object.at(3, 1)),
+            Some(Object::goomba(3, 1))
+        );
+        assert_eq!(Object::of(ObjectType::Koopa), Some(Object::koopa(0, 0)));
+        assert_eq!(Object::of("Goombas"), None);
+
+        let pipe = Object::pipe(10, 1, 4, Some(ObjectType::PiranhaPlant));
+        assert_eq!(pipe.get_block_bounds(), (10, 1, 2, 4));
+        assert_eq!(pipe.get_object_type(), Some(ObjectType::Pipe));
+        assert_eq!(pipe.child_object_type, i8::from(ObjectType::PiranhaPlant));
+    }
+
+    #[test]
+    fn test_costume() {
+        let mut block = Object::question_block(4, 4, Some(ObjectType::MysteryMushroom));
+        assert_eq!(block.costume(), None);
+        block.set_costume(Costume(12)).unwrap();
+        assert_eq!(block.child_object_transformation_id, 12);
+        assert_eq!(block.costume(), Some(Costume(12)));
+
+        assert!(Object::goomba(1, 1).set_costume(Costume(12)).is_err());
+        assert!(block.set_costume(Costume(200)).is_err());
+    }
+
+    #[test]
+    fn test_position() {
+        let mut fire_bar = Object::of(ObjectType::FireBar).unwrap().at(4, 3);
+        assert_eq!(fire_bar.position(), (4.0, 3.0));
+        fire_bar.set_position(4.5, 2.96);
+        assert_eq!((fire_bar.x_position, fire_bar.y_position), (45, 30));
+        assert_eq!(fire_bar.position(), (4.5, 3.0));
+        assert_eq!(fire_bar.get_block_bounds().0, 4);
+
+        fire_bar.set_position(-1.0, -0.5);
+        assert_eq!(fire_bar.position(), (0.0, -0.5));
+    }
+
+    #[test]
+    fn test_layer() {
+        assert_eq!(Object::of("Bridge").unwrap().layer(), Layer::Background);
+        assert_eq!(Object::ground(0, 0).layer(), Layer::Terrain);
+        assert_eq!(Object::goomba(0, 1).layer(), Layer::Actors);
+        assert!(Layer::Background < Layer::Actors);
+    }
+
+    #[test]
+    fn test_semisolid_config() {
+        let mut platform = Object::of(ObjectType::SemisolidPlatform).unwrap().at(2, 0);
+        let config = platform.semisolid_config().unwrap();
+        assert_eq!((config.width, config.height), (3, 1));
+
+        let taller = SemisolidConfig {
+            width: 5,
+            height: 4,
+            ..config
+        };
+        platform.set_semisolid_config(taller).unwrap();
+        assert_eq!(platform.get_block_bounds(), (2, 0, 5, 4));
+        assert_eq!(platform.semisolid_config(), Some(taller));
+
+        let narrow = SemisolidConfig { width: 2, ..config };
+        assert!(platform.set_semisolid_config(narrow).is_err());
+        assert!(Object::goomba(0, 0).semisolid_config().is_none());
+    }
+
+    #[test]
+    fn test_orientation() {
+        let mut burner = Object::of(ObjectType::Burner).unwrap();
+        assert_eq!(burner.orientation(), Some(Orientation::Up));
+        burner.set_orientation(Orientation::Left).unwrap();
+        assert_eq!(burner.orientation(), Some(Orientation::Left));
+        assert_eq!(burner.object_flags, 0x0600_0000);
+        assert!(burner.set_orientation(Orientation::UpLeft).is_err());
+
+        let mut cannon = Object::of(ObjectType::Cannon).unwrap();
+        cannon.set_orientation(Orientation::DownRight).unwrap();
+        assert_eq!(cannon.orientation(), Some(Orientation::DownRight));
+        assert_eq!(Orientation::DownRight.offset(), (1, -1));
+
+        assert!(Object::goomba(0, 0).orientation().is_none());
+    }
+
+    #[test]
+    fn test_conveyor_config() {
+        let mut conveyor = Object::of(ObjectType::ConveyorBelt).unwrap();
+        conveyor.set_big(true);
+        assert_eq!(conveyor.conveyor_config(), Some(ConveyorConfig::default()));
+
+        let config = ConveyorConfig {
+            direction: ConveyorDirection::Left,
+            speed: ConveyorSpeed::Fast,
+        };
+        conveyor.set_conveyor_config(config).unwrap();
+        assert_eq!(conveyor.conveyor_config(), Some(config));
+        assert!(conveyor.is_big());
+
+        conveyor
+            .set_conveyor_config(ConveyorConfig {
+                speed: ConveyorSpeed::Normal,
+                ..config
+            })
+            .unwrap();
+        assert_eq!(conveyor.object_flags, FLAG_BIG | FLAG_CONVEYOR_LEFT);
+        assert!(Object::goomba(0, 0).conveyor_config().is_none());
+        assert!(Object::goomba(0, 0).set_conveyor_config(config).is_err());
+    }
+
+    #[test]
+    fn test_compatibility() {
+        assert!(ObjectType::MysteryMushroom.available_in(GameMode::SuperMarioBros));
+        assert!(!ObjectType::MysteryMushroom.available_in(GameMode::NewSuperMarioBrosU));
+        assert!(ObjectType::Goomba.available_in(GameMode::MarioWorld));
+        let mut big = Object::of(ObjectType::MysteryMushroom).unwrap();
+        big.set_big(true);
+        assert!(big.available_in(GameMode::SuperMarioBros));
+        assert!(!big.available_in(GameMode::Mario3));
+        assert!(!ObjectType::MysteryMushroom.available_with_flags_in(FLAG_BIG, GameMode::Mario3));
+        assert_eq!(
+            ObjectType::MysteryMushroom.flags_unavailable_in(GameMode::Mario3),
+            FLAG_BIG
+        );
+        assert_eq!(
+            ObjectType::MysteryMushroom.flags_unavailable_in(GameMode::SuperMarioBros),
+            0
+        );
+        let mut big_goomba = Object::goomba(0, 0);
+        big_goomba.set_big(true);
+        assert!(big_goomba.available_in(GameMode::NewSuperMarioBrosU));
+        let game_modes = [
+            GameMode::SuperMarioBros,
+            GameMode::Mario3,
+            GameMode::MarioWorld,
+            GameMode::NewSuperMarioBrosU,
+        ];
+        let limited: Vec<ObjectType> = names::NAMES
+            .iter()
+            .map(|(object_type, _, _)| *object_type)
+            .filter(|object_type| {
+                !game_modes
+                    .iter()
+                    .all(|game_mode| object_type.available_in(*game_mode))
+            })
+            .collect();
+        assert_eq!(limited, vec![ObjectType::MysteryMushroom]);
+        for game_mode in game_modes {
+            assert_eq!(
+                ObjectType::MysteryMushroom.replacement_in(game_mode),
+                Some(if game_mode == GameMode::SuperMarioBros {
+                    ObjectType::MysteryMushroom
+                } else {
+                    ObjectType::SuperMushroom
+                })
+            );
+        }
+
+        assert!(ObjectType::Blooper.behaves_differently_in(CourseTheme::Water));
+        assert!(!ObjectType::Blooper.behaves_differently_in(CourseTheme::Castle));
+        assert!(!ObjectType::Goomba.behaves_differently_in(CourseTheme::Water));
+    }
 }
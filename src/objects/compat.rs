@@ -0,0 +1,33 @@
+// Which parts each game style offers and which change behaviour with the course theme. Nearly every
+// part exists in all four styles with a different look, the exceptions are listed here.
+
+use crate::level::{CourseTheme, GameMode};
+
+use super::{ObjectType, FLAG_BIG};
+
+// (type, the only styles offering it), for the identified ObjectType variants only. Of those the
+// Mystery Mushroom is the one part Mario Maker 1 limits to a style, parts like the Goomba's Shoe only
+// change their look (Yoshi's Egg in the later styles). Unidentified types aren't listed and so
+// count as offered everywhere
+pub(super) const STYLE_EXCLUSIVE: [(ObjectType, &[GameMode]); 1] =
+    [(ObjectType::MysteryMushroom, &[GameMode::SuperMarioBros])];
+
+// (type, flag, the only styles offering the type with the flag set), for flags that turn a part
+// into a different one. The Big Mushroom is a Mystery Mushroom with FLAG_BIG set
+pub(super) const FLAG_EXCLUSIVE: [(ObjectType, u32, &[GameMode]); 1] = [(
+    ObjectType::MysteryMushroom,
+    FLAG_BIG,
+    &[GameMode::SuperMarioBros],
+)];
+
+// (type, closest part offered everywhere), for converting levels out of the styles offering it.
+// Only parts in STYLE_EXCLUSIVE ever need replacing, so there's exactly one entry for each of them
+pub(super) const REPLACEMENTS: [(ObjectType, ObjectType); 1] =
+    [(ObjectType::MysteryMushroom, ObjectType::SuperMushroom)];
+
+// (type, themes it acts differently in). Cheep Cheeps swim instead of flopping around and Bloopers
+// swim instead of hovering
+pub(super) const THEME_DEPENDENT: [(ObjectType, &[CourseTheme]); 2] = [
+    (ObjectType::CheepCheep, &[CourseTheme::Water]),
+    (ObjectType::Blooper, &[CourseTheme::Water]),
+];
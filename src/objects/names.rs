@@ -0,0 +1,78 @@
+// Internal actor names used by the game's editor alongside English names, for every identified
+// object type.
+
+use super::ObjectType;
+
+// (type, internal name, display name)
+pub(super) const NAMES: [(ObjectType, &str, &str); 70] = [
+    (ObjectType::Goomba, "EditKuribo", "Goomba"),
+    (ObjectType::Koopa, "EditNokonoko", "Koopa Troopa"),
+    (ObjectType::PiranhaPlant, "EditPakkun", "Piranha Plant"),
+    (ObjectType::HammerBro, "EditHammerBros", "Hammer Bro"),
+    (ObjectType::Brick, "EditRengaBlock", "Brick Block"),
+    (ObjectType::QuestionBlock, "EditHatenaBlock", "? Block"),
+    (ObjectType::HardBlock, "EditHardBlock", "Hard Block"),
+    (ObjectType::Ground, "EditGround", "Ground"),
+    (ObjectType::Coin, "EditCoin", "Coin"),
+    (ObjectType::Pipe, "EditDokan", "Pipe"),
+    (ObjectType::Trampoline, "EditJumpStep", "Trampoline"),
+    (ObjectType::Lift, "EditLift", "Lift"),
+    (ObjectType::Thwomp, "EditDossun", "Thwomp"),
+    (ObjectType::BillBlaster, "EditKillerHoudai", "Bill Blaster"),
+    (ObjectType::MushroomPlatform, "EditGroundMushroom", "Mushroom Platform"),
+    (ObjectType::BobOmb, "EditBombhei", "Bob-omb"),
+    (ObjectType::SemisolidPlatform, "EditGroundBox", "Semisolid Platform"),
+    (ObjectType::Bridge, "EditBridge", "Bridge"),
+    (ObjectType::PSwitch, "EditPSwitch", "P Switch"),
+    (ObjectType::Pow, "EditPowBlock", "POW Block"),
+    (ObjectType::SuperMushroom, "EditKinoko", "Super Mushroom"),
+    (ObjectType::DonutBlock, "EditChikuwaBlock", "Donut Block"),
+    (ObjectType::Cloud, "EditKumoBlock", "Cloud Block"),
+    (ObjectType::NoteBlock, "EditOnpuBlock", "Note Block"),
+    (ObjectType::FireBar, "EditFireBar", "Fire Bar"),
+    (ObjectType::Spiny, "EditTogezo", "Spiny"),
+    (ObjectType::GoalGround, "EditGoalGround", "Goal Ground"),
+    (ObjectType::Goal, "EditGoalPole", "Goal Pole"),
+    (ObjectType::BuzzyBeetle, "EditMet", "Buzzy Beetle"),
+    (ObjectType::HiddenBlock, "EditClearBlock", "Hidden Block"),
+    (ObjectType::Lakitu, "EditJugem", "Lakitu"),
+    (ObjectType::LakituCloud, "EditJugemKumo", "Lakitu's Cloud"),
+    (ObjectType::BanzaiBill, "EditMagnumKiller", "Banzai Bill"),
+    (ObjectType::OneUp, "Edit1upKinoko", "1-Up Mushroom"),
+    (ObjectType::FireFlower, "EditFireFlower", "Fire Flower"),
+    (ObjectType::SuperStar, "EditSuperStar", "Super Star"),
+    (ObjectType::LavaLift, "EditYouganLift", "Lava Lift"),
+    (ObjectType::StartBlock, "EditStartBlock", "Start Ground"),
+    (ObjectType::StartArrow, "EditStartSignBoard", "Start Arrow"),
+    (ObjectType::Magikoopa, "EditKameck", "Magikoopa"),
+    (ObjectType::SpikeTop, "EditTogemet", "Spike Top"),
+    (ObjectType::Boo, "EditTeresa", "Boo"),
+    (ObjectType::ClownCar, "EditKoopaClown", "Koopa Clown Car"),
+    (ObjectType::Spikes, "EditTogeBlock", "Spike Trap"),
+    (ObjectType::MysteryMushroom, "EditKinokoFunny", "Mystery Mushroom"),
+    (ObjectType::ShoeGoomba, "EditKutsuKuribo", "Goomba's Shoe"),
+    (ObjectType::DryBones, "EditKaron", "Dry Bones"),
+    (ObjectType::Cannon, "EditHoudai", "Cannon"),
+    (ObjectType::Blooper, "EditGesso", "Blooper"),
+    (ObjectType::CastleBridge, "EditCastleBridge", "Castle Bridge"),
+    (ObjectType::HopChops, "EditCharaMechaKoopa", "Hop-Chops"),
+    (ObjectType::Skipsqueak, "EditPyonchu", "Skipsqueak"),
+    (ObjectType::Wiggler, "EditHanachan", "Wiggler"),
+    (ObjectType::ConveyorBelt, "EditBeltConveyor", "Conveyor Belt"),
+    (ObjectType::Burner, "EditBurner", "Burner"),
+    (ObjectType::Door, "EditDoor", "Door"),
+    (ObjectType::CheepCheep, "EditPukupuku", "Cheep Cheep"),
+    (ObjectType::Muncher, "EditBlackPakkun", "Muncher"),
+    (ObjectType::RockyWrench, "EditPoo", "Rocky Wrench"),
+    (ObjectType::Track, "EditRail", "Track"),
+    (ObjectType::LavaBubble, "EditBubble", "Lava Bubble"),
+    (ObjectType::ChainChomp, "EditWanwan", "Chain Chomp"),
+    (ObjectType::Bowser, "EditKoopa", "Bowser"),
+    (ObjectType::IceBlock, "EditIceBlock", "Ice Block"),
+    (ObjectType::Vine, "EditTsuta", "Vine"),
+    (ObjectType::ArrowSign, "EditSignBoard", "Arrow Sign"),
+    (ObjectType::OneWayWall, "EditOneWay", "One-Way Wall"),
+    (ObjectType::Grinder, "EditSaw", "Grinder"),
+    (ObjectType::BoomBoom, "EditBunbun", "Boom Boom"),
+    (ObjectType::CheckpointFlag, "EditMiddleFlag", "Checkpoint Flag"),
+];
@@ -0,0 +1,24 @@
+// Progress reporting for long running operations. Sinks are called before each item with the
+// number of items finished so far, and once more with done equal to total when the operation
+// completes. Returning false before an item cancels the operation with Error::Cancelled.
+
+use std::path::Path;
+
+pub trait ProgressSink {
+    fn progress(&mut self, done: usize, total: usize, current_path: Option<&Path>) -> bool;
+}
+
+// Sink for callers that don't need progress
+pub struct NoProgress;
+
+impl ProgressSink for NoProgress {
+    fn progress(&mut self, _done: usize, _total: usize, _current_path: Option<&Path>) -> bool {
+        true
+    }
+}
+
+impl<F: FnMut(usize, usize, Option<&Path>) -> bool> ProgressSink for F {
+    fn progress(&mut self, done: usize, total: usize, current_path: Option<&Path>) -> bool {
+        self(done, total, current_path)
+    }
+}
@@ -0,0 +1,270 @@
+use image::{imageops::FilterType, Rgba, RgbaImage};
+
+use crate::{
+    level::{CourseTheme, Level},
+    objects::{Object, ObjectType},
+    progress::{NoProgress, ProgressSink},
+    thumbnail::{PREVIEW_SIZE, THUMBNAIL_SIZE},
+    Error,
+};
+
+// Pixels per block, the same as on console
+pub const BLOCK_SIZE: u32 = 16;
+
+// Flat color preview of a level, one colored rectangle per object. Fails with Error::InvalidData
+// for a level wider than Level::MAX_BLOCK_WIDTH
+pub fn render_level(level: &Level) -> Result<RgbaImage, Error> {
+    render_level_with_progress(level, &mut NoProgress)
+}
+
+// Like render_level, reporting progress per object
+pub fn render_level_with_progress(
+    level: &Level,
+    progress: &mut impl ProgressSink,
+) -> Result<RgbaImage, Error> {
+    if level.block_width() > Level::MAX_BLOCK_WIDTH {
+        return Err(Error::InvalidData);
+    }
+    let mut image = RgbaImage::from_pixel(
+        level.block_width() * BLOCK_SIZE,
+        level.block_height() * BLOCK_SIZE,
+        theme_color(level.course_theme),
+    );
+
+    let total = level.objects.len();
+    for (done, object) in level.objects_sorted_by_layer().into_iter().enumerate() {
+        if !progress.progress(done, total, None) {
+            return Err(Error::Cancelled);
+        }
+        draw_object(&mut image, level, object);
+    }
+    progress.progress(total, total, None);
+
+    Ok(image)
+}
+
+// The whole level squeezed into the wide thumbnail0.tnl preview
+pub fn render_preview(level: &Level) -> Result<RgbaImage, Error> {
+    let image = render_level(level)?;
+    if image.width() == 0 {
+        return Ok(RgbaImage::from_pixel(
+            PREVIEW_SIZE.0,
+            PREVIEW_SIZE.1,
+            theme_color(level.course_theme),
+        ));
+    }
+    Ok(image::imageops::resize(
+        &image,
+        PREVIEW_SIZE.0,
+        PREVIEW_SIZE.1,
+        FilterType::Triangle,
+    ))
+}
+
+// The start of the level at full height, scaled to the thumbnail1.tnl course list thumbnail
+pub fn render_thumbnail(level: &Level) -> Result<RgbaImage, Error> {
+    let image = render_level(level)?;
+    let width = (image.height() * THUMBNAIL_SIZE.0 / THUMBNAIL_SIZE.1).min(image.width());
+    if width == 0 {
+        return Ok(RgbaImage::from_pixel(
+            THUMBNAIL_SIZE.0,
+            THUMBNAIL_SIZE.1,
+            theme_color(level.course_theme),
+        ));
+    }
+    let start = image::imageops::crop_imm(&image, 0, 0, width, image.height()).to_image();
+    Ok(image::imageops::resize(
+        &start,
+        THUMBNAIL_SIZE.0,
+        THUMBNAIL_SIZE.1,
+        FilterType::Triangle,
+    ))
+}
+
+pub fn theme_color(theme: CourseTheme) -> Rgba<u8> {
+    match theme {
+        CourseTheme::Overworld => Rgba([92, 148, 252, 255]),
+        CourseTheme::Underground => Rgba([0, 0, 0, 255]),
+        CourseTheme::Castle => Rgba([40, 40, 40, 255]),
+        CourseTheme::Airship => Rgba([60, 80, 130, 255]),
+        CourseTheme::Water => Rgba([32, 56, 236, 255]),
+        CourseTheme::GhostHouse => Rgba([20, 20, 60, 255]),
+    }
+}
+
+pub fn object_color(object_type: Option<ObjectType>) -> Rgba<u8> {
+    match object_type {
+        Some(ObjectType::Coin) => Rgba([252, 216, 0, 255]),
+        Some(ObjectType::Goal) | Some(ObjectType::CheckpointFlag) => Rgba([255, 255, 255, 255]),
+        Some(ObjectType::Pipe) => Rgba([0, 168, 0, 255]),
+        Some(ObjectType::Spikes) | Some(ObjectType::Muncher) | Some(ObjectType::FireBar) => {
+            Rgba([160, 160, 160, 255])
+        }
+        Some(object_type) if object_type.is_powerup() => Rgba([0, 220, 120, 255]),
+        Some(object_type) if object_type.is_enemy() => Rgba([228, 40, 40, 255]),
+        Some(object_type) if object_type.is_solid() => Rgba([136, 80, 24, 255]),
+        Some(object_type) if object_type.is_semisolid() => Rgba([220, 170, 100, 255]),
+        _ => Rgba([255, 0, 255, 255]),
+    }
+}
+
+// What a minimap pixel shows, later variants drawn over earlier ones
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum MinimapTile {
+    Empty,
+    Terrain,
+    Item,
+    Hazard,
+    Goal,
+}
+
+impl MinimapTile {
+    fn of(object_type: Option<ObjectType>) -> MinimapTile {
+        match object_type {
+            Some(ObjectType::Goal | ObjectType::GoalGround | ObjectType::CheckpointFlag) => {
+                MinimapTile::Goal
+            }
+            Some(object_type) if object_type.is_hazard() || object_type.is_enemy() => {
+                MinimapTile::Hazard
+            }
+            Some(object_type) if object_type.is_powerup() => MinimapTile::Item,
+            Some(ObjectType::Coin) => MinimapTile::Item,
+            Some(object_type) if object_type.is_solid() || object_type.is_semisolid() => {
+                MinimapTile::Terrain
+            }
+            _ => MinimapTile::Empty,
+        }
+    }
+
+    fn color(self, theme: CourseTheme) -> Rgba<u8> {
+        match self {
+            MinimapTile::Empty => theme_color(theme),
+            MinimapTile::Terrain => Rgba([136, 80, 24, 255]),
+            MinimapTile::Item => Rgba([252, 216, 0, 255]),
+            MinimapTile::Hazard => Rgba([228, 40, 40, 255]),
+            MinimapTile::Goal => Rgba([255, 255, 255, 255]),
+        }
+    }
+}
+
+// Color coded overview at most max_width pixels wide, for course lists. Narrow levels get several
+// pixels per block up to BLOCK_SIZE, wide ones several blocks per pixel with the most important one
+// shown. Fails with Error::InvalidData for a level wider than Level::MAX_BLOCK_WIDTH
+pub fn render_minimap(level: &Level, max_width: u32) -> Result<RgbaImage, Error> {
+    let block_width = level.block_width().max(1);
+    let block_height = level.block_height();
+    if block_width > Level::MAX_BLOCK_WIDTH {
+        return Err(Error::InvalidData);
+    }
+    let mut tiles = vec![MinimapTile::Empty; (block_width * block_height) as usize];
+    for object in &level.objects {
+        let tile = MinimapTile::of(object.get_object_type());
+        let (left, bottom, width, height) = object.get_block_bounds();
+        for x in left.max(0)..(left + width as i32).min(block_width as i32) {
+            for y in bottom.max(0)..(bottom + height as i32).min(block_height as i32) {
+                let index = (y as u32 * block_width + x as u32) as usize;
+                tiles[index] = tiles[index].max(tile);
+            }
+        }
+    }
+
+    let max_width = max_width.max(1);
+    let (pixels_per_block, blocks_per_pixel) = if block_width <= max_width {
+        ((max_width / block_width).min(BLOCK_SIZE), 1)
+    } else {
+        (1, block_width.div_ceil(max_width))
+    };
+    let width = (block_width * pixels_per_block / blocks_per_pixel).max(1);
+    let height = block_height
+        .checked_mul(pixels_per_block)
+        .ok_or(Error::InvalidData)?
+        .div_ceil(blocks_per_pixel);
+    debug_assert!(width <= max_width);
+
+    // Blocks covered by a pixel along either axis
+    let blocks = |pixel: u32, limit: u32| {
+        pixel * blocks_per_pixel / pixels_per_block
+            ..((pixel + 1) * blocks_per_pixel)
+                .div_ceil(pixels_per_block)
+                .min(limit)
+    };
+    Ok(RgbaImage::from_fn(width, height, |px, py| {
+        // Row 0 is the bottom of the course but the top of the image
+        let ys = blocks(height - 1 - py, block_height);
+        let tile = blocks(px, block_width)
+            .flat_map(|x| ys.clone().map(move |y| (x, y)))
+            .map(|(x, y)| tiles[(y * block_width + x) as usize])
+            .max()
+            .unwrap_or(MinimapTile::Empty);
+        tile.color(level.course_theme)
+    }))
+}
+
+fn draw_object(image: &mut RgbaImage, level: &Level, object: &Object) {
+    let (left, bottom, width, height) = object.get_block_bounds();
+    let color = object_color(object.get_object_type());
+
+    // Row 0 is the bottom of the course but the top of the image
+    let top = level.block_height() as i32 - (bottom + height as i32);
+    let x_range = (left * BLOCK_SIZE as i32).max(0)
+        ..((left + width as i32) * BLOCK_SIZE as i32).min(image.width() as i32);
+    let y_range = (top * BLOCK_SIZE as i32).max(0)
+        ..((top + height as i32) * BLOCK_SIZE as i32).min(image.height() as i32);
+
+    for x in x_range {
+        for y in y_range.clone() {
+            image.put_pixel(x as u32, y as u32, color);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixtures::minimal_level;
+    use crate::level::GameMode;
+
+    use super::*;
+
+    #[test]
+    fn test_render_level_width() {
+        let mut level = minimal_level(GameMode::Mario3, CourseTheme::Castle);
+        let image = render_level(&level).unwrap();
+        assert_eq!(
+            image.dimensions(),
+            (level.block_width() * BLOCK_SIZE, 27 * BLOCK_SIZE)
+        );
+
+        level.width = 0xFFFF_FFF0;
+        assert!(matches!(render_level(&level), Err(Error::InvalidData)));
+        assert!(matches!(render_preview(&level), Err(Error::InvalidData)));
+    }
+
+    #[test]
+    fn test_render_minimap() {
+        let mut level = minimal_level(GameMode::MarioWorld, CourseTheme::Overworld);
+        level.width = 100 * 16;
+        level.objects.push(Object::goomba(50, 1));
+
+        let minimap = render_minimap(&level, 50).unwrap();
+        assert_eq!(minimap.dimensions(), (50, 14));
+        assert_eq!(*minimap.get_pixel(25, 13), Rgba([228, 40, 40, 255]));
+        assert_eq!(
+            *minimap.get_pixel(0, 0),
+            theme_color(CourseTheme::Overworld)
+        );
+
+        let minimap = render_minimap(&level, 300).unwrap();
+        assert_eq!(minimap.dimensions(), (300, 81));
+        assert_eq!(*minimap.get_pixel(151, 76), Rgba([228, 40, 40, 255]));
+
+        // Never more than BLOCK_SIZE pixels a block, however wide the request
+        let minimap = render_minimap(&level, u32::MAX).unwrap();
+        assert_eq!(minimap.dimensions(), (100 * BLOCK_SIZE, 27 * BLOCK_SIZE));
+
+        level.width = u32::MAX;
+        assert!(matches!(
+            render_minimap(&level, 480),
+            Err(Error::InvalidData)
+        ));
+    }
+}
@@ -0,0 +1,348 @@
+// Fixing up what validation reports, for levels that came out of corrupted saves or generators.
+// Every change is logged so tools can show what was done to a course.
+
+use std::{borrow::Cow, collections::HashSet};
+
+use crate::{
+    checksum::{ChecksumKind, ChecksumPolicy},
+    level::Level,
+    objects::Object,
+    validation::{object_bounds_issues, ValidationIssue},
+    Error,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutOfBoundsPolicy {
+    // Moves the object to the nearest position inside the level
+    #[default]
+    Clamp,
+    Remove,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RepairPolicy {
+    pub out_of_bounds: OutOfBoundsPolicy,
+    // Unknown types may be parts that haven't been identified yet, so they're kept by default
+    pub remove_unknown_types: bool,
+}
+
+// Object indexes are from before the repair
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Repair {
+    // Width or height was negative or larger than any level, the old values are kept here
+    Resized {
+        object: usize,
+        width: i8,
+        height: i8,
+    },
+    // Positions in blocks
+    Moved {
+        object: usize,
+        from: (i32, i32),
+        to: (i32, i32),
+    },
+    RemovedOutOfBounds {
+        object: usize,
+        removed: Object,
+    },
+    RemovedUnknownType {
+        object: usize,
+        removed: Object,
+    },
+}
+
+// What else two objects of the same type at the same position have to share to count as duplicates
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DuplicateFields {
+    pub size: bool,
+    pub flags: bool,
+    pub child: bool,
+    // Link ID, sound effect and transformation IDs
+    pub links: bool,
+}
+
+// Only byte identical objects are duplicates
+impl Default for DuplicateFields {
+    fn default() -> Self {
+        DuplicateFields {
+            size: true,
+            flags: true,
+            child: true,
+            links: true,
+        }
+    }
+}
+
+// How far course data was from Level::SIZE, by its length
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SizeDeviation {
+    Truncated { length: usize },
+    Padded { length: usize },
+}
+
+// Pads truncated course data with zeros or cuts off trailing bytes. Padding changes what the
+// checksum covers so it's recomputed, trailing bytes were never covered
+pub fn fix_level_size(bytes: &[u8]) -> (Cow<'_, [u8]>, Option<SizeDeviation>) {
+    let length = bytes.len();
+    if length < Level::SIZE {
+        let mut padded = bytes.to_vec();
+        padded.resize(Level::SIZE, 0);
+        // Too short to hold a checksum, parsing fails on the header anyway
+        let _ = ChecksumKind::CourseData.patch(&mut padded);
+        (
+            Cow::Owned(padded),
+            Some(SizeDeviation::Truncated { length }),
+        )
+    } else if length > Level::SIZE {
+        (
+            Cow::Borrowed(&bytes[..Level::SIZE]),
+            Some(SizeDeviation::Padded { length }),
+        )
+    } else {
+        (Cow::Borrowed(bytes), None)
+    }
+}
+
+impl Level {
+    // Like from_bytes_with_policy, accepting data of the wrong size through fix_level_size
+    pub fn from_bytes_fixing_size(
+        bytes: &[u8],
+        policy: ChecksumPolicy,
+    ) -> Result<(Level, Option<SizeDeviation>), Error> {
+        let (bytes, deviation) = fix_level_size(bytes);
+        Ok((Level::from_bytes_with_policy(&bytes, policy)?, deviation))
+    }
+
+    // Removes objects duplicating an earlier one, returning how many were removed
+    pub fn dedupe_objects(&mut self, fields: DuplicateFields) -> usize {
+        let mut seen = HashSet::new();
+        let count = self.objects.len();
+        self.objects.retain(|object| {
+            seen.insert((
+                (
+                    object.object_type,
+                    object.x_position,
+                    object.y_position,
+                    object.z_position,
+                ),
+                fields.size.then_some((object.width, object.height)),
+                fields
+                    .flags
+                    .then_some((object.object_flags, object.extended_object_data)),
+                fields.child.then_some((
+                    object.child_object_type,
+                    object.child_object_flags,
+                    object.child_object_transformation_id,
+                )),
+                fields.links.then_some((
+                    object.link_id,
+                    object.effect_index,
+                    object.transformation_id,
+                )),
+            ))
+        });
+        count - self.objects.len()
+    }
+
+    // Sizes are fixed before bounds are checked, as they decide whether an object fits
+    pub fn repair(&mut self, policy: RepairPolicy) -> Vec<Repair> {
+        let mut repairs = Vec::new();
+
+        let max_width = Level::MAX_BLOCK_WIDTH as i64;
+        let max_height = self.block_height() as i64;
+        for (index, object) in self.objects.iter_mut().enumerate() {
+            let impossible = |size: i8, max: i64| size < 0 || size as i64 > max;
+            if impossible(object.width, max_width) || impossible(object.height, max_height) {
+                repairs.push(Repair::Resized {
+                    object: index,
+                    width: object.width,
+                    height: object.height,
+                });
+                if impossible(object.width, max_width) {
+                    object.width = 0;
+                }
+                if impossible(object.height, max_height) {
+                    object.height = 0;
+                }
+            }
+        }
+
+        let mut remove = vec![false; self.objects.len()];
+        for issue in object_bounds_issues(self) {
+            let ValidationIssue::ObjectOutOfBounds {
+                object,
+                x,
+                y,
+                suggested_x,
+                suggested_y,
+            } = issue
+            else {
+                continue;
+            };
+
+            match policy.out_of_bounds {
+                OutOfBoundsPolicy::Clamp => {
+                    let data = &mut self.objects[object];
+                    if suggested_x != x {
+                        data.x_position = suggested_x as u32 * 10;
+                    }
+                    if suggested_y != y {
+                        data.y_position = suggested_y as i16 * 10;
+                    }
+                    repairs.push(Repair::Moved {
+                        object,
+                        from: (x, y),
+                        to: (suggested_x, suggested_y),
+                    });
+                }
+                OutOfBoundsPolicy::Remove => {
+                    remove[object] = true;
+                    repairs.push(Repair::RemovedOutOfBounds {
+                        object,
+                        removed: self.objects[object].clone(),
+                    });
+                }
+            }
+        }
+
+        if policy.remove_unknown_types {
+            for (index, object) in self.objects.iter().enumerate() {
+                if !remove[index] && object.get_object_type().is_none() {
+                    remove[index] = true;
+                    repairs.push(Repair::RemovedUnknownType {
+                        object: index,
+                        removed: object.clone(),
+                    });
+                }
+            }
+        }
+
+        let mut index = 0;
+        self.objects.retain(|_| {
+            index += 1;
+            !remove[index - 1]
+        });
+
+        repairs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        fixtures::minimal_level,
+        level::{CourseTheme, GameMode},
+        validation::validate,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_repair() {
+        let mut level = minimal_level(GameMode::Mario3, CourseTheme::Overworld);
+        let objects = level.objects.len();
+        let mut tall = Object::coin(5, 3);
+        tall.height = 100;
+        level.objects.push(tall);
+        level.objects.push(Object::goomba(40, 2));
+        let mut unknown = Object::coin(6, 3);
+        unknown.object_type = 100;
+        level.objects.push(unknown.clone());
+
+        let mut clamped = level.clone();
+        assert_eq!(
+            clamped.repair(RepairPolicy::default()),
+            vec![
+                Repair::Resized {
+                    object: objects,
+                    width: 1,
+                    height: 100
+                },
+                Repair::Moved {
+                    object: objects + 1,
+                    from: (40, 2),
+                    to: (23, 2)
+                },
+            ]
+        );
+        assert!(validate(&clamped).is_empty());
+        assert_eq!(clamped.objects.len(), objects + 3);
+
+        let policy = RepairPolicy {
+            out_of_bounds: OutOfBoundsPolicy::Remove,
+            remove_unknown_types: true,
+        };
+        let repairs = level.repair(policy);
+        assert_eq!(
+            repairs[1..],
+            [
+                Repair::RemovedOutOfBounds {
+                    object: objects + 1,
+                    removed: Object::goomba(40, 2)
+                },
+                Repair::RemovedUnknownType {
+                    object: objects + 2,
+                    removed: unknown
+                },
+            ]
+        );
+        assert_eq!(level.objects.len(), objects + 1);
+    }
+
+    #[test]
+    fn test_from_bytes_fixing_size() {
+        let level = minimal_level(GameMode::NewSuperMarioBrosU, CourseTheme::Water);
+        let bytes = level.to_bytes().unwrap();
+        assert_eq!(
+            Level::from_bytes_fixing_size(&bytes, ChecksumPolicy::Require).unwrap(),
+            (level.clone(), None)
+        );
+
+        let mut padded = bytes.clone();
+        padded.extend_from_slice(&[0xAB; 0x20]);
+        assert!(Level::from_bytes(&padded).is_err());
+        assert_eq!(
+            Level::from_bytes_fixing_size(&padded, ChecksumPolicy::Require).unwrap(),
+            (
+                level.clone(),
+                Some(SizeDeviation::Padded {
+                    length: Level::SIZE + 0x20
+                })
+            )
+        );
+
+        // Only the unused padding at the end is lost
+        let truncated = &bytes[..Level::SIZE - 0x40];
+        assert_eq!(
+            Level::from_bytes_fixing_size(truncated, ChecksumPolicy::Require).unwrap(),
+            (
+                level,
+                Some(SizeDeviation::Truncated {
+                    length: Level::SIZE - 0x40
+                })
+            )
+        );
+    }
+
+    #[test]
+    fn test_dedupe_objects() {
+        let mut level = minimal_level(GameMode::MarioWorld, CourseTheme::Castle);
+        let original = level.clone();
+        level.objects.extend_from_slice(&original.objects[..3]);
+        let mut wide = Object::coin(2, 0);
+        wide.object_type = level.objects[2].object_type;
+        wide.width = 3;
+        level.objects.push(wide);
+
+        let mut exact = level.clone();
+        assert_eq!(exact.dedupe_objects(DuplicateFields::default()), 3);
+        assert_eq!(exact.objects[..original.objects.len()], original.objects);
+
+        let fields = DuplicateFields {
+            size: false,
+            ..Default::default()
+        };
+        assert_eq!(level.dedupe_objects(fields), 4);
+        assert_eq!(level, original);
+    }
+}
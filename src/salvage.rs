@@ -0,0 +1,277 @@
+// Recovering what's left of corrupted course data, e.g. from a failing SD card. Header fields that
+// can't be read fall back to defaults and broken object slots are skipped, each loss recorded so
+// tools can show what couldn't be rescued. Checksums are ignored.
+
+use std::ops::Range;
+
+use num_enum::TryFromPrimitive;
+use packed_struct::prelude::*;
+
+use crate::{
+    date::CreationDate,
+    level::{AutoScroll, CourseTheme, GameMode, Level},
+    migrate::CURRENT_VERSION,
+    objects::Object,
+    sound_effects::SoundEffect,
+};
+
+const OBJECTS_OFFSET: usize = 0xF0;
+const OBJECT_SIZE: usize = 0x20;
+const SOUND_EFFECTS_OFFSET: usize = 0x145F0;
+// Only the first four bytes of each record are used, the rest is padding
+const SOUND_EFFECT_SIZE: usize = 0x8;
+// In tenths of blocks, objects further than this above or below the level are taken as garbage
+const LEVEL_HEIGHT: i16 = 270;
+
+// Byte ranges refer to the salvaged data
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Unrecoverable {
+    // The field was missing or invalid and has its default value
+    Field {
+        name: &'static str,
+        range: Range<usize>,
+    },
+    // The slot was malformed and the object was dropped. When the data ends early, every slot from
+    // this one on is covered by the range
+    Object {
+        slot: usize,
+        range: Range<usize>,
+    },
+    // Missing sound effects are left empty
+    SoundEffects {
+        range: Range<usize>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Salvaged {
+    pub level: Level,
+    pub unrecoverable: Vec<Unrecoverable>,
+}
+
+impl Salvaged {
+    pub fn is_complete(&self) -> bool {
+        self.unrecoverable.is_empty()
+    }
+}
+
+struct Salvager<'a> {
+    bytes: &'a [u8],
+    unrecoverable: Vec<Unrecoverable>,
+}
+
+impl Salvager<'_> {
+    // Reads a field with parse, falling back to the default when it's missing or doesn't parse
+    fn field<T>(
+        &mut self,
+        name: &'static str,
+        range: Range<usize>,
+        default: T,
+        parse: impl FnOnce(&[u8]) -> Option<T>,
+    ) -> T {
+        match self.bytes.get(range.clone()).and_then(parse) {
+            Some(value) => value,
+            None => {
+                self.unrecoverable
+                    .push(Unrecoverable::Field { name, range });
+                default
+            }
+        }
+    }
+}
+
+fn be<const N: usize>(bytes: &[u8]) -> [u8; N] {
+    bytes.try_into().unwrap()
+}
+
+// Whether an object slot holds something the game could have written
+fn is_plausible(object: &Object) -> bool {
+    object.get_object_type().is_some()
+        && object.width >= 0
+        && object.height >= 0
+        && object.x_position <= Level::MAX_BLOCK_WIDTH * 10
+        && (-LEVEL_HEIGHT..=2 * LEVEL_HEIGHT).contains(&object.y_position)
+}
+
+impl Level {
+    pub fn salvage(bytes: &[u8]) -> Salvaged {
+        let mut salvager = Salvager {
+            bytes,
+            unrecoverable: Vec::new(),
+        };
+
+        let version = salvager.field("version", 0x0..0x8, CURRENT_VERSION, |bytes| {
+            Some(u64::from_be_bytes(be(bytes)))
+        });
+        let default_time = CreationDate::new(2015, 9, 10, 0, 0)
+            .and_then(CreationDate::to_creation_time)
+            .unwrap();
+        let creation_time = salvager.field("creation time", 0x10..0x16, default_time, |bytes| {
+            CreationDate {
+                year: u16::from_be_bytes(be(&bytes[..2])),
+                month: bytes[2],
+                day: bytes[3],
+                hour: bytes[4],
+                minute: bytes[5],
+            }
+            .to_creation_time()
+        });
+        let level_name = salvager.field("name", 0x28..0x6A, String::new(), |bytes| {
+            Some(
+                bytes
+                    .chunks_exact(2)
+                    .map(|unit| u16::from_be_bytes(be(unit)))
+                    .take_while(|&unit| unit != 0)
+                    .map(|unit| char::from_u32(unit.into()).unwrap_or(char::REPLACEMENT_CHARACTER))
+                    .collect(),
+            )
+        });
+        let game_mode = salvager.field("game mode", 0x6A..0x6C, GameMode::default(), |bytes| {
+            GameMode::unpack(&be(bytes)).ok()
+        });
+        let course_theme = salvager.field(
+            "course theme",
+            0x6D..0x6E,
+            CourseTheme::default(),
+            |bytes| CourseTheme::try_from_primitive(bytes[0]).ok(),
+        );
+        let time_limit = salvager.field("time limit", 0x70..0x72, 300, |bytes| {
+            Some(u16::from_be_bytes(be(bytes)))
+        });
+        let auto_scroll = salvager.field("auto scroll", 0x72..0x73, AutoScroll::None, |bytes| {
+            AutoScroll::try_from_primitive(bytes[0]).ok()
+        });
+        let flags = salvager.field("flags", 0x73..0x74, 0, |bytes| Some(bytes[0]));
+        let width = salvager.field("width", 0x74..0x78, Level::MAX_BLOCK_WIDTH * 16, |bytes| {
+            Some(u32::from_be_bytes(be(bytes)))
+                .filter(|width| *width <= Level::MAX_BLOCK_WIDTH * 16)
+        });
+        let mii_data = salvager.field("mii data", 0x78..0xD8, [0; 0x60], |bytes| Some(be(bytes)));
+        // A bad count means every slot is read until the first empty one
+        let object_count = salvager.field("object count", 0xEC..0xF0, None, |bytes| {
+            let count = u32::from_be_bytes(be(bytes)) as usize;
+            (count <= Level::MAX_OBJECTS).then_some(Some(count))
+        });
+
+        let mut objects = Vec::new();
+        let slots = object_count.unwrap_or(Level::MAX_OBJECTS);
+        for slot in 0..slots {
+            let start = OBJECTS_OFFSET + slot * OBJECT_SIZE;
+            let range = start..start + OBJECT_SIZE;
+            let Some(slot_bytes) = bytes.get(range.clone()) else {
+                // The file ends here, the remaining slots are reported together
+                if object_count.is_some() {
+                    salvager.unrecoverable.push(Unrecoverable::Object {
+                        slot,
+                        range: start..OBJECTS_OFFSET + slots * OBJECT_SIZE,
+                    });
+                }
+                break;
+            };
+            if object_count.is_none() && slot_bytes.iter().all(|byte| *byte == 0) {
+                break;
+            }
+            match Object::unpack(&be(slot_bytes)) {
+                Ok(object) if is_plausible(&object) => objects.push(object),
+                _ => salvager
+                    .unrecoverable
+                    .push(Unrecoverable::Object { slot, range }),
+            }
+        }
+
+        let mut sound_effects = Vec::with_capacity(Level::SOUND_EFFECTS);
+        for slot in 0..Level::SOUND_EFFECTS {
+            let start = SOUND_EFFECTS_OFFSET + slot * SOUND_EFFECT_SIZE;
+            match bytes.get(start..start + SOUND_EFFECT_SIZE) {
+                Some(effect) => {
+                    sound_effects.push(SoundEffect::from_raw(u32::from_be_bytes(be(&effect[..4]))))
+                }
+                None => {
+                    salvager.unrecoverable.push(Unrecoverable::SoundEffects {
+                        range: start
+                            ..SOUND_EFFECTS_OFFSET + Level::SOUND_EFFECTS * SOUND_EFFECT_SIZE,
+                    });
+                    sound_effects.resize(Level::SOUND_EFFECTS, SoundEffect::default());
+                    break;
+                }
+            }
+        }
+
+        Salvaged {
+            level: Level::new(
+                version,
+                creation_time,
+                level_name,
+                game_mode,
+                course_theme,
+                time_limit,
+                auto_scroll,
+                flags,
+                width,
+                mii_data,
+                objects,
+                sound_effects,
+            ),
+            unrecoverable: salvager.unrecoverable,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixtures::minimal_level;
+
+    use super::*;
+
+    #[test]
+    fn test_salvage_sound_effects() {
+        let mut level = minimal_level(GameMode::Mario3, CourseTheme::Overworld);
+        level.sound_effects[0] = SoundEffect::from_raw(0x0102_0304);
+        level.sound_effects[1] = SoundEffect::from_raw(0x0500_0607);
+        let salvaged = Level::salvage(&level.to_bytes().unwrap());
+        assert!(salvaged.is_complete());
+        assert_eq!(salvaged.level.sound_effects, level.sound_effects);
+    }
+
+    #[test]
+    fn test_salvage() {
+        let level = minimal_level(GameMode::MarioWorld, CourseTheme::Castle);
+        let bytes = level.to_bytes().unwrap();
+        let salvaged = Level::salvage(&bytes);
+        assert!(salvaged.is_complete());
+        assert_eq!(salvaged.level, level);
+
+        let mut corrupted = bytes.clone();
+        corrupted[0x6A] = b'X';
+        // The second object's type
+        corrupted[OBJECTS_OFFSET + OBJECT_SIZE + 0x18] = 100;
+        let salvaged = Level::salvage(&corrupted);
+        assert_eq!(
+            salvaged.unrecoverable,
+            vec![
+                Unrecoverable::Field {
+                    name: "game mode",
+                    range: 0x6A..0x6C
+                },
+                Unrecoverable::Object {
+                    slot: 1,
+                    range: 0x110..0x130
+                },
+            ]
+        );
+        assert_eq!(salvaged.level.game_mode, GameMode::default());
+        assert_eq!(salvaged.level.objects.len(), level.objects.len() - 1);
+
+        let truncated = Level::salvage(&bytes[..OBJECTS_OFFSET + 2 * OBJECT_SIZE]);
+        assert_eq!(truncated.level.objects, level.objects[..2]);
+        assert_eq!(truncated.level.level_name, level.level_name);
+        assert_eq!(truncated.level.sound_effects.len(), Level::SOUND_EFFECTS);
+        assert!(matches!(
+            truncated.unrecoverable[..],
+            [
+                Unrecoverable::Object { slot: 2, .. },
+                Unrecoverable::SoundEffects { .. }
+            ]
+        ));
+    }
+}
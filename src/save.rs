@@ -0,0 +1,595 @@
+// The save.dat kept next to the course directories on the Wii U. It records which of the course
+// slots are in use and where each sits in the in-game list, so directories left behind by deleted
+// courses aren't mistaken for live ones.
+
+use std::{
+    cmp::Ordering,
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    analysis::difficulty_score,
+    checksum::ChecksumKind,
+    course::{Course, CourseData},
+    date::CreationDate,
+    level::{CourseTheme, GameMode, Level},
+    thumbnail::phash_distance,
+    Error, FileKind,
+};
+
+pub const SAVE_SIZE: usize = 0xA000;
+pub const SLOTS: usize = 120;
+
+// One byte per slot holding its position in the course list
+const ORDER_OFFSET: usize = 0x4340;
+const EMPTY_SLOT: u8 = 0xFF;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SaveData {
+    // Kept whole so the parts that aren't understood yet survive a round trip
+    data: Vec<u8>,
+}
+
+impl SaveData {
+    pub fn from_bytes(bytes: &[u8]) -> Result<SaveData, Error> {
+        if bytes.len() != SAVE_SIZE {
+            return Err(Error::WrongSize {
+                expected: SAVE_SIZE,
+                actual: bytes.len(),
+                kind: FileKind::SaveData,
+            });
+        }
+
+        Ok(SaveData {
+            data: bytes.to_vec(),
+        })
+    }
+
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<SaveData, Error> {
+        SaveData::from_bytes(&std::fs::read(path).map_err(Error::Io)?)
+    }
+
+    // Like course data, the checksum at 0x8 covers everything from 0x10 onwards
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = self.data.clone();
+        ChecksumKind::CourseData
+            .patch(&mut bytes)
+            .expect("saves are larger than the checksum header");
+        bytes
+    }
+
+    pub fn checksum_valid(&self) -> bool {
+        ChecksumKind::CourseData.verify(&self.data).is_valid()
+    }
+
+    // The slot's position in the course list, None when the slot is empty
+    pub fn slot_order(&self, slot: usize) -> Option<u8> {
+        self.data
+            .get(ORDER_OFFSET..ORDER_OFFSET + SLOTS)?
+            .get(slot)
+            .copied()
+            .filter(|&order| order != EMPTY_SLOT)
+    }
+
+    pub fn set_slot_order(&mut self, slot: usize, order: Option<u8>) -> Result<(), Error> {
+        if slot >= SLOTS || order == Some(EMPTY_SLOT) {
+            return Err(Error::InvalidData);
+        }
+
+        self.data[ORDER_OFFSET + slot] = order.unwrap_or(EMPTY_SLOT);
+        Ok(())
+    }
+
+    // Lists the slot after every other course, keeping its position if it's already listed
+    pub fn register_slot(&mut self, slot: usize) -> Result<u8, Error> {
+        if let Some(order) = self.slot_order(slot) {
+            return Ok(order);
+        }
+
+        let order = (0..SLOTS)
+            .filter_map(|slot| self.slot_order(slot))
+            .max()
+            .map_or(0, |order| order + 1);
+        self.set_slot_order(slot, Some(order))?;
+        Ok(order)
+    }
+
+    pub fn first_free_slot(&self) -> Option<usize> {
+        (0..SLOTS).find(|&slot| self.slot_order(slot).is_none())
+    }
+
+    // Occupied slots in the order the game lists them
+    pub fn active_slots(&self) -> Vec<usize> {
+        let mut slots: Vec<usize> = (0..SLOTS)
+            .filter(|&slot| self.slot_order(slot).is_some())
+            .collect();
+        slots.sort_by_key(|&slot| self.slot_order(slot));
+        slots
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SaveSlot {
+    pub slot: usize,
+    pub order: Option<u8>,
+    pub path: PathBuf,
+    pub has_directory: bool,
+}
+
+impl SaveSlot {
+    pub fn is_active(&self) -> bool {
+        self.order.is_some()
+    }
+}
+
+// What search needs from a listed course, read from its main area
+#[derive(Debug, Clone, PartialEq)]
+pub struct CourseHeader {
+    pub slot: usize,
+    pub name: String,
+    pub game_mode: GameMode,
+    pub course_theme: CourseTheme,
+    pub block_width: u32,
+    pub object_count: usize,
+    pub creation_date: CreationDate,
+    pub difficulty: f32,
+}
+
+impl CourseHeader {
+    fn from_level(slot: usize, level: &Level) -> CourseHeader {
+        CourseHeader {
+            slot,
+            name: level.level_name.clone(),
+            game_mode: level.game_mode,
+            course_theme: level.course_theme,
+            block_width: level.block_width(),
+            object_count: level.objects.len(),
+            creation_date: CreationDate::from_creation_time(&level.creation_time),
+            difficulty: difficulty_score(level),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortKey {
+    // Case insensitive
+    Name,
+    CreationDate,
+    ObjectCount,
+    Difficulty,
+}
+
+impl SortKey {
+    // Ties are broken by slot so the order is stable across refreshes
+    pub fn compare(&self, a: &CourseHeader, b: &CourseHeader) -> Ordering {
+        match self {
+            SortKey::Name => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+            SortKey::CreationDate => a.creation_date.cmp(&b.creation_date),
+            SortKey::ObjectCount => a.object_count.cmp(&b.object_count),
+            SortKey::Difficulty => a.difficulty.total_cmp(&b.difficulty),
+        }
+        .then(a.slot.cmp(&b.slot))
+    }
+}
+
+// Sorted headers split into pages of a fixed size, the last page may be shorter
+#[derive(Debug, Clone)]
+pub struct Pages<'a> {
+    headers: Vec<&'a CourseHeader>,
+    page_size: usize,
+    next: usize,
+}
+
+impl<'a> Pages<'a> {
+    pub fn page_count(&self) -> usize {
+        self.headers.len().div_ceil(self.page_size)
+    }
+
+    pub fn page(&self, page: usize) -> Option<&[&'a CourseHeader]> {
+        self.headers.chunks(self.page_size).nth(page)
+    }
+
+    pub fn total(&self) -> usize {
+        self.headers.len()
+    }
+}
+
+impl<'a> Iterator for Pages<'a> {
+    type Item = Vec<&'a CourseHeader>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let page = self.page(self.next)?.to_vec();
+        self.next += 1;
+        Some(page)
+    }
+}
+
+// Every filter that's set has to match, the default matches everything
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Query {
+    // Case insensitive substring of the name
+    pub name: Option<String>,
+    pub game_mode: Option<GameMode>,
+    pub course_theme: Option<CourseTheme>,
+    pub block_width: Option<RangeInclusive<u32>>,
+    pub object_count: Option<RangeInclusive<usize>>,
+    pub creation_date: Option<RangeInclusive<CreationDate>>,
+}
+
+impl Query {
+    pub fn matches(&self, header: &CourseHeader) -> bool {
+        self.name
+            .as_ref()
+            .is_none_or(|name| header.name.to_lowercase().contains(&name.to_lowercase()))
+            && self
+                .game_mode
+                .is_none_or(|game_mode| header.game_mode == game_mode)
+            && self
+                .course_theme
+                .is_none_or(|course_theme| header.course_theme == course_theme)
+            && self
+                .block_width
+                .as_ref()
+                .is_none_or(|range| range.contains(&header.block_width))
+            && self
+                .object_count
+                .as_ref()
+                .is_none_or(|range| range.contains(&header.object_count))
+            && self
+                .creation_date
+                .as_ref()
+                .is_none_or(|range| range.contains(&header.creation_date))
+    }
+}
+
+// A save directory as found on the Wii U, save.dat alongside course000 to course119
+#[derive(Debug, Clone)]
+pub struct SaveLibrary {
+    pub path: PathBuf,
+    pub save_data: SaveData,
+    // Headers of the listed courses that could be read, by slot
+    headers: Vec<CourseHeader>,
+}
+
+impl SaveLibrary {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<SaveLibrary, Error> {
+        let mut library = SaveLibrary {
+            path: path.as_ref().to_path_buf(),
+            save_data: SaveData::from_file(path.as_ref().join("save.dat"))?,
+            headers: Vec::new(),
+        };
+        library.refresh_headers();
+        Ok(library)
+    }
+
+    // Rereads the header cache, needed after changing the course directories behind its back
+    pub fn refresh_headers(&mut self) {
+        self.headers = self
+            .save_data
+            .active_slots()
+            .into_iter()
+            .filter_map(|slot| self.read_header(slot))
+            .collect();
+        self.headers.sort_by_key(|header| header.slot);
+    }
+
+    fn read_header(&self, slot: usize) -> Option<CourseHeader> {
+        let path = self
+            .slot_path(slot)
+            .join(CourseData::CourseData.file_name());
+        let level = Level::from_bytes(&std::fs::read(path).ok()?).ok()?;
+        Some(CourseHeader::from_level(slot, &level))
+    }
+
+    pub fn headers(&self) -> &[CourseHeader] {
+        &self.headers
+    }
+
+    // Filters the cached headers, without touching the disk
+    pub fn search(&self, query: &Query) -> Vec<&CourseHeader> {
+        self.headers
+            .iter()
+            .filter(|header| query.matches(header))
+            .collect()
+    }
+
+    pub fn slot_path(&self, slot: usize) -> PathBuf {
+        self.path.join(format!("course{slot:03}"))
+    }
+
+    pub fn slots(&self) -> Vec<SaveSlot> {
+        (0..SLOTS)
+            .map(|slot| {
+                let path = self.slot_path(slot);
+                SaveSlot {
+                    slot,
+                    order: self.save_data.slot_order(slot),
+                    has_directory: path.is_dir(),
+                    path,
+                }
+            })
+            .collect()
+    }
+
+    // Course directories that exist but that the save doesn't list, e.g. left over after deleting
+    pub fn stale_slots(&self) -> Vec<usize> {
+        self.slots()
+            .into_iter()
+            .filter(|slot| slot.has_directory && !slot.is_active())
+            .map(|slot| slot.slot)
+            .collect()
+    }
+
+    // Fails with InvalidData when the save doesn't list the slot
+    pub fn load(&self, slot: usize) -> Result<Course, Error> {
+        if self.save_data.slot_order(slot).is_none() {
+            return Err(Error::InvalidData);
+        }
+
+        Course::from_dir(self.slot_path(slot))
+    }
+
+    pub fn sorted(&self, query: &Query, key: SortKey, descending: bool) -> Vec<&CourseHeader> {
+        let mut headers = self.search(query);
+        headers.sort_by(|a, b| key.compare(a, b));
+        if descending {
+            headers.reverse();
+        }
+        headers
+    }
+
+    // Page sizes of zero are treated as one
+    pub fn pages(
+        &self,
+        query: &Query,
+        key: SortKey,
+        descending: bool,
+        page_size: usize,
+    ) -> Pages<'_> {
+        Pages {
+            headers: self.sorted(query, key, descending),
+            page_size: page_size.max(1),
+            next: 0,
+        }
+    }
+
+    // Writes save.dat back with a fixed up checksum
+    pub fn save(&self) -> Result<(), Error> {
+        std::fs::write(self.path.join("save.dat"), self.save_data.to_bytes()).map_err(Error::Io)
+    }
+
+    // Writes the course into the slot, or the first free one, and registers it in save.dat so the
+    // game lists it. Returns the slot used.
+    pub fn inject(&mut self, course: &Course, slot: Option<usize>) -> Result<usize, Error> {
+        let slot = match slot {
+            Some(slot) if slot < SLOTS => slot,
+            Some(_) => return Err(Error::InvalidData),
+            None => self
+                .save_data
+                .first_free_slot()
+                .ok_or(Error::FileTooLarge)?,
+        };
+
+        course.to_dir(self.slot_path(slot))?;
+        self.save_data.register_slot(slot)?;
+        self.save()?;
+
+        let header = CourseHeader::from_level(slot, &course.level);
+        match self
+            .headers
+            .binary_search_by_key(&slot, |header| header.slot)
+        {
+            Ok(index) => self.headers[index] = header,
+            Err(index) => self.headers.insert(index, header),
+        }
+        Ok(slot)
+    }
+
+    // Groups course directories holding the same course, listed or not. With a threshold, courses
+    // whose previews are within that many bits of each other count as the same too. Directories
+    // that can't be read are skipped.
+    pub fn find_duplicates(&self, phash_threshold: Option<u32>) -> Vec<Vec<PathBuf>> {
+        let courses: Vec<(PathBuf, u64, Option<u64>)> = self
+            .slots()
+            .into_iter()
+            .filter(|slot| slot.has_directory)
+            .filter_map(|slot| {
+                let course = Course::from_dir(&slot.path).ok()?;
+                let phash = phash_threshold.and_then(|_| course.level_preview.phash().ok());
+                Some((slot.path, course.content_hash().ok()?, phash))
+            })
+            .collect();
+
+        // Merges by relabelling, there are at most SLOTS courses
+        let mut groups: Vec<usize> = (0..courses.len()).collect();
+        for i in 0..courses.len() {
+            for j in i + 1..courses.len() {
+                let similar = match (phash_threshold, courses[i].2, courses[j].2) {
+                    (Some(threshold), Some(a), Some(b)) => phash_distance(a, b) <= threshold,
+                    _ => false,
+                };
+                if (courses[i].1 == courses[j].1 || similar) && groups[i] != groups[j] {
+                    let (from, to) = (groups[j], groups[i]);
+                    groups
+                        .iter_mut()
+                        .filter(|group| **group == from)
+                        .for_each(|group| *group = to);
+                }
+            }
+        }
+
+        let mut duplicates: Vec<Vec<PathBuf>> = Vec::new();
+        for group in 0..courses.len() {
+            let paths: Vec<PathBuf> = (0..courses.len())
+                .filter(|&i| groups[i] == group)
+                .map(|i| courses[i].0.clone())
+                .collect();
+            if paths.len() > 1 {
+                duplicates.push(paths);
+            }
+        }
+        duplicates
+    }
+
+    // Active courses in list order
+    pub fn courses(&self) -> Result<Vec<(usize, Course)>, Error> {
+        self.save_data
+            .active_slots()
+            .into_iter()
+            .map(|slot| Ok((slot, self.load(slot)?)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        fixtures::minimal_course,
+        level::{CourseTheme, GameMode},
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_save_library() {
+        let directory = std::env::temp_dir().join(format!("mm1-save-{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+
+        let mut save_data = SaveData::from_bytes(&[0; SAVE_SIZE]).unwrap();
+        for slot in 0..SLOTS {
+            save_data.set_slot_order(slot, None).unwrap();
+        }
+        save_data.set_slot_order(3, Some(1)).unwrap();
+        save_data.set_slot_order(7, Some(0)).unwrap();
+        let bytes = save_data.to_bytes();
+        assert!(SaveData::from_bytes(&bytes).unwrap().checksum_valid());
+        std::fs::write(directory.join("save.dat"), bytes).unwrap();
+
+        let course = minimal_course(GameMode::MarioWorld, CourseTheme::GhostHouse);
+        for slot in [2, 3, 7] {
+            course
+                .to_dir(directory.join(format!("course{slot:03}")))
+                .unwrap();
+        }
+
+        let library = SaveLibrary::open(&directory).unwrap();
+        assert_eq!(library.save_data.active_slots(), vec![7, 3]);
+        assert_eq!(library.stale_slots(), vec![2]);
+        assert!(library.load(2).is_err());
+        assert_eq!(
+            library.courses().unwrap(),
+            vec![(7, course.clone()), (3, course)]
+        );
+
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn test_find_duplicates() {
+        let directory = std::env::temp_dir().join(format!("mm1-duplicates-{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        std::fs::write(directory.join("save.dat"), [0xFF; SAVE_SIZE]).unwrap();
+
+        let mut library = SaveLibrary::open(&directory).unwrap();
+        let course = minimal_course(GameMode::Mario3, CourseTheme::Water);
+        let mut renamed = course.clone();
+        renamed.level.level_name = "Renamed".to_string();
+        for (slot, course) in [(0, &course), (1, &renamed), (4, &course)] {
+            library.inject(course, Some(slot)).unwrap();
+        }
+
+        let duplicates = library.find_duplicates(None);
+        assert_eq!(
+            duplicates,
+            vec![vec![library.slot_path(0), library.slot_path(4)]]
+        );
+        // Same preview, so near identical
+        assert_eq!(library.find_duplicates(Some(0))[0].len(), 3);
+
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn test_inject() {
+        let directory = std::env::temp_dir().join(format!("mm1-inject-{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        let mut save_data = SaveData::from_bytes(&[0xFF; SAVE_SIZE]).unwrap();
+        save_data.set_slot_order(0, Some(0)).unwrap();
+        std::fs::write(directory.join("save.dat"), save_data.to_bytes()).unwrap();
+
+        let course = minimal_course(GameMode::Mario3, CourseTheme::Water);
+        let mut library = SaveLibrary::open(&directory).unwrap();
+        assert_eq!(library.inject(&course, None).unwrap(), 1);
+        assert_eq!(library.inject(&course, Some(5)).unwrap(), 5);
+
+        let library = SaveLibrary::open(&directory).unwrap();
+        assert!(library.save_data.checksum_valid());
+        assert_eq!(library.save_data.active_slots(), vec![0, 1, 5]);
+        assert_eq!(library.save_data.slot_order(5), Some(2));
+        assert_eq!(library.load(5).unwrap(), course);
+
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn test_search() {
+        let directory = std::env::temp_dir().join(format!("mm1-search-{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        std::fs::write(directory.join("save.dat"), [0xFF; SAVE_SIZE]).unwrap();
+
+        let mut library = SaveLibrary::open(&directory).unwrap();
+        let mut course = minimal_course(GameMode::Mario3, CourseTheme::Water);
+        course.level.level_name = "Deep Dive".to_string();
+        library.inject(&course, None).unwrap();
+        let course = minimal_course(GameMode::MarioWorld, CourseTheme::Water);
+        library.inject(&course, None).unwrap();
+
+        let slots = |query: &Query| -> Vec<usize> {
+            library
+                .search(query)
+                .iter()
+                .map(|header| header.slot)
+                .collect()
+        };
+        assert_eq!(slots(&Query::default()), vec![0, 1]);
+        let query = Query {
+            name: Some("dive".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(slots(&query), vec![0]);
+        let query = Query {
+            game_mode: Some(GameMode::MarioWorld),
+            course_theme: Some(CourseTheme::Water),
+            object_count: Some(0..=100),
+            ..Default::default()
+        };
+        assert_eq!(slots(&query), vec![1]);
+        let query = Query {
+            creation_date: Some(CreationDate::default()..=CreationDate::default()),
+            ..Default::default()
+        };
+        assert!(slots(&query).is_empty());
+
+        let names = |headers: Vec<&CourseHeader>| -> Vec<String> {
+            headers.iter().map(|header| header.name.clone()).collect()
+        };
+        assert_eq!(
+            names(library.sorted(&Query::default(), SortKey::Name, true)),
+            vec!["Fixture", "Deep Dive"]
+        );
+        let mut pages = library.pages(&Query::default(), SortKey::Name, false, 1);
+        assert_eq!(pages.page_count(), 2);
+        assert_eq!(pages.page(1).unwrap()[0].slot, 1);
+        assert_eq!(names(pages.next().unwrap()), vec!["Deep Dive"]);
+        assert_eq!(pages.count(), 1);
+
+        // A reopened library reads the same headers from disk
+        assert_eq!(
+            SaveLibrary::open(&directory).unwrap().headers(),
+            library.headers()
+        );
+
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+}
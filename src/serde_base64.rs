@@ -0,0 +1,38 @@
+// Serializes byte arrays as base64 strings rather than arrays of numbers, used through
+// #[serde(with = "crate::serde_base64")].
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+pub fn serialize<S: Serializer, T: AsRef<[u8]>>(
+    bytes: &T,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&STANDARD.encode(bytes))
+}
+
+pub fn deserialize<'de, D: Deserializer<'de>, T: TryFrom<Vec<u8>>>(
+    deserializer: D,
+) -> Result<T, D::Error> {
+    let string = String::deserialize(deserializer)?;
+    let bytes = STANDARD.decode(string).map_err(D::Error::custom)?;
+    let length = bytes.len();
+    T::try_from(bytes).map_err(|_| D::Error::invalid_length(length, &"the field's byte length"))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        course::Course,
+        fixtures::minimal_course,
+        level::{CourseTheme, GameMode},
+    };
+
+    #[test]
+    fn test_course_json_round_trip() {
+        let course = minimal_course(GameMode::Mario3, CourseTheme::Airship);
+        let json = serde_json::to_string(&course).unwrap();
+        assert!(json.contains("\"mii_data\":\"AAAA"));
+        assert_eq!(serde_json::from_str::<Course>(&json).unwrap(), course);
+    }
+}
@@ -0,0 +1,225 @@
+// A small HTTP service for web course viewers. Every endpoint takes a tar of the four course files
+// as the request body:
+//   POST /course             the course in its JSON form
+//   POST /course/minimap     a PNG minimap, ?width= caps its width (at most MAX_MINIMAP_WIDTH) and
+//                            ?area=sub picks the sub area
+//   POST /course/validation  issues in either area and between the two, as JSON
+
+use std::{fmt::Debug, io::Cursor};
+
+use axum::{
+    body::Bytes,
+    extract::Query,
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    routing::post,
+    Json, Router,
+};
+use image::ImageFormat;
+
+use crate::{course::Course, level::Level, render::render_minimap, validation::validate, Error};
+
+// Wide enough for the longest levels at two pixels a block
+const DEFAULT_MINIMAP_WIDTH: u32 = 480;
+// Wider requests are refused rather than rendered
+pub const MAX_MINIMAP_WIDTH: u32 = 4096;
+
+#[derive(Debug)]
+pub enum ServerError {
+    Course(Error),
+    WidthTooLarge(u32),
+}
+
+impl From<Error> for ServerError {
+    fn from(error: Error) -> Self {
+        ServerError::Course(error)
+    }
+}
+
+// Bad uploads and queries are the client's fault, anything else is ours
+impl IntoResponse for ServerError {
+    fn into_response(self) -> Response {
+        let status = match self {
+            ServerError::Course(Error::Io(_)) => StatusCode::INTERNAL_SERVER_ERROR,
+            _ => StatusCode::BAD_REQUEST,
+        };
+        let message = match self {
+            ServerError::Course(error) => format!("{:?}", error),
+            ServerError::WidthTooLarge(width) => {
+                format!("width {} is over {}", width, MAX_MINIMAP_WIDTH)
+            }
+        };
+        (status, message).into_response()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Area {
+    #[default]
+    Main,
+    Sub,
+}
+
+#[derive(Debug, Clone, Default, serde::Deserialize)]
+pub struct MinimapQuery {
+    pub width: Option<u32>,
+    #[serde(default)]
+    pub area: Area,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct ValidationReport {
+    pub main: Vec<String>,
+    pub sub: Vec<String>,
+    pub consistency: Vec<String>,
+}
+
+// Levels wider than the game allows are refused before anything sizes a buffer by their width
+fn read_course(body: &[u8]) -> Result<Course, ServerError> {
+    let course = Course::from_tar(&mut tar::Archive::new(body))?;
+    for level in [&course.level, &course.sub_level] {
+        if level.block_width() > Level::MAX_BLOCK_WIDTH {
+            return Err(Error::InvalidData.into());
+        }
+    }
+    Ok(course)
+}
+
+fn describe<T: Debug>(issues: &[T]) -> Vec<String> {
+    issues.iter().map(|issue| format!("{:?}", issue)).collect()
+}
+
+async fn course(body: Bytes) -> Result<Json<Course>, ServerError> {
+    Ok(Json(read_course(&body)?))
+}
+
+async fn minimap(
+    Query(query): Query<MinimapQuery>,
+    body: Bytes,
+) -> Result<impl IntoResponse, ServerError> {
+    let width = query.width.unwrap_or(DEFAULT_MINIMAP_WIDTH);
+    if width > MAX_MINIMAP_WIDTH {
+        return Err(ServerError::WidthTooLarge(width));
+    }
+    let course = read_course(&body)?;
+    let level = match query.area {
+        Area::Main => &course.level,
+        Area::Sub => &course.sub_level,
+    };
+    let image = render_minimap(level, width)?;
+
+    let mut png = Cursor::new(Vec::new());
+    image
+        .write_to(&mut png, ImageFormat::Png)
+        .map_err(|_| Error::InvalidData)?;
+    Ok(([(header::CONTENT_TYPE, "image/png")], png.into_inner()))
+}
+
+async fn validation(body: Bytes) -> Result<Json<ValidationReport>, ServerError> {
+    let course = read_course(&body)?;
+    Ok(Json(ValidationReport {
+        main: describe(&validate(&course.level)),
+        sub: describe(&validate(&course.sub_level)),
+        consistency: describe(&course.check_consistency()),
+    }))
+}
+
+// The endpoints, for mounting into a larger service
+pub fn router() -> Router {
+    Router::new()
+        .route("/course", post(course))
+        .route("/course/minimap", post(minimap))
+        .route("/course/validation", post(validation))
+}
+
+pub async fn serve(listener: tokio::net::TcpListener) -> std::io::Result<()> {
+    axum::serve(listener, router()).await
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{body::Body, http::Request};
+    use tower::ServiceExt;
+
+    use crate::{
+        course::CourseData,
+        fixtures::minimal_course,
+        level::{CourseTheme, GameMode},
+    };
+
+    use super::*;
+
+    async fn post(uri: &str, body: Vec<u8>) -> (StatusCode, Vec<u8>) {
+        let request = Request::post(uri).body(Body::from(body)).unwrap();
+        let response = router().oneshot(request).await.unwrap();
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, body.to_vec())
+    }
+
+    #[tokio::test]
+    async fn test_router() {
+        let course = minimal_course(GameMode::Mario3, CourseTheme::Overworld);
+        let mut tar = Vec::new();
+        course.to_tar(&mut tar::Builder::new(&mut tar)).unwrap();
+
+        let (status, body) = post("/course", tar.clone()).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(serde_json::from_slice::<Course>(&body).unwrap(), course);
+
+        let (status, body) = post("/course/minimap?width=48&area=sub", tar.clone()).await;
+        assert_eq!(status, StatusCode::OK);
+        let image = image::load_from_memory_with_format(&body, ImageFormat::Png).unwrap();
+        assert!(image.width() <= 48);
+
+        let (status, body) = post("/course/validation", tar).await;
+        assert_eq!(status, StatusCode::OK);
+        let report: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(report["consistency"], serde_json::json!([]));
+
+        let (status, _) = post("/course", vec![0; 0x200]).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_router_rejects() {
+        let mut course = minimal_course(GameMode::MarioWorld, CourseTheme::Castle);
+        let mut tar = Vec::new();
+        course.to_tar(&mut tar::Builder::new(&mut tar)).unwrap();
+
+        let uri = format!("/course/minimap?width={}", u32::MAX);
+        let (status, _) = post(&uri, tar.clone()).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        // A thumbnail cut short is a bad upload, not a crash
+        let mut short = Vec::new();
+        let mut builder = tar::Builder::new(&mut short);
+        for data in CourseData::ALL {
+            let mut bytes = course.to_data_bytes(data).unwrap();
+            if data == CourseData::Thumbnail1 {
+                bytes.truncate(16);
+            }
+            let mut header = tar::Header::new_gnu();
+            header.set_size(bytes.len() as u64);
+            header.set_cksum();
+            builder
+                .append_data(&mut header, data.file_name(), bytes.as_slice())
+                .unwrap();
+        }
+        builder.finish().unwrap();
+        drop(builder);
+        for uri in ["/course", "/course/minimap", "/course/validation"] {
+            assert_eq!(post(uri, short.clone()).await.0, StatusCode::BAD_REQUEST);
+        }
+
+        course.sub_level.width = u32::MAX;
+        let mut wide = Vec::new();
+        course.to_tar(&mut tar::Builder::new(&mut wide)).unwrap();
+        for uri in ["/course", "/course/minimap", "/course/validation"] {
+            assert_eq!(post(uri, wide.clone()).await.0, StatusCode::BAD_REQUEST);
+        }
+    }
+}
@@ -0,0 +1,221 @@
+use std::io::Read;
+
+use crate::{course::Course, Error};
+
+pub const DEFAULT_BASE_URL: &str = "https://smmdb.net/api";
+
+// Largest download accepted, far above the ~250KB of an uncompressed course tar
+const MAX_DOWNLOAD_SIZE: u64 = 16 * 1024 * 1024;
+
+// What this client reads of SMMDB's description of an uploaded course, the other fields (maker,
+// style, theme, ...) are ignored
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+pub struct UploadedCourse {
+    pub id: String,
+    pub title: String,
+}
+
+// Blocking client for the SMMDB course archive
+#[derive(Debug, Clone)]
+pub struct SmmdbClient {
+    agent: ureq::Agent,
+    base_url: String,
+    api_key: Option<String>,
+}
+
+impl Default for SmmdbClient {
+    fn default() -> Self {
+        SmmdbClient::new(DEFAULT_BASE_URL)
+    }
+}
+
+impl SmmdbClient {
+    pub fn new(base_url: &str) -> SmmdbClient {
+        SmmdbClient {
+            agent: ureq::Agent::new_with_defaults(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            api_key: None,
+        }
+    }
+
+    // Uploading requires the API key from an SMMDB account
+    pub fn with_api_key(mut self, api_key: &str) -> SmmdbClient {
+        self.api_key = Some(api_key.to_string());
+        self
+    }
+
+    // Downloads a course in SMMDB's tar distribution format. Fails with Error::FileTooLarge for a
+    // response over MAX_DOWNLOAD_SIZE
+    pub fn download_tar(&self, id: &str) -> Result<Vec<u8>, Error> {
+        let mut response = self
+            .agent
+            .get(format!("{}/downloadcourse", self.base_url))
+            .query("id", id)
+            .query("type", "tar")
+            .call()
+            .map_err(to_error)?;
+
+        let mut bytes = Vec::new();
+        response
+            .body_mut()
+            .as_reader()
+            .take(MAX_DOWNLOAD_SIZE + 1)
+            .read_to_end(&mut bytes)
+            .map_err(Error::Io)?;
+        if bytes.len() as u64 > MAX_DOWNLOAD_SIZE {
+            return Err(Error::FileTooLarge);
+        }
+        Ok(bytes)
+    }
+
+    pub fn download(&self, id: &str) -> Result<Course, Error> {
+        let bytes = self.download_tar(id)?;
+        Course::from_tar(&mut tar::Archive::new(bytes.as_slice()))
+    }
+
+    // Uploads a course as a tar, returning the courses SMMDB created from it
+    pub fn upload(&self, course: &Course) -> Result<Vec<UploadedCourse>, Error> {
+        let api_key = self.api_key.as_ref().ok_or(Error::Http(
+            "uploading to SMMDB requires an API key".to_string(),
+        ))?;
+
+        let mut builder = tar::Builder::new(Vec::new());
+        course.to_tar(&mut builder)?;
+        let bytes = builder.into_inner().map_err(Error::Io)?;
+
+        let response = self
+            .agent
+            .post(format!("{}/uploadcourse", self.base_url))
+            .header("Authorization", format!("APIKEY {}", api_key))
+            .header("Content-Type", "application/x-tar")
+            .send(&bytes[..])
+            .map_err(to_error)?
+            .body_mut()
+            .read_to_string()
+            .map_err(to_error)?;
+        parse_upload_response(&response)
+    }
+}
+
+// SMMDB answers an upload with a JSON array of the courses it created
+fn parse_upload_response(response: &str) -> Result<Vec<UploadedCourse>, Error> {
+    serde_json::from_str(response)
+        .map_err(|error| Error::Http(format!("unexpected upload response: {}", error)))
+}
+
+fn to_error(error: ureq::Error) -> Error {
+    match error {
+        ureq::Error::Io(error) => Error::Io(error),
+        error => Error::Http(error.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{BufRead, BufReader, Write},
+        net::TcpListener,
+        thread::{self, JoinHandle},
+    };
+
+    use crate::{
+        fixtures::minimal_course,
+        level::{CourseTheme, GameMode},
+    };
+
+    use super::*;
+
+    const UPLOAD_RESPONSE: &str = include_str!("fixtures/smmdb_upload.json");
+
+    // Answers a single request with the body, returning the request line and body it was sent
+    fn serve_once(body: Vec<u8>) -> (String, JoinHandle<(String, Vec<u8>)>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let handle = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(&stream);
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+            let mut length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if line.trim().is_empty() {
+                    break;
+                }
+                if let Some((name, value)) = line.split_once(':') {
+                    if name.eq_ignore_ascii_case("content-length") {
+                        length = value.trim().parse().unwrap();
+                    }
+                }
+            }
+            let mut request_body = vec![0; length];
+            reader.read_exact(&mut request_body).unwrap();
+
+            let mut stream = &stream;
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                body.len()
+            )
+            .unwrap();
+            stream.write_all(&body).unwrap();
+            (request_line, request_body)
+        });
+        (base_url, handle)
+    }
+
+    #[test]
+    fn test_download_round_trip() {
+        let course = minimal_course(GameMode::Mario3, CourseTheme::Airship);
+        let mut tar = Vec::new();
+        course.to_tar(&mut tar::Builder::new(&mut tar)).unwrap();
+
+        let (base_url, server) = serve_once(tar);
+        assert_eq!(SmmdbClient::new(&base_url).download("abc").unwrap(), course);
+        let (request_line, _) = server.join().unwrap();
+        assert!(request_line.starts_with("GET /downloadcourse?id=abc&type=tar "));
+
+        let (base_url, server) = serve_once(vec![0; MAX_DOWNLOAD_SIZE as usize + 1]);
+        assert!(matches!(
+            SmmdbClient::new(&base_url).download_tar("abc"),
+            Err(Error::FileTooLarge)
+        ));
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn test_upload() {
+        let course = minimal_course(GameMode::MarioWorld, CourseTheme::Castle);
+        assert!(SmmdbClient::default().upload(&course).is_err());
+
+        let (base_url, server) = serve_once(UPLOAD_RESPONSE.as_bytes().to_vec());
+        let uploaded = SmmdbClient::new(&base_url)
+            .with_api_key("key")
+            .upload(&course)
+            .unwrap();
+        assert_eq!(
+            uploaded,
+            vec![UploadedCourse {
+                id: "5b0d8a3c2f1e4a0012345678".to_string(),
+                title: "Castle Run".to_string(),
+            }]
+        );
+
+        let (request_line, body) = server.join().unwrap();
+        assert!(request_line.starts_with("POST /uploadcourse "));
+        assert_eq!(
+            Course::from_tar(&mut tar::Archive::new(body.as_slice())).unwrap(),
+            course
+        );
+    }
+
+    #[test]
+    fn test_parse_upload_response() {
+        assert_eq!(parse_upload_response(UPLOAD_RESPONSE).unwrap().len(), 1);
+        assert!(matches!(
+            parse_upload_response("<html>Bad Gateway</html>"),
+            Err(Error::Http(_))
+        ));
+    }
+}
@@ -1,11 +1,127 @@
+use num_enum::{IntoPrimitive, TryFromPrimitive};
 use packed_struct::prelude::*;
 
-#[derive(Debug, PackedStruct, Clone, PartialEq, Eq)]
+use crate::Error;
+
+// Sound types the editor offers, effects with a higher type don't play
+pub const SOUND_TYPES: u8 = 48;
+
+// Variations per sound type, the rest only have the normal one. Shaking an effect in the editor
+// cycles through them
+const VARIATIONS: [(u8, u8); 8] = [
+    (0, 3),
+    (1, 2),
+    (2, 2),
+    (5, 3),
+    (9, 2),
+    (14, 2),
+    (21, 4),
+    (30, 2),
+];
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, IntoPrimitive, TryFromPrimitive, Default)]
+#[repr(u8)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Variation {
+    #[default]
+    Normal = 0,
+    Second = 1,
+    Third = 2,
+    Fourth = 3,
+}
+
+impl Variation {
+    // Whether the sound type has this variation
+    pub fn is_available_for(&self, sound_type: u8) -> bool {
+        u8::from(*self) < variation_count(sound_type)
+    }
+}
+
+// Number of variations of the sound type, 0 for types the game doesn't have
+pub fn variation_count(sound_type: u8) -> u8 {
+    if sound_type >= SOUND_TYPES {
+        return 0;
+    }
+    VARIATIONS
+        .iter()
+        .find(|(variation_type, _)| *variation_type == sound_type)
+        .map_or(1, |(_, count)| *count)
+}
+
+// 00 	u8 	Sound type
+// 01 	u8 	Variation
+// 02 	u8 	X position (in blocks)
+// 03 	u8 	Y position (in blocks)
+// 04 	padding 	4 unused bytes
+#[derive(Debug, PackedStruct, Clone, PartialEq, Eq, Default)]
 #[packed_struct(bit_numbering="msb0", endian="msb", size_bytes="8")]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SoundEffect {
-    // pub sound_type: u8,
-    // pub variation: u8,
-    // pub x_position: u8,
-    // pub y_position: u8,
-    pub unknown: u32,
-}
\ No newline at end of file
+    #[packed_field(bytes="0x00")]
+    pub sound_type: u8,
+    #[packed_field(bytes="0x01")]
+    pub variation: u8,
+    #[packed_field(bytes="0x02")]
+    pub x_position: u8,
+    #[packed_field(bytes="0x03")]
+    pub y_position: u8,
+}
+
+impl SoundEffect {
+    // The four used bytes as one big endian value, as older versions stored them
+    pub fn from_raw(raw: u32) -> SoundEffect {
+        let [sound_type, variation, x_position, y_position] = raw.to_be_bytes();
+        SoundEffect {
+            sound_type,
+            variation,
+            x_position,
+            y_position,
+        }
+    }
+
+    // None if the variation byte isn't one the sound type has
+    pub fn get_variation(&self) -> Option<Variation> {
+        Variation::try_from_primitive(self.variation)
+            .ok()
+            .filter(|variation| variation.is_available_for(self.sound_type))
+    }
+
+    pub fn set_variation(&mut self, variation: Variation) -> Result<(), Error> {
+        if !variation.is_available_for(self.sound_type) {
+            return Err(Error::InvalidData);
+        }
+        self.variation = variation.into();
+        Ok(())
+    }
+
+    // Unused slots in the table are zeroed
+    pub fn is_empty(&self) -> bool {
+        self.raw() == 0
+    }
+
+    pub fn raw(&self) -> u32 {
+        u32::from_be_bytes([self.sound_type, self.variation, self.x_position, self.y_position])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_variations() {
+        let mut effect = SoundEffect::from_raw(0x15000000);
+        assert_eq!(effect.get_variation(), Some(Variation::Normal));
+        effect.set_variation(Variation::Fourth).unwrap();
+        assert_eq!(effect.get_variation(), Some(Variation::Fourth));
+
+        effect.sound_type = 3;
+        assert_eq!(effect.get_variation(), None);
+        assert!(effect.set_variation(Variation::Second).is_err());
+        effect.set_variation(Variation::Normal).unwrap();
+        assert_eq!(effect.get_variation(), Some(Variation::Normal));
+
+        effect.sound_type = SOUND_TYPES;
+        assert!(effect.set_variation(Variation::Normal).is_err());
+    }
+}
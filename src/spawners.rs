@@ -0,0 +1,148 @@
+// What Lakitus throw and what bill blasters and cannons fire. Dropping a part onto one in the editor
+// makes it the projectile, stored as the child object along with whether it has wings or was
+// enlarged.
+
+use crate::{
+    level::Level,
+    objects::{Object, ObjectType, FLAG_BIG, FLAG_WINGS},
+    Error,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Projectile {
+    // Spinies for Lakitus, Bullet Bills for bill blasters and cannonballs for cannons
+    #[default]
+    Default,
+    Object(ObjectType),
+    // A child type that hasn't been identified
+    Unknown(i8),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct SpawnerSettings {
+    pub projectile: Projectile,
+    // Only kept for a projectile other than the default
+    pub wings: bool,
+    pub big: bool,
+}
+
+impl ObjectType {
+    pub fn is_spawner(&self) -> bool {
+        matches!(
+            self,
+            ObjectType::Lakitu | ObjectType::BillBlaster | ObjectType::Cannon
+        )
+    }
+}
+
+impl Object {
+    // None if the object doesn't throw or fire anything
+    pub fn spawner_settings(&self) -> Option<SpawnerSettings> {
+        if !self.get_object_type()?.is_spawner() {
+            return None;
+        }
+        let projectile = match self.child_object_type {
+            -1 => Projectile::Default,
+            id => ObjectType::try_from(id).map_or(Projectile::Unknown(id), Projectile::Object),
+        };
+        Some(SpawnerSettings {
+            projectile,
+            wings: self.child_object_flags & FLAG_WINGS != 0,
+            big: self.child_object_flags & FLAG_BIG != 0,
+        })
+    }
+
+    // Fails if the object doesn't throw or fire anything. Other child flags are left alone
+    pub fn set_spawner_settings(&mut self, settings: SpawnerSettings) -> Result<(), Error> {
+        if !self
+            .get_object_type()
+            .is_some_and(|object_type| object_type.is_spawner())
+        {
+            return Err(Error::InvalidData);
+        }
+
+        let mut flags = self.child_object_flags & !(FLAG_WINGS | FLAG_BIG);
+        self.child_object_type = match settings.projectile {
+            Projectile::Default => {
+                flags = 0;
+                -1
+            }
+            Projectile::Object(object_type) => object_type.into(),
+            Projectile::Unknown(id) => id,
+        };
+        if settings.projectile != Projectile::Default {
+            if settings.wings {
+                flags |= FLAG_WINGS;
+            }
+            if settings.big {
+                flags |= FLAG_BIG;
+            }
+        }
+        self.child_object_flags = flags;
+        self.child_object_transformation_id = -1;
+        Ok(())
+    }
+}
+
+impl Level {
+    // Every Lakitu, bill blaster and cannon by object index, for auditing what a level throws
+    pub fn projectile_sources(&self) -> Vec<(usize, SpawnerSettings)> {
+        self.objects
+            .iter()
+            .enumerate()
+            .filter_map(|(index, object)| Some((index, object.spawner_settings()?)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        fixtures::minimal_level,
+        level::{CourseTheme, GameMode},
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_spawner_settings() {
+        let mut lakitu = Object::of(ObjectType::Lakitu).unwrap().at(5, 12);
+        assert_eq!(lakitu.spawner_settings(), Some(SpawnerSettings::default()));
+
+        let settings = SpawnerSettings {
+            projectile: Projectile::Object(ObjectType::Goomba),
+            wings: true,
+            big: false,
+        };
+        lakitu.set_spawner_settings(settings).unwrap();
+        assert_eq!(lakitu.child_object_type, i8::from(ObjectType::Goomba));
+        assert_eq!(lakitu.child_object_flags, FLAG_WINGS);
+        assert_eq!(lakitu.spawner_settings(), Some(settings));
+
+        lakitu
+            .set_spawner_settings(SpawnerSettings::default())
+            .unwrap();
+        assert_eq!(
+            (lakitu.child_object_type, lakitu.child_object_flags),
+            (-1, 0)
+        );
+
+        let mut blaster = Object::of(ObjectType::BillBlaster).unwrap().at(8, 1);
+        blaster.child_object_type = 100;
+        let mut level = minimal_level(GameMode::SuperMarioBros, CourseTheme::Overworld);
+        let objects = level.objects.len();
+        level.objects.push(blaster);
+        level.objects.push(Object::goomba(3, 1));
+        assert_eq!(
+            level.projectile_sources(),
+            vec![(
+                objects,
+                SpawnerSettings {
+                    projectile: Projectile::Unknown(100),
+                    ..Default::default()
+                }
+            )]
+        );
+        assert!(Object::goomba(0, 0).set_spawner_settings(settings).is_err());
+    }
+}
@@ -0,0 +1,107 @@
+// Enemies placed on top of each other, which the game turns into a tower moving as one. Each rider
+// sits directly on the one below it, at the same x position.
+
+use crate::{level::Level, objects::Object};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnemyStack {
+    // Object indexes, riders from the bottom up
+    pub base: usize,
+    pub riders: Vec<usize>,
+}
+
+impl Object {
+    // The base followed by the riders, each moved on top of the one before it
+    pub fn stack(base: Object, riders: impl IntoIterator<Item = Object>) -> Vec<Object> {
+        let mut objects = vec![base];
+        for mut rider in riders {
+            let below = &objects[objects.len() - 1];
+            rider.x_position = below.x_position;
+            rider.y_position = below.y_position + below.height.max(1) as i16 * 10;
+            objects.push(rider);
+        }
+        objects
+    }
+}
+
+impl Level {
+    // Ordered by the position of their base
+    pub fn enemy_stacks(&self) -> Vec<EnemyStack> {
+        let mut enemies: Vec<usize> = (0..self.objects.len())
+            .filter(|&index| {
+                self.objects[index]
+                    .get_object_type()
+                    .is_some_and(|object_type| object_type.is_enemy())
+            })
+            .collect();
+        enemies.sort_by_key(|&index| {
+            let object = &self.objects[index];
+            (object.x_position, object.y_position)
+        });
+
+        let mut stacks = Vec::new();
+        let mut tower: Vec<usize> = Vec::new();
+        let mut finish = |tower: &mut Vec<usize>| {
+            if tower.len() > 1 {
+                stacks.push(EnemyStack {
+                    base: tower[0],
+                    riders: tower[1..].to_vec(),
+                });
+            }
+            tower.clear();
+        };
+        for index in enemies {
+            let object = &self.objects[index];
+            let on_top = tower.last().is_some_and(|&below| {
+                let below = &self.objects[below];
+                object.x_position == below.x_position
+                    && object.y_position == below.y_position + below.height.max(1) as i16 * 10
+            });
+            if !on_top {
+                finish(&mut tower);
+            }
+            tower.push(index);
+        }
+        finish(&mut tower);
+
+        stacks
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        fixtures::minimal_level,
+        level::{CourseTheme, GameMode},
+        objects::ObjectType,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_enemy_stacks() {
+        let mut level = minimal_level(GameMode::Mario3, CourseTheme::Overworld);
+        let first = level.objects.len();
+        let mut big_koopa = Object::koopa(0, 0);
+        big_koopa.height = 2;
+        level.objects.extend(Object::stack(
+            Object::goomba(6, 1),
+            [big_koopa, Object::of(ObjectType::Spiny).unwrap()],
+        ));
+        assert_eq!(level.objects[first + 1].y_position, 20);
+        assert_eq!(level.objects[first + 2].y_position, 40);
+        // Neither on top of anything nor under anything
+        level.objects.push(Object::goomba(6, 6));
+        level.objects.push(Object::goomba(9, 1));
+        // A coin doesn't make a stack
+        level.objects.push(Object::coin(9, 2));
+
+        assert_eq!(
+            level.enemy_stacks(),
+            vec![EnemyStack {
+                base: first,
+                riders: vec![first + 1, first + 2]
+            }]
+        );
+    }
+}
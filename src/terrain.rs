@@ -0,0 +1,125 @@
+// Ground as tiles or as boxes. Levels are capped at 2600 objects, so covering ground with as few
+// boxes as possible leaves room for everything else, while single tiles are easier to analyse.
+// Only plain ground is touched, anything with flags, a child or links set is left as it is.
+
+use std::collections::BTreeSet;
+
+use crate::{level::Level, objects::Object};
+
+// Whether the object is ground on whole blocks with nothing else set
+fn is_plain_ground(object: &Object) -> bool {
+    let (x, y, width, height) = object.get_block_bounds();
+    let mut plain = Object::ground(x as u32, y as i16);
+    plain.width = width as i8;
+    plain.height = height as i8;
+    *object == plain
+}
+
+// Takes the plain ground out of the level as (x, y) tiles, in blocks
+fn take_tiles(level: &mut Level) -> BTreeSet<(i32, i32)> {
+    let mut tiles = BTreeSet::new();
+    level.objects.retain(|object| {
+        if !is_plain_ground(object) {
+            return true;
+        }
+        let (left, bottom, width, height) = object.get_block_bounds();
+        for x in left..left + width as i32 {
+            for y in bottom..bottom + height as i32 {
+                tiles.insert((x, y));
+            }
+        }
+        false
+    });
+    tiles
+}
+
+// Replaces plain ground with boxes covering the same tiles, returning how many objects were saved.
+// Boxes are grown greedily, each as wide as possible from its bottom left tile and then as tall
+pub fn merge(level: &mut Level) -> usize {
+    let count = level.objects.len();
+    let mut tiles = take_tiles(level);
+    // Ordered by row so boxes start at their bottom left tile
+    let mut order: Vec<(i32, i32)> = tiles.iter().map(|&(x, y)| (y, x)).collect();
+    order.sort();
+
+    for (y, x) in order {
+        if !tiles.contains(&(x, y)) {
+            continue;
+        }
+        let mut width = 1;
+        while width < i8::MAX as i32 && tiles.contains(&(x + width, y)) {
+            width += 1;
+        }
+        let mut height = 1;
+        while height < i8::MAX as i32
+            && (x..x + width).all(|column| tiles.contains(&(column, y + height)))
+        {
+            height += 1;
+        }
+
+        for column in x..x + width {
+            for row in y..y + height {
+                tiles.remove(&(column, row));
+            }
+        }
+        let mut ground = Object::ground(x as u32, y as i16);
+        ground.width = width as i8;
+        ground.height = height as i8;
+        level.objects.push(ground);
+    }
+
+    count - level.objects.len()
+}
+
+// Splits plain ground into single tiles, returning how many objects were added
+pub fn explode(level: &mut Level) -> usize {
+    let count = level.objects.len();
+    let tiles = take_tiles(level);
+    level.objects.extend(
+        tiles
+            .into_iter()
+            .map(|(x, y)| Object::ground(x as u32, y as i16)),
+    );
+    level.objects.len() - count
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        fixtures::minimal_level,
+        level::{CourseTheme, GameMode},
+        objects::ObjectType,
+    };
+
+    use super::*;
+
+    fn ground_tiles(level: &Level) -> BTreeSet<(i32, i32)> {
+        take_tiles(&mut level.clone())
+    }
+
+    #[test]
+    fn test_merge_and_explode() {
+        let mut level = minimal_level(GameMode::Mario3, CourseTheme::Overworld);
+        level
+            .objects
+            .retain(|object| object.get_object_type() != Some(ObjectType::Ground));
+        level
+            .objects
+            .extend((4..10).flat_map(|x| (0..3).map(move |y| Object::ground(x, y))));
+        level.objects.push(Object::ground(12, 0));
+        let mut flagged = Object::ground(13, 0);
+        flagged.object_flags = 0x40;
+        level.objects.push(flagged.clone());
+        let tiles = ground_tiles(&level);
+        let count = level.objects.len();
+
+        assert_eq!(merge(&mut level), 17);
+        assert_eq!(level.objects.len(), count - 17);
+        assert!(level.objects.contains(&flagged));
+        assert_eq!(ground_tiles(&level), tiles);
+
+        assert_eq!(explode(&mut level), 17);
+        assert_eq!(level.objects.len(), count);
+        assert_eq!(ground_tiles(&level), tiles);
+    }
+}
@@ -0,0 +1,347 @@
+// proptest strategies for writing property tests against courses. Every strategy produces values
+// that pack and unpack back to themselves, and they compose, e.g. a level strategy over a fixed
+// width with objects drawn from a custom object strategy. Corrupted course data is generated too,
+// for checking that bad input is handled gracefully.
+
+use std::ops::Range;
+
+use num_enum::TryFromPrimitive;
+use proptest::{collection::vec, prelude::*, sample::SizeRange};
+
+use crate::{
+    checksum::ChecksumKind,
+    course::Course,
+    date::{CreationDate, CreationTime},
+    level::{AutoScroll, CourseTheme, GameMode, Level},
+    objects::{Object, ObjectType},
+    sound_effects::SoundEffect,
+    thumbnail::Thumbnail,
+};
+
+// Fields of a course data file by name, for corrupting one at a time
+pub const LEVEL_REGIONS: [(&str, Range<usize>); 14] = [
+    ("version", 0x0..0x8),
+    ("checksum", 0x8..0xC),
+    ("creation time", 0x10..0x16),
+    ("name", 0x28..0x6A),
+    ("game mode", 0x6A..0x6C),
+    ("course theme", 0x6D..0x6E),
+    ("time limit", 0x70..0x72),
+    ("auto scroll", 0x72..0x73),
+    ("flags", 0x73..0x74),
+    ("width", 0x74..0x78),
+    ("mii data", 0x78..0xD8),
+    ("object count", 0xEC..0xF0),
+    ("objects", 0xF0..0x145F0),
+    ("sound effects", 0x145F0..0x14F50),
+];
+
+pub const MAX_OBJECTS: usize = 2600;
+pub const SOUND_EFFECTS: usize = 300;
+pub const MAX_BLOCK_WIDTH: u32 = 240;
+const MAX_NAME_LENGTH: usize = 32;
+const LEVEL_HEIGHT: i16 = 27;
+
+pub fn game_mode() -> impl Strategy<Value = GameMode> {
+    prop_oneof![
+        Just(GameMode::SuperMarioBros),
+        Just(GameMode::Mario3),
+        Just(GameMode::MarioWorld),
+        Just(GameMode::NewSuperMarioBrosU),
+    ]
+}
+
+pub fn course_theme() -> impl Strategy<Value = CourseTheme> {
+    prop_oneof![
+        Just(CourseTheme::Overworld),
+        Just(CourseTheme::Underground),
+        Just(CourseTheme::Castle),
+        Just(CourseTheme::Airship),
+        Just(CourseTheme::Water),
+        Just(CourseTheme::GhostHouse),
+    ]
+}
+
+pub fn auto_scroll() -> impl Strategy<Value = AutoScroll> {
+    prop_oneof![
+        Just(AutoScroll::None),
+        Just(AutoScroll::Slow),
+        Just(AutoScroll::Medium),
+        Just(AutoScroll::Fast),
+    ]
+}
+
+// Minute precision, as seconds aren't stored
+pub fn creation_time() -> impl Strategy<Value = CreationTime> {
+    (2015u16..=2099, 1u8..=12, 1u8..=28, 0u8..24, 0u8..60).prop_map(
+        |(year, month, day, hour, minute)| {
+            CreationDate::new(year, month, day, hour, minute)
+                .and_then(CreationDate::to_creation_time)
+                .unwrap()
+        },
+    )
+}
+
+// Names made of characters that fit in a single UCS-2 unit, excluding the terminator
+pub fn level_name() -> impl Strategy<Value = String> {
+    vec(
+        prop_oneof![1u32..0xD800, 0xE000u32..=0xFFFF]
+            .prop_map(|unit| char::from_u32(unit).unwrap()),
+        0..=MAX_NAME_LENGTH,
+    )
+    .prop_map(|characters| characters.into_iter().collect())
+}
+
+// Widths in pixels of whole blocks, up to the editor's limit
+pub fn width() -> impl Strategy<Value = u32> {
+    (0..=MAX_BLOCK_WIDTH).prop_map(|blocks| blocks * 16)
+}
+
+// The narrowest and widest levels along with their neighbours
+pub fn boundary_width() -> impl Strategy<Value = u32> {
+    prop::sample::select(vec![0, 1, 2, MAX_BLOCK_WIDTH - 1, MAX_BLOCK_WIDTH])
+        .prop_map(|blocks| blocks * 16)
+}
+
+pub fn object_type() -> impl Strategy<Value = ObjectType> {
+    let object_types: Vec<ObjectType> = (i8::MIN..=i8::MAX)
+        .filter_map(|id| ObjectType::try_from_primitive(id).ok())
+        .collect();
+    prop::sample::select(object_types)
+}
+
+// Objects of known types placed inside a level of the given width in pixels
+pub fn object(width: u32) -> impl Strategy<Value = Object> {
+    (
+        (0..(width / 16).max(1) * 10, 0..LEVEL_HEIGHT * 10),
+        (1i8..=16, 1i8..=16),
+        object_type(),
+        prop::option::of(object_type()),
+        any::<(u32, u32, u32)>(),
+    )
+        .prop_map(
+            |((x, y), (width, height), object_type, child_object_type, flags)| {
+                Object::new(
+                    x,
+                    0,
+                    y,
+                    width,
+                    height,
+                    flags.0,
+                    flags.1,
+                    flags.2,
+                    object_type.into(),
+                    child_object_type.map_or(-1, i8::from),
+                    -1,
+                    -1,
+                    -1,
+                    -1,
+                )
+            },
+        )
+}
+
+// Objects with every field drawn from its full range, for exercising the serializer
+pub fn raw_object() -> impl Strategy<Value = Object> {
+    (
+        any::<(u32, u32, i16, i8, i8)>(),
+        any::<(u32, u32, u32)>(),
+        any::<(i8, i8, i16, i16, i8, i8)>(),
+    )
+        .prop_map(|(position, flags, ids)| {
+            Object::new(
+                position.0, position.1, position.2, position.3, position.4, flags.0, flags.1,
+                flags.2, ids.0, ids.1, ids.2, ids.3, ids.4, ids.5,
+            )
+        })
+}
+
+pub fn sound_effect() -> impl Strategy<Value = SoundEffect> {
+    any::<u32>().prop_map(SoundEffect::from_raw)
+}
+
+// Levels with the given width and objects, everything else drawn at random
+pub fn level_with<W, O>(
+    width: W,
+    objects: impl Fn(u32) -> O,
+    object_count: impl Into<SizeRange>,
+) -> impl Strategy<Value = Level>
+where
+    W: Strategy<Value = u32>,
+    O: Strategy<Value = Object>,
+{
+    let object_count = object_count.into();
+    width.prop_flat_map(move |width| {
+        (
+            (creation_time(), level_name(), game_mode(), course_theme()),
+            (any::<u16>(), auto_scroll(), any::<u8>()),
+            vec(any::<u8>(), 0x60),
+            vec(objects(width), object_count.clone()),
+            vec(sound_effect(), SOUND_EFFECTS),
+        )
+            .prop_map(
+                move |(
+                    (creation_time, level_name, game_mode, course_theme),
+                    (time_limit, auto_scroll, flags),
+                    mii_data,
+                    objects,
+                    sound_effects,
+                )| {
+                    Level::new(
+                        0xB,
+                        creation_time,
+                        level_name,
+                        game_mode,
+                        course_theme,
+                        time_limit,
+                        auto_scroll,
+                        flags,
+                        width,
+                        mii_data.try_into().unwrap(),
+                        objects,
+                        sound_effects,
+                    )
+                },
+            )
+    })
+}
+
+pub fn level() -> impl Strategy<Value = Level> {
+    level_with(width(), object, 0..=64)
+}
+
+pub fn boundary_width_level() -> impl Strategy<Value = Level> {
+    level_with(boundary_width(), object, 0..=64)
+}
+
+// Levels holding as many objects as the format allows
+pub fn max_object_level() -> impl Strategy<Value = Level> {
+    level_with(width(), object, MAX_OBJECTS)
+}
+
+pub fn thumbnail() -> impl Strategy<Value = Thumbnail> {
+    vec(any::<u8>(), 0..=0x400).prop_map(|jpeg_data| Thumbnail { jpeg_data })
+}
+
+pub fn course() -> impl Strategy<Value = Course> {
+    (level(), level(), thumbnail(), thumbnail()).prop_map(
+        |(level, sub_level, level_preview, level_thumbnail)| {
+            Course::new(level, sub_level, level_preview, level_thumbnail)
+        },
+    )
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Corruption {
+    Truncated {
+        length: usize,
+    },
+    // The checksum is fixed up afterwards, unless the flip is in the checksum itself
+    BitFlip {
+        region: &'static str,
+        offset: usize,
+        bit: u8,
+    },
+    WrongChecksum,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CorruptedBytes {
+    pub corruption: Corruption,
+    pub bytes: Vec<u8>,
+}
+
+fn fix_checksum(bytes: &mut [u8]) {
+    let _ = ChecksumKind::CourseData.patch(bytes);
+}
+
+// Systematic variants of valid course data: truncated at every field boundary, each field with
+// bits flipped at its start, middle and end, and a wrong checksum
+pub fn corrupt_level(bytes: &[u8]) -> Vec<CorruptedBytes> {
+    let mut corrupted = Vec::new();
+
+    let mut lengths = vec![0, bytes.len() / 2, bytes.len().saturating_sub(1)];
+    lengths.extend(LEVEL_REGIONS.iter().map(|(_, region)| region.start));
+    lengths.retain(|&length| length < bytes.len());
+    lengths.sort();
+    lengths.dedup();
+    for length in lengths {
+        corrupted.push(CorruptedBytes {
+            corruption: Corruption::Truncated { length },
+            bytes: bytes[..length].to_vec(),
+        });
+    }
+
+    for (region, range) in LEVEL_REGIONS.iter().cloned() {
+        let range = range.start..range.end.min(bytes.len());
+        if range.is_empty() {
+            continue;
+        }
+        let mut offsets = vec![range.start, (range.start + range.end) / 2, range.end - 1];
+        offsets.dedup();
+        for (offset, bit) in offsets.into_iter().zip([0, 7, 3]) {
+            let mut bytes = bytes.to_vec();
+            bytes[offset] ^= 1 << bit;
+            if region != "checksum" && bytes.len() >= 0x10 {
+                fix_checksum(&mut bytes);
+            }
+            corrupted.push(CorruptedBytes {
+                corruption: Corruption::BitFlip {
+                    region,
+                    offset,
+                    bit,
+                },
+                bytes,
+            });
+        }
+    }
+
+    if bytes.len() >= 0x10 {
+        let mut bytes = bytes.to_vec();
+        fix_checksum(&mut bytes);
+        bytes[0x8] ^= 0xFF;
+        corrupted.push(CorruptedBytes {
+            corruption: Corruption::WrongChecksum,
+            bytes,
+        });
+    }
+
+    corrupted
+}
+
+// One of the corrupt_level variants
+pub fn corrupted_level(bytes: &[u8]) -> impl Strategy<Value = CorruptedBytes> {
+    prop::sample::select(corrupt_level(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixtures::minimal_level;
+
+    use super::*;
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(32))]
+
+        #[test]
+        fn test_level_round_trip(level in boundary_width_level()) {
+            prop_assert_eq!(Level::from_bytes(&level.to_bytes().unwrap()).unwrap(), level);
+        }
+
+        #[test]
+        fn test_corrupted_levels_dont_panic(corrupted in corrupted_level(
+            &minimal_level(GameMode::SuperMarioBros, CourseTheme::Overworld).to_bytes().unwrap()
+        )) {
+            let result = Level::from_bytes(&corrupted.bytes);
+            if let Corruption::Truncated { .. } = corrupted.corruption {
+                prop_assert!(result.is_err());
+            }
+        }
+
+        #[test]
+        fn test_max_object_level_round_trip(level in max_object_level()) {
+            prop_assert_eq!(level.objects.len(), MAX_OBJECTS);
+            prop_assert_eq!(Level::from_bytes(&level.to_bytes().unwrap()).unwrap(), level);
+        }
+    }
+}
@@ -1,25 +1,360 @@
+use std::ops::Range;
+
 use crc32fast::Hasher;
+use image::{
+    codecs::jpeg::JpegEncoder, imageops::FilterType, DynamicImage, ImageFormat, Rgba, RgbaImage,
+};
+
+use crate::{
+    checksum::{ChecksumKind, ChecksumPolicy},
+    Error, FileKind,
+};
+
+mod font;
+
+// thumbnail0.tnl is a wide preview of the course, thumbnail1.tnl the course list thumbnail
+pub const PREVIEW_SIZE: (u32, u32) = (720, 81);
+pub const THUMBNAIL_SIZE: (u32, u32) = (320, 240);
+// Size of a whole .tnl file
+pub const FILE_SIZE: usize = 0xC800;
+// Largest JPEG a .tnl can hold
+pub const MAX_JPEG_SIZE: usize = 0xC7F8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThumbnailSlot {
+    // thumbnail0.tnl
+    Preview,
+    // thumbnail1.tnl
+    CourseList,
+}
+
+impl ThumbnailSlot {
+    pub fn size(&self) -> (u32, u32) {
+        match self {
+            ThumbnailSlot::Preview => PREVIEW_SIZE,
+            ThumbnailSlot::CourseList => THUMBNAIL_SIZE,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThumbnailIssue {
+    // No frame header could be found, the data isn't a JPEG or is truncated
+    NotJpeg,
+    WrongSize {
+        expected: (u32, u32),
+        actual: (u32, u32),
+    },
+    // Only baseline JPEGs decode on console, not progressive or lossless ones
+    NotBaseline,
+    // Color JPEGs have three components, YCbCr
+    ComponentCount(u8),
+    // Chroma has to be sampled at most as often as luma, with luma at most 2x2 (4:2:0)
+    UnsupportedSubsampling {
+        horizontal: u8,
+        vertical: u8,
+    },
+}
+
+// The parts of a JPEG start of frame header the console cares about
+struct FrameHeader {
+    marker: u8,
+    width: u32,
+    height: u32,
+    // Sampling factors of each component
+    sampling: Vec<(u8, u8)>,
+}
+
+impl FrameHeader {
+    fn parse(jpeg_data: &[u8]) -> Option<FrameHeader> {
+        let (marker, segment) = header_segments(jpeg_data)?
+            .into_iter()
+            // Start of frame markers, excluding DHT, JPG and DAC which share the range
+            .map(|(marker, range)| (marker, &jpeg_data[range.start + 4..range.end]))
+            .find(|(marker, _)| {
+                (0xC0..=0xCF).contains(marker) && ![0xC4, 0xC8, 0xCC].contains(marker)
+            })?;
+
+        let components = *segment.get(5)? as usize;
+        let sampling = (0..components)
+            .map(|i| {
+                let factors = *segment.get(6 + i * 3 + 1)?;
+                Some((factors >> 4, factors & 0xF))
+            })
+            .collect::<Option<_>>()?;
+        Some(FrameHeader {
+            marker,
+            width: u16::from_be_bytes(segment[3..5].try_into().ok()?) as u32,
+            height: u16::from_be_bytes(segment[1..3].try_into().ok()?) as u32,
+            sampling,
+        })
+    }
+}
+
+// Marker and byte range of each segment after SOI, ending with the start of scan. The entropy
+// coded data follows the last range. None if the JPEG is malformed or truncated
+fn header_segments(jpeg_data: &[u8]) -> Option<Vec<(u8, Range<usize>)>> {
+    if jpeg_data.get(0..2)? != [0xFF, 0xD8] {
+        return None;
+    }
 
-use crate::Error;
+    let mut segments = Vec::new();
+    let mut offset = 2;
+    loop {
+        if *jpeg_data.get(offset)? != 0xFF {
+            return None;
+        }
+        let marker = *jpeg_data.get(offset + 1)?;
+        // Fill bytes before a marker
+        if marker == 0xFF {
+            offset += 1;
+            continue;
+        }
+        let length =
+            u16::from_be_bytes(jpeg_data.get(offset + 2..offset + 4)?.try_into().ok()?) as usize;
+        if length < 2 || offset + 2 + length > jpeg_data.len() {
+            return None;
+        }
+
+        segments.push((marker, offset..offset + 2 + length));
+        offset += 2 + length;
+        if marker == 0xDA {
+            return Some(segments);
+        }
+    }
+}
 
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Thumbnail {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_base64"))]
     pub jpeg_data: Vec<u8>,
 }
 
-impl Thumbnail {
-    pub fn from_bytes(bytes: &[u8]) -> Thumbnail {
-        let jpeg_length = bytes[0x4..0x8].try_into().unwrap();
-        let jpeg_length = u32::from_be_bytes(jpeg_length) as usize;
+// A thumbnail borrowing its JPEG from the .tnl bytes, for viewers that hand it straight to a
+// decoder
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ThumbnailRef<'a> {
+    pub jpeg_data: &'a [u8],
+}
+
+impl<'a> ThumbnailRef<'a> {
+    // Uses the default ChecksumPolicy
+    pub fn from_bytes(bytes: &'a [u8]) -> Result<ThumbnailRef<'a>, Error> {
+        ThumbnailRef::from_bytes_with_policy(bytes, ChecksumPolicy::default())
+    }
+
+    // Data of the wrong size fails with Error::WrongSize, a JPEG length past the end of the file
+    // with Error::InvalidData
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub fn from_bytes_with_policy(
+        bytes: &'a [u8],
+        policy: ChecksumPolicy,
+    ) -> Result<ThumbnailRef<'a>, Error> {
+        if bytes.len() != FILE_SIZE {
+            return Err(Error::WrongSize {
+                expected: FILE_SIZE,
+                actual: bytes.len(),
+                kind: FileKind::Thumbnail,
+            });
+        }
+        policy.check(ChecksumKind::Thumbnail, bytes)?;
 
+        let jpeg_length = bytes.get(0x4..0x8).ok_or(Error::InvalidData)?;
+        let jpeg_length = u32::from_be_bytes(jpeg_length.try_into().unwrap()) as usize;
+        let jpeg_data = bytes
+            .get(0x8..0x8 + jpeg_length)
+            .ok_or(Error::InvalidData)?;
+        Ok(ThumbnailRef { jpeg_data })
+    }
+
+    pub fn to_thumbnail(&self) -> Thumbnail {
         Thumbnail {
-            jpeg_data: bytes[0x8..0x8 + jpeg_length].to_vec(),
+            jpeg_data: self.jpeg_data.to_vec(),
+        }
+    }
+}
+
+impl Thumbnail {
+    // Uses the default ChecksumPolicy
+    pub fn from_bytes(bytes: &[u8]) -> Result<Thumbnail, Error> {
+        Thumbnail::from_bytes_with_policy(bytes, ChecksumPolicy::default())
+    }
+
+    pub fn from_bytes_with_policy(
+        bytes: &[u8],
+        policy: ChecksumPolicy,
+    ) -> Result<Thumbnail, Error> {
+        ThumbnailRef::from_bytes_with_policy(bytes, policy)
+            .map(|thumbnail| thumbnail.to_thumbnail())
+    }
+
+    // Encodes the image as a JPEG, dropping any alpha channel
+    pub fn from_image(image: &RgbaImage) -> Result<Thumbnail, Error> {
+        let image = DynamicImage::ImageRgba8(image.clone()).to_rgb8();
+        let mut jpeg_data = Vec::new();
+        JpegEncoder::new(&mut jpeg_data)
+            .encode_image(&image)
+            .map_err(|_| Error::InvalidData)?;
+        Ok(Thumbnail { jpeg_data })
+    }
+
+    // A solid color image of the slot's size with the text centered on it, e.g. the course name.
+    // Text that doesn't fit on one line is cut off
+    pub fn placeholder(slot: ThumbnailSlot, text: &str) -> Result<Thumbnail, Error> {
+        let (width, height) = slot.size();
+        let mut image = RgbaImage::from_pixel(width, height, Rgba([92, 148, 252, 255]));
+
+        // Glyphs are separated by a column of padding
+        let advance = font::GLYPH_WIDTH + 1;
+        let length = (text.chars().count() as u32).min(width / advance);
+        let scale = (height / 2 / font::GLYPH_HEIGHT)
+            .min(width * 9 / 10 / (length * advance).max(1))
+            .max(1);
+        let left = width.saturating_sub((length * advance).saturating_sub(1) * scale) / 2;
+        let top = (height - font::GLYPH_HEIGHT * scale) / 2;
+
+        let visible = (width - left) / (advance * scale);
+        for (i, character) in text.chars().take(visible as usize).enumerate() {
+            let glyph_left = left + i as u32 * advance * scale;
+            for (row, bits) in font::glyph(character).iter().enumerate() {
+                for column in 0..font::GLYPH_WIDTH {
+                    if bits >> (font::GLYPH_WIDTH - 1 - column) & 1 == 0 {
+                        continue;
+                    }
+                    for y in 0..scale {
+                        for x in 0..scale {
+                            image.put_pixel(
+                                glyph_left + column * scale + x,
+                                top + row as u32 * scale + y,
+                                Rgba([255, 255, 255, 255]),
+                            );
+                        }
+                    }
+                }
+            }
+        }
+
+        Thumbnail::from_image(&image)
+    }
+
+    // Removes metadata segments such as EXIF, XMP, ICC profiles and comments, which the console
+    // ignores but which count against the size limit. JFIF and Adobe segments are kept as they
+    // affect how colors decode, the image data is untouched. Returns the number of bytes removed
+    pub fn strip_metadata(&mut self) -> Result<usize, Error> {
+        let segments = header_segments(&self.jpeg_data).ok_or(Error::InvalidData)?;
+        // header_segments always ends with the start of scan
+        let scan_end = segments.last().map_or(2, |(_, range)| range.end);
+
+        let mut jpeg_data = Vec::with_capacity(self.jpeg_data.len());
+        jpeg_data.extend_from_slice(&self.jpeg_data[0..2]);
+        for (marker, range) in segments {
+            // APP1 to APP13, APP15 and COM
+            let is_metadata = (0xE1..=0xEF).contains(&marker) && marker != 0xEE || marker == 0xFE;
+            if !is_metadata {
+                jpeg_data.extend_from_slice(&self.jpeg_data[range]);
+            }
+        }
+        // Metadata after the first scan, e.g. in progressive JPEGs, is left alone
+        jpeg_data.extend_from_slice(&self.jpeg_data[scan_end..]);
+
+        let removed = self.jpeg_data.len() - jpeg_data.len();
+        self.jpeg_data = jpeg_data;
+        Ok(removed)
+    }
+
+    // 64 bit DCT perceptual hash of the image. Thumbnails that look the same hash the same or
+    // within a few bits of each other, even after re-encoding or resizing, see phash_distance
+    pub fn phash(&self) -> Result<u64, Error> {
+        const SIZE: usize = 32;
+        const BITS: usize = 8;
+
+        let image = image::load_from_memory_with_format(&self.jpeg_data, ImageFormat::Jpeg)
+            .map_err(|_| Error::InvalidData)?
+            .resize_exact(SIZE as u32, SIZE as u32, FilterType::Triangle)
+            .to_luma8();
+
+        // Only the lowest frequencies of the DCT are needed
+        let cosines: Vec<[f64; SIZE]> = (0..BITS)
+            .map(|frequency| {
+                std::array::from_fn(|x| {
+                    ((2 * x + 1) as f64 * frequency as f64 * std::f64::consts::PI
+                        / (2 * SIZE) as f64)
+                        .cos()
+                })
+            })
+            .collect();
+        let mut coefficients = [0.0; BITS * BITS];
+        for (v, row_cosines) in cosines.iter().enumerate() {
+            for (u, column_cosines) in cosines.iter().enumerate() {
+                coefficients[v * BITS + u] = image
+                    .enumerate_pixels()
+                    .map(|(x, y, pixel)| {
+                        pixel.0[0] as f64 * column_cosines[x as usize] * row_cosines[y as usize]
+                    })
+                    .sum();
+            }
+        }
+
+        // The DC term is the average brightness, which would swamp the median
+        let mut sorted = coefficients[1..].to_vec();
+        sorted.sort_by(f64::total_cmp);
+        let median = sorted[sorted.len() / 2];
+
+        Ok(coefficients
+            .iter()
+            .enumerate()
+            .filter(|&(_, &coefficient)| coefficient > median)
+            .fold(0, |hash, (i, _)| hash | 1 << i))
+    }
+
+    // Checks the JPEG header against what the console expects for the slot, without decoding
+    // the image. Returns every problem found, so an empty list means the thumbnail is usable
+    pub fn validate_for(&self, slot: ThumbnailSlot) -> Vec<ThumbnailIssue> {
+        let Some(header) = FrameHeader::parse(&self.jpeg_data) else {
+            return vec![ThumbnailIssue::NotJpeg];
+        };
+
+        let mut issues = Vec::new();
+        let actual = (header.width, header.height);
+        if actual != slot.size() {
+            issues.push(ThumbnailIssue::WrongSize {
+                expected: slot.size(),
+                actual,
+            });
+        }
+        if header.marker != 0xC0 {
+            issues.push(ThumbnailIssue::NotBaseline);
+        }
+        if header.sampling.len() != 3 {
+            issues.push(ThumbnailIssue::ComponentCount(header.sampling.len() as u8));
+        } else {
+            let (horizontal, vertical) = header.sampling[0];
+            let supported = (1..=2).contains(&horizontal)
+                && (1..=2).contains(&vertical)
+                && header.sampling[1..]
+                    .iter()
+                    .all(|&factors| factors == (1, 1));
+            if !supported {
+                issues.push(ThumbnailIssue::UnsupportedSubsampling {
+                    horizontal,
+                    vertical,
+                });
+            }
+        }
+
+        issues
+    }
+
+    pub fn as_thumbnail_ref(&self) -> ThumbnailRef<'_> {
+        ThumbnailRef {
+            jpeg_data: &self.jpeg_data,
         }
     }
 
     pub fn to_bytes(&self) -> Result<Vec<u8>, Error> {
         let mut bytes_without_checksum = Vec::new();
-        if self.jpeg_data.len() > 0xC7F8 {
+        if self.jpeg_data.len() > MAX_JPEG_SIZE {
             return Err(Error::FileTooLarge);
         }
 
@@ -27,7 +362,7 @@ impl Thumbnail {
 
         bytes_without_checksum.extend_from_slice(&jpeg_length);
         bytes_without_checksum.extend_from_slice(&self.jpeg_data);
-        bytes_without_checksum.resize(0xC800 - 4, 0);
+        bytes_without_checksum.resize(FILE_SIZE - 4, 0);
 
         let mut hasher = Hasher::new();
         hasher.update(&bytes_without_checksum);
@@ -39,4 +374,181 @@ impl Thumbnail {
 
         Ok(bytes)
     }
+
+    // Like to_bytes, but a JPEG too large for the file is re-encoded at decreasing quality, then
+    // at half the size, until it fits. Returns the quality used, None if the JPEG already fit
+    pub fn to_bytes_fitted(&self) -> Result<(Vec<u8>, Option<u8>), Error> {
+        if self.jpeg_data.len() <= MAX_JPEG_SIZE {
+            return Ok((self.to_bytes()?, None));
+        }
+
+        let mut image = image::load_from_memory_with_format(&self.jpeg_data, ImageFormat::Jpeg)
+            .map_err(|_| Error::InvalidData)?
+            .to_rgb8();
+        loop {
+            for quality in (10..=90).rev().step_by(10) {
+                let mut jpeg_data = Vec::new();
+                JpegEncoder::new_with_quality(&mut jpeg_data, quality)
+                    .encode_image(&image)
+                    .map_err(|_| Error::InvalidData)?;
+                if jpeg_data.len() <= MAX_JPEG_SIZE {
+                    return Ok((Thumbnail { jpeg_data }.to_bytes()?, Some(quality)));
+                }
+            }
+
+            if image.width() <= 1 && image.height() <= 1 {
+                return Err(Error::FileTooLarge);
+            }
+            image = image::imageops::resize(
+                &image,
+                (image.width() / 2).max(1),
+                (image.height() / 2).max(1),
+                FilterType::Triangle,
+            );
+        }
+    }
+}
+
+// Number of differing bits between two perceptual hashes, roughly 10 or fewer is the same image
+pub fn phash_distance(a: u64, b: u64) -> u32 {
+    (a ^ b).count_ones()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixtures::thumbnail;
+
+    use super::*;
+
+    #[test]
+    fn test_thumbnail_ref_borrows() {
+        let thumbnail = thumbnail(THUMBNAIL_SIZE, [0, 0, 0]);
+        let bytes = thumbnail.to_bytes().unwrap();
+
+        let thumbnail_ref = ThumbnailRef::from_bytes(&bytes).unwrap();
+        assert_eq!(thumbnail_ref.jpeg_data.as_ptr(), bytes[0x8..].as_ptr());
+        assert_eq!(thumbnail_ref, thumbnail.as_thumbnail_ref());
+        assert_eq!(thumbnail_ref.to_thumbnail(), thumbnail);
+    }
+
+    #[test]
+    fn test_from_bytes_wrong_size() {
+        let bytes = thumbnail(THUMBNAIL_SIZE, [0, 0, 0]).to_bytes().unwrap();
+        assert!(matches!(
+            Thumbnail::from_bytes(&bytes[..16]),
+            Err(Error::WrongSize {
+                expected: FILE_SIZE,
+                actual: 16,
+                kind: FileKind::Thumbnail
+            })
+        ));
+        assert!(ThumbnailRef::from_bytes(&[]).is_err());
+
+        // A JPEG length running past the end of the file
+        let mut bytes = bytes;
+        bytes[0x4..0x8].copy_from_slice(&(FILE_SIZE as u32).to_be_bytes());
+        assert!(matches!(
+            Thumbnail::from_bytes_with_policy(&bytes, ChecksumPolicy::Ignore),
+            Err(Error::InvalidData)
+        ));
+    }
+
+    #[test]
+    fn test_validate_for() {
+        let preview = thumbnail(PREVIEW_SIZE, [0, 0, 0]);
+        assert!(preview.validate_for(ThumbnailSlot::Preview).is_empty());
+        assert_eq!(
+            preview.validate_for(ThumbnailSlot::CourseList),
+            vec![ThumbnailIssue::WrongSize {
+                expected: THUMBNAIL_SIZE,
+                actual: PREVIEW_SIZE
+            }]
+        );
+        assert_eq!(
+            Thumbnail {
+                jpeg_data: vec![0xFF, 0xD8, 0xFF]
+            }
+            .validate_for(ThumbnailSlot::Preview),
+            vec![ThumbnailIssue::NotJpeg]
+        );
+    }
+
+    #[test]
+    fn test_placeholder() {
+        for slot in [ThumbnailSlot::Preview, ThumbnailSlot::CourseList] {
+            for text in ["", "Super Mario Maker", &"Long name ".repeat(40)] {
+                let placeholder = Thumbnail::placeholder(slot, text).unwrap();
+                assert!(placeholder.validate_for(slot).is_empty());
+                assert!(placeholder.to_bytes().is_ok());
+            }
+        }
+    }
+
+    #[test]
+    fn test_strip_metadata() {
+        let original = thumbnail(THUMBNAIL_SIZE, [0, 0, 0]);
+        // An EXIF and a comment segment after the JFIF header
+        let mut with_metadata = original.clone();
+        let jfif_end =
+            2 + 2 + u16::from_be_bytes([original.jpeg_data[4], original.jpeg_data[5]]) as usize;
+        let metadata = [
+            &[0xFF, 0xE1, 0x00, 0x08][..],
+            b"Exif\0\0",
+            &[0xFF, 0xFE, 0x00, 0x07],
+            b"hello",
+        ]
+        .concat();
+        with_metadata
+            .jpeg_data
+            .splice(jfif_end..jfif_end, metadata.iter().copied());
+
+        assert_eq!(with_metadata.strip_metadata().unwrap(), metadata.len());
+        assert_eq!(with_metadata, original);
+        assert!(with_metadata
+            .validate_for(ThumbnailSlot::CourseList)
+            .is_empty());
+        assert_eq!(with_metadata.strip_metadata().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_phash() {
+        let placeholder = Thumbnail::placeholder(ThumbnailSlot::CourseList, "Castle").unwrap();
+        // Smaller and at a much lower quality
+        let image = image::load_from_memory(&placeholder.jpeg_data).unwrap();
+        let mut jpeg_data = Vec::new();
+        JpegEncoder::new_with_quality(&mut jpeg_data, 30)
+            .encode_image(&image.resize_exact(160, 120, FilterType::Nearest))
+            .unwrap();
+        let reencoded = Thumbnail { jpeg_data };
+        let other = Thumbnail::placeholder(ThumbnailSlot::CourseList, "Airship 2").unwrap();
+
+        let hash = placeholder.phash().unwrap();
+        assert!(phash_distance(hash, reencoded.phash().unwrap()) <= 10);
+        assert!(phash_distance(hash, other.phash().unwrap()) > 10);
+    }
+
+    #[test]
+    fn test_to_bytes_fitted() {
+        // Noise doesn't compress, so at full quality it's far over the limit
+        let noise = image::RgbImage::from_fn(320, 240, |x, y| {
+            let value = (x * 7919 + y * 104729).wrapping_mul(2654435761) >> 8;
+            image::Rgb([value as u8, (value >> 8) as u8, (value >> 16) as u8])
+        });
+        let mut jpeg_data = Vec::new();
+        JpegEncoder::new_with_quality(&mut jpeg_data, 100)
+            .encode_image(&noise)
+            .unwrap();
+        let large = Thumbnail { jpeg_data };
+        assert!(matches!(large.to_bytes(), Err(Error::FileTooLarge)));
+
+        let (bytes, quality) = large.to_bytes_fitted().unwrap();
+        assert!(quality.is_some());
+        assert!(Thumbnail::from_bytes(&bytes).unwrap().jpeg_data.len() <= MAX_JPEG_SIZE);
+
+        let small = thumbnail(THUMBNAIL_SIZE, [0, 0, 0]);
+        assert_eq!(
+            small.to_bytes_fitted().unwrap(),
+            (small.to_bytes().unwrap(), None)
+        );
+    }
 }
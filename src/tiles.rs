@@ -0,0 +1,124 @@
+// Levels as a grid of typed tiles, the input most gameplay analysis and machine learning projects
+// want. Objects are laid down in draw order, so a tile shows what's drawn in front.
+
+use crate::{level::Level, objects::ObjectType};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Tile {
+    #[default]
+    Empty,
+    // Ground, goal ground and the start block
+    Ground,
+    // Solid blocks and pipes along with what they hold, and hidden blocks
+    Block {
+        object_type: ObjectType,
+        contents: Option<ObjectType>,
+    },
+    Semisolid(ObjectType),
+    Enemy(ObjectType),
+    // See ObjectType::is_hazard
+    Hazard(ObjectType),
+    // Coins and power-ups
+    Item(ObjectType),
+    // The goal pole
+    Goal,
+    Other(ObjectType),
+    // The object type byte of a part that hasn't been identified
+    Unknown(i8),
+}
+
+impl Tile {
+    fn of(object_type: ObjectType, contents: Option<ObjectType>) -> Tile {
+        match object_type {
+            ObjectType::Ground | ObjectType::GoalGround | ObjectType::StartBlock => Tile::Ground,
+            ObjectType::Goal => Tile::Goal,
+            ObjectType::HiddenBlock => Tile::Block {
+                object_type,
+                contents,
+            },
+            _ if object_type.is_solid() => Tile::Block {
+                object_type,
+                contents,
+            },
+            _ if object_type.is_semisolid() => Tile::Semisolid(object_type),
+            _ if object_type.is_hazard() => Tile::Hazard(object_type),
+            _ if object_type.is_enemy() => Tile::Enemy(object_type),
+            _ if object_type.is_powerup() || object_type == ObjectType::Coin => {
+                Tile::Item(object_type)
+            }
+            _ => Tile::Other(object_type),
+        }
+    }
+}
+
+impl Level {
+    // Indexed as [x][y] in blocks, block_width columns of block_height tiles with y = 0 at the
+    // bottom. A width past Level::MAX_BLOCK_WIDTH is cut off there
+    pub fn to_tile_matrix(&self) -> Vec<Vec<Tile>> {
+        let block_width = self.block_width().min(Level::MAX_BLOCK_WIDTH) as i32;
+        let block_height = self.block_height() as i32;
+        let mut tiles = vec![vec![Tile::Empty; block_height as usize]; block_width as usize];
+
+        for object in self.objects_sorted_by_layer() {
+            let tile = match object.get_object_type() {
+                Some(object_type) => Tile::of(
+                    object_type,
+                    ObjectType::try_from(object.child_object_type).ok(),
+                ),
+                None => Tile::Unknown(object.object_type),
+            };
+            let (left, bottom, width, height) = object.get_block_bounds();
+            for x in left.max(0)..(left + width as i32).min(block_width) {
+                for y in bottom.max(0)..(bottom + height as i32).min(block_height) {
+                    tiles[x as usize][y as usize] = tile;
+                }
+            }
+        }
+
+        tiles
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        fixtures::minimal_level,
+        level::{CourseTheme, GameMode},
+        objects::Object,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_to_tile_matrix() {
+        let mut level = minimal_level(GameMode::Mario3, CourseTheme::Overworld);
+        level.objects.push(Object::goomba(3, 1));
+        level
+            .objects
+            .push(Object::question_block(5, 4, Some(ObjectType::FireFlower)));
+        let mut spikes = Object::of(ObjectType::Spikes).unwrap().at(7, 1);
+        spikes.width = 2;
+        level.objects.push(spikes);
+
+        let tiles = level.to_tile_matrix();
+        assert_eq!((tiles.len(), tiles[0].len()), (24, 27));
+        assert_eq!(tiles[0][0], Tile::Ground);
+        assert_eq!(tiles[0][1], Tile::Empty);
+        assert_eq!(tiles[3][1], Tile::Enemy(ObjectType::Goomba));
+        assert_eq!(
+            tiles[5][4],
+            Tile::Block {
+                object_type: ObjectType::QuestionBlock,
+                contents: Some(ObjectType::FireFlower)
+            }
+        );
+        assert_eq!(tiles[8][1], Tile::Hazard(ObjectType::Spikes));
+        assert_eq!(tiles[18][1], Tile::Goal);
+
+        level.width = u32::MAX;
+        assert_eq!(
+            level.to_tile_matrix().len(),
+            Level::MAX_BLOCK_WIDTH as usize
+        );
+    }
+}
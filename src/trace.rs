@@ -0,0 +1,10 @@
+// Span wrapper over tracing that compiles to nothing without the tracing feature, so call sites
+// don't each need their own cfg attributes.
+
+// Enters a debug span for the rest of the enclosing scope
+macro_rules! trace_span {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!($($arg)*).entered();
+    };
+}
@@ -0,0 +1,700 @@
+// Structural checks for levels that parse fine but that the console rejects or silently fixes
+// up, unlike analysis which looks at how a level plays.
+
+use std::collections::HashMap;
+
+use crate::{
+    analysis::{Cell, OccupancyGrid},
+    level::{GameMode, Level},
+    objects::{Object, ObjectType, Orientation},
+};
+
+// Blocks of start ground the editor keeps clear
+const START_AREA: i32 = 7;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValidationIssue {
+    // More objects than the file has room for
+    TooManyObjects {
+        count: usize,
+    },
+    // The sound effect table has to have exactly Level::SOUND_EFFECTS slots
+    SoundEffectCount {
+        count: usize,
+    },
+    // Part of the object lies outside the level, which the game doesn't handle. Positions are the
+    // bottom left block, suggested being the nearest one keeping the object inside
+    ObjectOutOfBounds {
+        object: usize,
+        x: i32,
+        y: i32,
+        suggested_x: i32,
+        suggested_y: i32,
+    },
+    // Doors come in pairs sharing a link ID
+    UnpairedDoor {
+        object: usize,
+        link_id: i16,
+    },
+    // The object references a sound effect slot past the end of the table
+    MissingSoundEffect {
+        object: usize,
+        effect_index: i16,
+    },
+    // The effect lies outside the level, the console drops it
+    SoundEffectOutOfBounds {
+        effect_index: usize,
+        x: u8,
+        y: u8,
+    },
+    // The sound type doesn't exist or doesn't have the variation
+    InvalidSoundEffect {
+        effect_index: usize,
+        sound_type: u8,
+        variation: u8,
+    },
+    // The effect isn't placed on the object referencing it
+    SoundEffectMismatch {
+        object: usize,
+        effect_index: usize,
+    },
+    // More than Level::MAX_CHECKPOINTS checkpoint flags
+    TooManyCheckpoints {
+        count: usize,
+    },
+    // Checkpoints can't be placed in the start area or past the goal
+    CheckpointPlacement {
+        object: usize,
+    },
+    // The part, or the part it holds, isn't offered in the level's style
+    UnavailableInStyle {
+        object: usize,
+        object_type: ObjectType,
+        game_mode: GameMode,
+    },
+    // A door of a pair past Level::MAX_DOOR_PAIRS, counting pairs in the order they first appear
+    TooManyDoors {
+        object: usize,
+        link_id: i16,
+    },
+    // A pipe with a destination past Level::MAX_WARP_PIPES
+    TooManyWarpPipes {
+        object: usize,
+        link_id: i16,
+    },
+    // The orientation bits point a way the part can't be turned
+    InvalidOrientation {
+        object: usize,
+        object_type: ObjectType,
+        orientation: Orientation,
+    },
+    // Bill blasters have to stand on, or hang from, something solid
+    UnattachedOrientation {
+        object: usize,
+        orientation: Orientation,
+    },
+}
+
+pub type Rule = fn(&Level) -> Vec<ValidationIssue>;
+
+// A configurable set of rules, the default runs all of them
+#[derive(Debug, Clone)]
+pub struct Validator {
+    rules: Vec<Rule>,
+}
+
+impl Default for Validator {
+    fn default() -> Self {
+        Validator {
+            rules: vec![
+                limit_issues,
+                object_bounds_issues,
+                door_link_issues,
+                link_limit_issues,
+                sound_effect_issues,
+                checkpoint_issues,
+                style_issues,
+                orientation_issues,
+            ],
+        }
+    }
+}
+
+impl Validator {
+    // A validator without any rules
+    pub fn new() -> Validator {
+        Validator { rules: Vec::new() }
+    }
+
+    pub fn with_rule(mut self, rule: Rule) -> Validator {
+        self.rules.push(rule);
+        self
+    }
+
+    pub fn validate(&self, level: &Level) -> Vec<ValidationIssue> {
+        self.rules.iter().flat_map(|rule| rule(level)).collect()
+    }
+}
+
+// Every issue found by the default rules, grouped by rule
+pub fn validate(level: &Level) -> Vec<ValidationIssue> {
+    Validator::default().validate(level)
+}
+
+pub fn limit_issues(level: &Level) -> Vec<ValidationIssue> {
+    let mut issues = Vec::new();
+    if level.objects.len() > Level::MAX_OBJECTS {
+        issues.push(ValidationIssue::TooManyObjects {
+            count: level.objects.len(),
+        });
+    }
+    if level.sound_effects.len() != Level::SOUND_EFFECTS {
+        issues.push(ValidationIssue::SoundEffectCount {
+            count: level.sound_effects.len(),
+        });
+    }
+    issues
+}
+
+pub fn checkpoint_issues(level: &Level) -> Vec<ValidationIssue> {
+    let checkpoints = level.checkpoints();
+    let mut issues = Vec::new();
+    if checkpoints.len() > Level::MAX_CHECKPOINTS {
+        issues.push(ValidationIssue::TooManyCheckpoints {
+            count: checkpoints.len(),
+        });
+    }
+
+    // Sub areas have no goal
+    let goal_x = level
+        .objects
+        .iter()
+        .find(|object| object.get_object_type() == Some(ObjectType::Goal))
+        .map(|goal| goal.get_block_bounds().0);
+    issues.extend(
+        checkpoints
+            .into_iter()
+            .filter(|&(_, x, _)| x < START_AREA || goal_x.is_some_and(|goal_x| x >= goal_x))
+            .map(|(object, _, _)| ValidationIssue::CheckpointPlacement { object }),
+    );
+    issues
+}
+
+// Flags are checked too, see ObjectType::available_with_flags_in
+pub fn style_issues(level: &Level) -> Vec<ValidationIssue> {
+    let mut issues = Vec::new();
+    for (index, object) in level.objects.iter().enumerate() {
+        let child_type = ObjectType::try_from(object.child_object_type).ok();
+        let parts = [
+            (object.get_object_type(), object.object_flags),
+            (child_type, object.child_object_flags),
+        ];
+        for (object_type, flags) in parts {
+            let Some(object_type) = object_type else {
+                continue;
+            };
+            if !object_type.available_with_flags_in(flags, level.game_mode) {
+                issues.push(ValidationIssue::UnavailableInStyle {
+                    object: index,
+                    object_type,
+                    game_mode: level.game_mode,
+                });
+            }
+        }
+    }
+    issues
+}
+
+// Burners and cannons can be placed anywhere, bill blasters need something solid behind their base
+pub fn orientation_issues(level: &Level) -> Vec<ValidationIssue> {
+    let grid = OccupancyGrid::from_level(level);
+    let mut issues = Vec::new();
+    for (index, object) in level.objects.iter().enumerate() {
+        let (Some(object_type), Some(orientation)) =
+            (object.get_object_type(), object.orientation())
+        else {
+            continue;
+        };
+
+        if !object_type.orientations().contains(&orientation) {
+            issues.push(ValidationIssue::InvalidOrientation {
+                object: index,
+                object_type,
+                orientation,
+            });
+        } else if object_type == ObjectType::BillBlaster {
+            let (left, bottom, _, height) = object.get_block_bounds();
+            let behind = match orientation {
+                Orientation::Down => bottom + height as i32,
+                _ => bottom - 1,
+            };
+            if grid.get(left, behind) != Cell::Solid {
+                issues.push(ValidationIssue::UnattachedOrientation {
+                    object: index,
+                    orientation,
+                });
+            }
+        }
+    }
+    issues
+}
+
+pub fn object_bounds_issues(level: &Level) -> Vec<ValidationIssue> {
+    // Objects bigger than the level are pinned to its bottom left
+    let clamp = |position: i32, size: u32, limit: u32| {
+        position.clamp(0, (limit as i64 - size as i64).max(0) as i32)
+    };
+
+    level
+        .objects
+        .iter()
+        .enumerate()
+        .filter_map(|(object, data)| {
+            let (left, bottom, width, height) = data.get_block_bounds();
+            let suggested_x = clamp(left, width, level.block_width());
+            let suggested_y = clamp(bottom, height, level.block_height());
+            let outside = left as i64 + width as i64 > level.block_width() as i64
+                || bottom as i64 + height as i64 > level.block_height() as i64
+                || (suggested_x, suggested_y) != (left, bottom);
+            outside.then_some(ValidationIssue::ObjectOutOfBounds {
+                object,
+                x: left,
+                y: bottom,
+                suggested_x,
+                suggested_y,
+            })
+        })
+        .collect()
+}
+
+pub fn door_link_issues(level: &Level) -> Vec<ValidationIssue> {
+    let is_door = |object: &&Object| object.get_object_type() == Some(ObjectType::Door);
+
+    let mut counts: HashMap<i16, usize> = HashMap::new();
+    for object in level.objects.iter().filter(is_door) {
+        *counts.entry(object.link_id).or_default() += 1;
+    }
+
+    level
+        .objects
+        .iter()
+        .enumerate()
+        .filter(|(_, object)| is_door(object))
+        .filter(|(_, object)| object.link_id < 0 || counts[&object.link_id] != 2)
+        .map(|(object_index, object)| ValidationIssue::UnpairedDoor {
+            object: object_index,
+            link_id: object.link_id,
+        })
+        .collect()
+}
+
+// Unpaired doors count towards the limit too, door_link_issues reports them separately
+pub fn link_limit_issues(level: &Level) -> Vec<ValidationIssue> {
+    let mut door_pairs = Vec::new();
+    let mut warp_pipes = 0;
+    let mut issues = Vec::new();
+    for (index, object) in level.objects.iter().enumerate() {
+        match object.get_object_type() {
+            Some(ObjectType::Door) => {
+                let pair = match door_pairs
+                    .iter()
+                    .position(|&link_id| link_id == object.link_id)
+                {
+                    Some(pair) => pair,
+                    None => {
+                        door_pairs.push(object.link_id);
+                        door_pairs.len() - 1
+                    }
+                };
+                if pair >= Level::MAX_DOOR_PAIRS {
+                    issues.push(ValidationIssue::TooManyDoors {
+                        object: index,
+                        link_id: object.link_id,
+                    });
+                }
+            }
+            Some(ObjectType::Pipe) if object.link_id >= 0 => {
+                warp_pipes += 1;
+                if warp_pipes > Level::MAX_WARP_PIPES {
+                    issues.push(ValidationIssue::TooManyWarpPipes {
+                        object: index,
+                        link_id: object.link_id,
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+    issues
+}
+
+// Placed effects have to lie inside the level, and objects referencing one through effect_index
+// have to sit on it
+pub fn sound_effect_issues(level: &Level) -> Vec<ValidationIssue> {
+    let mut issues = Vec::new();
+
+    for (effect_index, effect) in level.sound_effects.iter().enumerate() {
+        if effect.is_empty() {
+            continue;
+        }
+        if u32::from(effect.x_position) >= level.block_width()
+            || u32::from(effect.y_position) >= level.block_height()
+        {
+            issues.push(ValidationIssue::SoundEffectOutOfBounds {
+                effect_index,
+                x: effect.x_position,
+                y: effect.y_position,
+            });
+        }
+        if effect.get_variation().is_none() {
+            issues.push(ValidationIssue::InvalidSoundEffect {
+                effect_index,
+                sound_type: effect.sound_type,
+                variation: effect.variation,
+            });
+        }
+    }
+
+    for (object_index, object) in level.objects.iter().enumerate() {
+        if object.effect_index < 0 {
+            continue;
+        }
+        let effect_index = object.effect_index as usize;
+        let Some(effect) = level.sound_effects.get(effect_index) else {
+            issues.push(ValidationIssue::MissingSoundEffect {
+                object: object_index,
+                effect_index: object.effect_index,
+            });
+            continue;
+        };
+
+        if object.get_x_block() != u32::from(effect.x_position)
+            || object.get_y_block() != i16::from(effect.y_position)
+        {
+            issues.push(ValidationIssue::SoundEffectMismatch {
+                object: object_index,
+                effect_index,
+            });
+        }
+    }
+
+    issues
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LevelNameIssue {
+    // Longer than Level::MAX_NAME_LENGTH UCS-2 units
+    TooLong { length: usize },
+    // Can't be stored, either outside UCS-2 or the zero terminator. Index is in characters
+    UnsupportedCharacter { index: usize, character: char },
+    // Stored fine, but the console shows nothing sensible for it
+    ControlCharacter { index: usize, character: char },
+}
+
+impl LevelNameIssue {
+    // Whether the name can't be written at all, rather than just showing oddly
+    pub fn is_fatal(&self) -> bool {
+        !matches!(self, LevelNameIssue::ControlCharacter { .. })
+    }
+}
+
+pub fn validate_level_name(name: &str) -> Vec<LevelNameIssue> {
+    let mut issues = Vec::new();
+
+    let length = name.encode_utf16().count();
+    if length > Level::MAX_NAME_LENGTH {
+        issues.push(LevelNameIssue::TooLong { length });
+    }
+    for (index, character) in name.chars().enumerate() {
+        if character == '\0' || character.len_utf16() != 1 {
+            issues.push(LevelNameIssue::UnsupportedCharacter { index, character });
+        } else if character.is_control() {
+            issues.push(LevelNameIssue::ControlCharacter { index, character });
+        }
+    }
+
+    issues
+}
+
+// Disagreements between the two areas of a course, which load incorrectly on console
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConsistencyIssue {
+    GameModeMismatch { main: GameMode, sub: GameMode },
+    VersionMismatch { main: u64, sub: u64 },
+    // Sub areas have no start or goal, the object is in the sub area
+    StartInSubArea { object: usize },
+    GoalInSubArea { object: usize },
+}
+
+pub fn consistency_issues(main: &Level, sub: &Level) -> Vec<ConsistencyIssue> {
+    let mut issues = Vec::new();
+    if main.game_mode != sub.game_mode {
+        issues.push(ConsistencyIssue::GameModeMismatch {
+            main: main.game_mode,
+            sub: sub.game_mode,
+        });
+    }
+    if main.version != sub.version {
+        issues.push(ConsistencyIssue::VersionMismatch {
+            main: main.version,
+            sub: sub.version,
+        });
+    }
+
+    for (index, object) in sub.objects.iter().enumerate() {
+        match object.get_object_type() {
+            Some(ObjectType::StartBlock | ObjectType::StartArrow) => {
+                issues.push(ConsistencyIssue::StartInSubArea { object: index })
+            }
+            Some(ObjectType::Goal | ObjectType::GoalGround) => {
+                issues.push(ConsistencyIssue::GoalInSubArea { object: index })
+            }
+            _ => {}
+        }
+    }
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        fixtures::{minimal_level, minimal_sub_level},
+        level::{CourseTheme, GameMode},
+        objects::Object,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_validate_level_name() {
+        assert!(validate_level_name("Mario's Castle").is_empty());
+        assert_eq!(
+            validate_level_name(&"ab".repeat(17)),
+            vec![LevelNameIssue::TooLong { length: 34 }]
+        );
+        assert_eq!(
+            validate_level_name("a\tb🍄\0"),
+            vec![
+                LevelNameIssue::ControlCharacter {
+                    index: 1,
+                    character: '\t'
+                },
+                LevelNameIssue::UnsupportedCharacter {
+                    index: 3,
+                    character: '🍄'
+                },
+                LevelNameIssue::UnsupportedCharacter {
+                    index: 4,
+                    character: '\0'
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_checkpoint_issues() {
+        let mut level = minimal_level(GameMode::SuperMarioBros, CourseTheme::Overworld);
+        let checkpoint = Object::of(ObjectType::CheckpointFlag).unwrap();
+        let objects = level.objects.len();
+        for x in [2, 8, 10, 20] {
+            level.objects.push(checkpoint.clone().at(x, 1));
+        }
+
+        assert_eq!(
+            level.checkpoints()[1..3],
+            [(objects + 1, 8, 1), (objects + 2, 10, 1)]
+        );
+        assert_eq!(
+            checkpoint_issues(&level),
+            vec![
+                ValidationIssue::TooManyCheckpoints { count: 4 },
+                ValidationIssue::CheckpointPlacement { object: objects },
+                ValidationIssue::CheckpointPlacement {
+                    object: objects + 3
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_consistency_issues() {
+        let main = minimal_level(GameMode::Mario3, CourseTheme::Overworld);
+        let mut sub = minimal_sub_level(GameMode::Mario3, CourseTheme::Underground);
+        assert!(consistency_issues(&main, &sub).is_empty());
+
+        sub.game_mode = GameMode::MarioWorld;
+        sub.version = 0xA;
+        sub.objects
+            .push(Object::with_type(ObjectType::Goal).at(10, 1));
+        assert_eq!(
+            consistency_issues(&main, &sub),
+            vec![
+                ConsistencyIssue::GameModeMismatch {
+                    main: GameMode::Mario3,
+                    sub: GameMode::MarioWorld
+                },
+                ConsistencyIssue::VersionMismatch {
+                    main: 0xB,
+                    sub: 0xA
+                },
+                ConsistencyIssue::GoalInSubArea {
+                    object: sub.objects.len() - 1
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_style_issues() {
+        let mut level = minimal_level(GameMode::SuperMarioBros, CourseTheme::Overworld);
+        let block = Object::question_block(6, 4, Some(ObjectType::MysteryMushroom));
+        level.objects.push(block);
+        assert!(style_issues(&level).is_empty());
+
+        level.game_mode = GameMode::MarioWorld;
+        assert_eq!(
+            style_issues(&level),
+            vec![ValidationIssue::UnavailableInStyle {
+                object: level.objects.len() - 1,
+                object_type: ObjectType::MysteryMushroom,
+                game_mode: GameMode::MarioWorld,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_link_limit_issues() {
+        let mut level = minimal_level(GameMode::MarioWorld, CourseTheme::GhostHouse);
+        let objects = level.objects.len();
+        for link_id in 0..=Level::MAX_DOOR_PAIRS as i16 {
+            for x in [2, 20] {
+                let mut door = Object::of(ObjectType::Door).unwrap().at(x, 1);
+                door.link_id = link_id;
+                level.objects.push(door);
+            }
+        }
+        for link_id in 0..=Level::MAX_WARP_PIPES as i16 {
+            let mut pipe = Object::pipe(link_id as u32, 1, 2, None);
+            pipe.link_id = link_id;
+            level.objects.push(pipe);
+        }
+        // Plain pipes don't count
+        level.objects.push(Object::pipe(12, 1, 2, None));
+        assert!(door_link_issues(&level).is_empty());
+
+        let last_door = objects + 2 * Level::MAX_DOOR_PAIRS;
+        assert_eq!(
+            link_limit_issues(&level),
+            vec![
+                ValidationIssue::TooManyDoors {
+                    object: last_door,
+                    link_id: 10,
+                },
+                ValidationIssue::TooManyDoors {
+                    object: last_door + 1,
+                    link_id: 10,
+                },
+                ValidationIssue::TooManyWarpPipes {
+                    object: last_door + 2 + Level::MAX_WARP_PIPES,
+                    link_id: 10,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_orientation_issues() {
+        let mut level = minimal_level(GameMode::Mario3, CourseTheme::Airship);
+        let objects = level.objects.len();
+        let mut burner = Object::of(ObjectType::Burner).unwrap().at(4, 6);
+        burner.set_orientation(Orientation::Right).unwrap();
+        level.objects.push(burner);
+        let mut blaster = Object::of(ObjectType::BillBlaster).unwrap().at(8, 1);
+        blaster.height = 2;
+        level.objects.push(blaster.clone());
+        assert!(orientation_issues(&level).is_empty());
+
+        // Diagonal flags on a burner, and a blaster hanging from nothing
+        level.objects[objects].object_flags = 0x0100_0000;
+        blaster.set_orientation(Orientation::Down).unwrap();
+        level.objects.push(blaster);
+        assert_eq!(
+            orientation_issues(&level),
+            vec![
+                ValidationIssue::InvalidOrientation {
+                    object: objects,
+                    object_type: ObjectType::Burner,
+                    orientation: Orientation::UpRight,
+                },
+                ValidationIssue::UnattachedOrientation {
+                    object: objects + 2,
+                    orientation: Orientation::Down,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_object_bounds_issues() {
+        let mut level = minimal_level(GameMode::SuperMarioBros, CourseTheme::Overworld);
+        let objects = level.objects.len();
+        level.objects.push(Object::pipe(22, 25, 4, None));
+        level.objects.push(Object::goomba(3, -2));
+
+        assert_eq!(
+            object_bounds_issues(&level),
+            vec![
+                ValidationIssue::ObjectOutOfBounds {
+                    object: objects,
+                    x: 22,
+                    y: 25,
+                    suggested_x: 22,
+                    suggested_y: 23,
+                },
+                ValidationIssue::ObjectOutOfBounds {
+                    object: objects + 1,
+                    x: 3,
+                    y: -2,
+                    suggested_x: 3,
+                    suggested_y: 0,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sound_effect_issues() {
+        let mut level = minimal_level(GameMode::SuperMarioBros, CourseTheme::Overworld);
+        assert!(validate(&level).is_empty());
+
+        let mut goomba = Object::goomba(5, 1);
+        goomba.effect_index = 0;
+        level.objects.push(goomba.clone());
+        level.sound_effects[0].x_position = 5;
+        level.sound_effects[0].y_position = 1;
+        assert!(validate(&level).is_empty());
+
+        let object = level.objects.len();
+        goomba.effect_index = 300;
+        level.objects.push(goomba);
+        level.sound_effects[0].x_position = 200;
+        assert_eq!(
+            validate(&level),
+            vec![
+                ValidationIssue::SoundEffectOutOfBounds {
+                    effect_index: 0,
+                    x: 200,
+                    y: 1
+                },
+                ValidationIssue::SoundEffectMismatch {
+                    object: object - 1,
+                    effect_index: 0
+                },
+                ValidationIssue::MissingSoundEffect {
+                    object,
+                    effect_index: 300
+                },
+            ]
+        );
+    }
+}
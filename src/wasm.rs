@@ -0,0 +1,249 @@
+use wasm_bindgen::prelude::*;
+
+use crate::{course::Course, level::Level, objects::Object, thumbnail::Thumbnail, Error};
+
+fn to_js_error(error: Error) -> JsError {
+    JsError::new(&format!("{:?}", error))
+}
+
+#[wasm_bindgen(js_name = Level)]
+pub struct WasmLevel {
+    level: Level,
+}
+
+#[wasm_bindgen(js_class = Level)]
+impl WasmLevel {
+    // Parses the bytes of a course_data.cdt or course_data_sub.cdt
+    #[wasm_bindgen(constructor)]
+    pub fn new(bytes: &[u8]) -> Result<WasmLevel, JsError> {
+        Ok(WasmLevel {
+            level: Level::from_bytes(bytes).map_err(to_js_error)?,
+        })
+    }
+
+    #[wasm_bindgen(js_name = toBytes)]
+    pub fn to_bytes(&self) -> Result<Vec<u8>, JsError> {
+        self.level.to_bytes().map_err(to_js_error)
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn name(&self) -> String {
+        self.level.level_name.clone()
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_name(&mut self, name: String) {
+        self.level.level_name = name;
+    }
+
+    #[wasm_bindgen(getter, js_name = creationTime)]
+    pub fn creation_time(&self) -> String {
+        self.level.creation_time.to_string()
+    }
+
+    #[wasm_bindgen(getter, js_name = gameMode)]
+    pub fn game_mode(&self) -> u8 {
+        self.level.game_mode.into()
+    }
+
+    #[wasm_bindgen(getter, js_name = courseTheme)]
+    pub fn course_theme(&self) -> u8 {
+        self.level.course_theme.into()
+    }
+
+    #[wasm_bindgen(getter, js_name = timeLimit)]
+    pub fn time_limit(&self) -> u16 {
+        self.level.time_limit
+    }
+
+    #[wasm_bindgen(setter, js_name = timeLimit)]
+    pub fn set_time_limit(&mut self, time_limit: u16) {
+        self.level.time_limit = time_limit;
+    }
+
+    #[wasm_bindgen(getter, js_name = autoScroll)]
+    pub fn auto_scroll(&self) -> u8 {
+        self.level.auto_scroll.into()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn width(&self) -> u32 {
+        self.level.width
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn objects(&self) -> Vec<WasmObject> {
+        self.level
+            .objects
+            .iter()
+            .cloned()
+            .map(|object| WasmObject { object })
+            .collect()
+    }
+}
+
+#[wasm_bindgen(js_name = LevelObject)]
+pub struct WasmObject {
+    object: Object,
+}
+
+#[wasm_bindgen(js_class = LevelObject)]
+impl WasmObject {
+    #[wasm_bindgen(getter)]
+    pub fn x(&self) -> u32 {
+        self.object.x_position
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn y(&self) -> i16 {
+        self.object.y_position
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn z(&self) -> u32 {
+        self.object.z_position
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn width(&self) -> i8 {
+        self.object.width
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn height(&self) -> i8 {
+        self.object.height
+    }
+
+    #[wasm_bindgen(getter, js_name = objectType)]
+    pub fn object_type(&self) -> i8 {
+        self.object.object_type
+    }
+
+    #[wasm_bindgen(getter, js_name = childObjectType)]
+    pub fn child_object_type(&self) -> i8 {
+        self.object.child_object_type
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn flags(&self) -> u32 {
+        self.object.object_flags
+    }
+
+    #[wasm_bindgen(getter, js_name = linkId)]
+    pub fn link_id(&self) -> i16 {
+        self.object.link_id
+    }
+}
+
+#[wasm_bindgen(js_name = Course)]
+pub struct WasmCourse {
+    course: Course,
+}
+
+#[wasm_bindgen(js_class = Course)]
+impl WasmCourse {
+    // Parses a tar holding the four course files
+    #[wasm_bindgen(js_name = fromTar)]
+    pub fn from_tar(bytes: &[u8]) -> Result<WasmCourse, JsError> {
+        Ok(WasmCourse {
+            course: Course::from_tar(&mut tar::Archive::new(bytes)).map_err(to_js_error)?,
+        })
+    }
+
+    #[wasm_bindgen(js_name = toTar)]
+    pub fn to_tar(&self) -> Result<Vec<u8>, JsError> {
+        let mut builder = tar::Builder::new(Vec::new());
+        self.course.to_tar(&mut builder).map_err(to_js_error)?;
+        builder
+            .into_inner()
+            .map_err(|error| to_js_error(Error::Io(error)))
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn level(&self) -> WasmLevel {
+        WasmLevel {
+            level: self.course.level.clone(),
+        }
+    }
+
+    #[wasm_bindgen(getter, js_name = subLevel)]
+    pub fn sub_level(&self) -> WasmLevel {
+        WasmLevel {
+            level: self.course.sub_level.clone(),
+        }
+    }
+
+    #[wasm_bindgen(getter, js_name = previewJpeg)]
+    pub fn preview_jpeg(&self) -> Vec<u8> {
+        self.course.level_preview.jpeg_data.clone()
+    }
+
+    #[wasm_bindgen(getter, js_name = thumbnailJpeg)]
+    pub fn thumbnail_jpeg(&self) -> Vec<u8> {
+        self.course.level_thumbnail.jpeg_data.clone()
+    }
+}
+
+// Extracts the JPEG image held by a .tnl file
+#[wasm_bindgen(js_name = thumbnailJpeg)]
+pub fn thumbnail_jpeg(bytes: &[u8]) -> Result<Vec<u8>, JsError> {
+    Ok(Thumbnail::from_bytes(bytes).map_err(to_js_error)?.jpeg_data)
+}
+
+// Run with `wasm-pack test --node -- --features wasm`, JsError can't be built on other targets
+#[cfg(all(test, target_arch = "wasm32"))]
+mod tests {
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    use crate::{
+        fixtures::minimal_course,
+        level::{CourseTheme, GameMode},
+    };
+
+    use super::*;
+
+    fn course_tar() -> Vec<u8> {
+        let course = minimal_course(GameMode::MarioWorld, CourseTheme::Underground);
+        let mut builder = tar::Builder::new(Vec::new());
+        course.to_tar(&mut builder).unwrap();
+        builder.into_inner().unwrap()
+    }
+
+    #[wasm_bindgen_test]
+    fn test_round_trip() {
+        let tar = course_tar();
+        let course = WasmCourse::from_tar(&tar).unwrap();
+        assert_eq!(
+            WasmCourse::from_tar(&course.to_tar().unwrap())
+                .unwrap()
+                .course,
+            course.course
+        );
+
+        let bytes = course.level().to_bytes().unwrap();
+        let mut level = WasmLevel::new(&bytes).unwrap();
+        assert_eq!(level.to_bytes().unwrap(), bytes);
+        assert_eq!(level.game_mode(), u8::from(GameMode::MarioWorld));
+        assert_eq!(level.objects().len(), course.course.level.objects.len());
+
+        level.set_name("Renamed".to_string());
+        level.set_time_limit(100);
+        let level = WasmLevel::new(&level.to_bytes().unwrap()).unwrap();
+        assert_eq!(
+            (level.name().as_str(), level.time_limit()),
+            ("Renamed", 100)
+        );
+
+        let thumbnail = course.course.level_thumbnail.to_bytes().unwrap();
+        assert_eq!(thumbnail_jpeg(&thumbnail).unwrap(), course.thumbnail_jpeg());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_bad_input() {
+        assert!(WasmLevel::new(&[0; 16]).is_err());
+        assert!(WasmLevel::new(&[]).is_err());
+        assert!(WasmCourse::from_tar(&[0; 0x200]).is_err());
+        assert!(WasmCourse::from_tar(&course_tar()[..0x400]).is_err());
+        assert!(thumbnail_jpeg(&[0; 16]).is_err());
+    }
+}